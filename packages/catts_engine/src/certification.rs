@@ -0,0 +1,378 @@
+use crate::{
+    change_log::{self, ChangeLogItem},
+    http_error::HttpError,
+    http_gateway::{
+        recipe_badge, recipe_badge_path, recipe_path, run_badge, run_badge_path, run_path,
+        to_json_body, HeaderField,
+    },
+    recipe::{Recipe, RecipeId},
+    run::{Run, RunId},
+    CHANGE_LOG, RECIPES, RUNS,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use candid::{CandidType, Deserialize};
+use ic_certified_map::{AsHashTree, Hash, HashTree, RbTree};
+use ic_stable_structures::Storable;
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+use std::{cell::RefCell, ops::Bound, time::Duration};
+
+const RECIPES_LABEL: &[u8] = b"recipes";
+const RUNS_LABEL: &[u8] = b"runs";
+const CHANGE_LOG_LABEL: &[u8] = b"change_log";
+
+/// HTTP gateway responses are certified using the v1 response verification scheme, the
+/// gateway looks up `sha256(body)` at `["http_assets", path]`.
+const HTTP_ASSETS_LABEL: &[u8] = b"http_assets";
+
+/// The change log head is stored after all item indices, so that a range witness that
+/// ends at the head also proves the total count.
+const CHANGE_LOG_HEAD_KEY: [u8; 9] = [0xff; 9];
+
+/// Number of recipes, runs or change log items added to the tree per message while it is
+/// rebuilt after an upgrade.
+const REBUILD_BATCH_SIZE: usize = 500;
+
+/// A query response that can be verified by the caller. `data` holds the candid encoded
+/// values, each one certified by a leaf of `witness`: `sha256(data[i])`. The witness
+/// root hash is the certified data of the canister, signed by `certificate`.
+#[derive(CandidType, Deserialize, Serialize, Clone)]
+pub struct CertifiedResponse {
+    pub data: Vec<ByteBuf>,
+    pub certificate: ByteBuf,
+    pub witness: ByteBuf,
+}
+
+/// Position of the tree rebuild, the last key added from each stable map.
+#[derive(Clone, Copy)]
+enum RebuildCursor {
+    Recipes(Option<RecipeId>),
+    Runs(Option<RunId>),
+    ChangeLog(Option<u64>),
+}
+
+thread_local! {
+    // Lives on the heap, rebuilt from stable memory on upgrade
+    static CERTIFIED_DATA: RefCell<RbTree<Vec<u8>, RbTree<Vec<u8>, Hash>>> =
+        RefCell::new(RbTree::new());
+
+    // Set while the tree is being rebuilt, certified responses are unavailable until then
+    static REBUILD_CURSOR: RefCell<Option<RebuildCursor>> = RefCell::new(None);
+}
+
+fn is_rebuilding() -> bool {
+    REBUILD_CURSOR.with_borrow(|cursor| cursor.is_some())
+}
+
+fn hash_bytes(bytes: &[u8]) -> Hash {
+    Sha256::digest(bytes).into()
+}
+
+fn change_log_key(index: u64) -> Vec<u8> {
+    index.to_be_bytes().to_vec()
+}
+
+fn insert(
+    tree: &mut RbTree<Vec<u8>, RbTree<Vec<u8>, Hash>>,
+    label: &[u8],
+    key: Vec<u8>,
+    hash: Hash,
+) {
+    if tree.get(label).is_none() {
+        tree.insert(label.to_vec(), RbTree::new());
+    }
+    tree.modify(label, |subtree| subtree.insert(key, hash));
+}
+
+fn insert_recipe(tree: &mut RbTree<Vec<u8>, RbTree<Vec<u8>, Hash>>, recipe: &Recipe) {
+    insert(
+        tree,
+        RECIPES_LABEL,
+        recipe.id.to_vec(),
+        hash_bytes(&recipe.to_bytes()),
+    );
+    insert(
+        tree,
+        HTTP_ASSETS_LABEL,
+        recipe_path(&recipe.name).into_bytes(),
+        hash_bytes(&to_json_body(recipe)),
+    );
+    insert(
+        tree,
+        HTTP_ASSETS_LABEL,
+        recipe_badge_path(&recipe.name).into_bytes(),
+        hash_bytes(&recipe_badge(recipe)),
+    );
+}
+
+fn insert_run(tree: &mut RbTree<Vec<u8>, RbTree<Vec<u8>, Hash>>, run: &Run) {
+    insert(
+        tree,
+        RUNS_LABEL,
+        run.id.to_vec(),
+        hash_bytes(&run.to_bytes()),
+    );
+    insert(
+        tree,
+        HTTP_ASSETS_LABEL,
+        run_path(run).into_bytes(),
+        hash_bytes(&to_json_body(run)),
+    );
+    insert(
+        tree,
+        HTTP_ASSETS_LABEL,
+        run_badge_path(run).into_bytes(),
+        hash_bytes(&run_badge(run)),
+    );
+}
+
+fn update_certified_data() {
+    if is_rebuilding() {
+        return;
+    }
+    CERTIFIED_DATA.with_borrow(|tree| ic_cdk::api::set_certified_data(&tree.root_hash()));
+}
+
+fn after<K>(last_key: Option<K>) -> (Bound<K>, Bound<K>) {
+    match last_key {
+        Some(key) => (Bound::Excluded(key), Bound::Unbounded),
+        None => (Bound::Unbounded, Bound::Unbounded),
+    }
+}
+
+/// Adds the next batch of recipes, runs or change log items to the tree and returns the
+/// updated cursor, `None` once the tree is complete.
+fn rebuild_batch(cursor: RebuildCursor) -> Option<RebuildCursor> {
+    CERTIFIED_DATA.with_borrow_mut(|tree| match cursor {
+        RebuildCursor::Recipes(last_id) => RECIPES.with_borrow(|recipes| {
+            let mut last_id = last_id;
+            for (id, recipe) in recipes.range(after(last_id)).take(REBUILD_BATCH_SIZE) {
+                insert_recipe(tree, &recipe);
+                last_id = Some(id);
+            }
+            match recipes.range(after(last_id)).next() {
+                Some(_) => Some(RebuildCursor::Recipes(last_id)),
+                None => Some(RebuildCursor::Runs(None)),
+            }
+        }),
+        RebuildCursor::Runs(last_id) => RUNS.with_borrow(|runs| {
+            let mut last_id = last_id;
+            for (id, run) in runs.range(after(last_id)).take(REBUILD_BATCH_SIZE) {
+                insert_run(tree, &run);
+                last_id = Some(id);
+            }
+            match runs.range(after(last_id)).next() {
+                Some(_) => Some(RebuildCursor::Runs(last_id)),
+                None => Some(RebuildCursor::ChangeLog(None)),
+            }
+        }),
+        RebuildCursor::ChangeLog(last_index) => CHANGE_LOG.with_borrow(|log| {
+            let mut last_index = last_index;
+            for (index, item) in log.range(after(last_index)).take(REBUILD_BATCH_SIZE) {
+                insert(
+                    tree,
+                    CHANGE_LOG_LABEL,
+                    change_log_key(index),
+                    hash_bytes(&item.to_bytes()),
+                );
+                last_index = Some(index);
+            }
+            if log.range(after(last_index)).next().is_some() {
+                return Some(RebuildCursor::ChangeLog(last_index));
+            }
+            insert(
+                tree,
+                CHANGE_LOG_LABEL,
+                CHANGE_LOG_HEAD_KEY.to_vec(),
+                hash_bytes(&change_log::next_index().to_be_bytes()),
+            );
+            None
+        }),
+    })
+}
+
+fn schedule_rebuild_batch() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || {
+        let Some(cursor) = REBUILD_CURSOR.with_borrow(|cursor| *cursor) else {
+            return;
+        };
+        let cursor = rebuild_batch(cursor);
+        REBUILD_CURSOR.with_borrow_mut(|rebuild_cursor| *rebuild_cursor = cursor);
+        match cursor {
+            Some(_) => schedule_rebuild_batch(),
+            None => update_certified_data(),
+        }
+    });
+}
+
+/// Rebuilds the certified data tree from the recipes, runs and change log in stable
+/// memory. The tree is rebuilt in batches from timers so that the work of an upgrade
+/// doesn't grow with the stored data. Entities changed in the meantime are added to the
+/// tree as usual. Certified responses are unavailable until the rebuild is done.
+pub fn init_certified_data() {
+    CERTIFIED_DATA.with_borrow_mut(|tree| *tree = RbTree::new());
+    REBUILD_CURSOR.with_borrow_mut(|cursor| *cursor = Some(RebuildCursor::Recipes(None)));
+    schedule_rebuild_batch();
+}
+
+pub fn certify_recipe(recipe: &Recipe) {
+    CERTIFIED_DATA.with_borrow_mut(|tree| insert_recipe(tree, recipe));
+    update_certified_data();
+}
+
+pub fn uncertify_recipe(recipe: &Recipe) {
+    CERTIFIED_DATA.with_borrow_mut(|tree| {
+        tree.modify(RECIPES_LABEL, |subtree| subtree.delete(&recipe.id));
+        tree.modify(HTTP_ASSETS_LABEL, |subtree| {
+            subtree.delete(recipe_path(&recipe.name).as_bytes());
+            subtree.delete(recipe_badge_path(&recipe.name).as_bytes());
+        });
+    });
+    update_certified_data();
+}
+
+pub fn certify_run(run: &Run) {
+    CERTIFIED_DATA.with_borrow_mut(|tree| insert_run(tree, run));
+    update_certified_data();
+}
+
+pub fn certify_change_log_item(index: u64, item: &ChangeLogItem) {
+    CERTIFIED_DATA.with_borrow_mut(|tree| {
+        insert(
+            tree,
+            CHANGE_LOG_LABEL,
+            change_log_key(index),
+            hash_bytes(&item.to_bytes()),
+        );
+        insert(
+            tree,
+            CHANGE_LOG_LABEL,
+            CHANGE_LOG_HEAD_KEY.to_vec(),
+            hash_bytes(&(index + 1).to_be_bytes()),
+        );
+    });
+    update_certified_data();
+}
+
+/// Updates the hash of a rewritten change log entry, the head is kept.
+pub fn recertify_change_log_item(index: u64, item: &ChangeLogItem) {
+    CERTIFIED_DATA.with_borrow_mut(|tree| {
+        insert(
+            tree,
+            CHANGE_LOG_LABEL,
+            change_log_key(index),
+            hash_bytes(&item.to_bytes()),
+        );
+    });
+    update_certified_data();
+}
+
+/// Removes compacted change log entries from the tree.
+pub fn uncertify_change_log_items(indices: &[u64]) {
+    CERTIFIED_DATA.with_borrow_mut(|tree| {
+        tree.modify(CHANGE_LOG_LABEL, |subtree| {
+            for index in indices {
+                subtree.delete(&change_log_key(*index));
+            }
+        });
+    });
+    update_certified_data();
+}
+
+fn serialize_witness(witness: &HashTree) -> ByteBuf {
+    let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+    serializer.self_describe().unwrap();
+    witness.serialize(&mut serializer).unwrap();
+    ByteBuf::from(serializer.into_inner())
+}
+
+fn certified_response(
+    data: Vec<ByteBuf>,
+    witness: &HashTree,
+) -> Result<CertifiedResponse, HttpError> {
+    if is_rebuilding() {
+        return Err(HttpError::service_unavailable(
+            "Certified data is being rebuilt, try again shortly",
+        ));
+    }
+    let certificate = ic_cdk::api::data_certificate().ok_or_else(|| {
+        HttpError::internal_server_error("Certificate is only available in query calls")
+    })?;
+
+    Ok(CertifiedResponse {
+        data,
+        certificate: ByteBuf::from(certificate),
+        witness: serialize_witness(witness),
+    })
+}
+
+pub fn certified_recipe(recipe: &Recipe) -> Result<CertifiedResponse, HttpError> {
+    CERTIFIED_DATA.with_borrow(|tree| {
+        let witness = tree.nested_witness(RECIPES_LABEL, |subtree| subtree.witness(&recipe.id));
+        certified_response(vec![ByteBuf::from(recipe.to_bytes().to_vec())], &witness)
+    })
+}
+
+pub fn certified_run(run: &Run) -> Result<CertifiedResponse, HttpError> {
+    CERTIFIED_DATA.with_borrow(|tree| {
+        let witness = tree.nested_witness(RUNS_LABEL, |subtree| subtree.witness(&run.id));
+        certified_response(vec![ByteBuf::from(run.to_bytes().to_vec())], &witness)
+    })
+}
+
+/// Certifies a range of change log items. When the range ends at the last item, the
+/// witness includes the change log head, which proves the total count.
+pub fn certified_change_log_items(
+    start_index: u64,
+    items: &[ChangeLogItem],
+    total_count: u64,
+) -> Result<CertifiedResponse, HttpError> {
+    let end_index = start_index + items.len() as u64;
+    let last_key = if end_index >= total_count {
+        CHANGE_LOG_HEAD_KEY.to_vec()
+    } else {
+        change_log_key(end_index.saturating_sub(1))
+    };
+
+    CERTIFIED_DATA.with_borrow(|tree| {
+        let witness = tree.nested_witness(CHANGE_LOG_LABEL, |subtree| {
+            subtree.value_range(&change_log_key(start_index), &last_key)
+        });
+        let data = items
+            .iter()
+            .map(|item| ByteBuf::from(item.to_bytes().to_vec()))
+            .collect();
+        certified_response(data, &witness)
+    })
+}
+
+/// Returns the `IC-Certificate` header for a HTTP gateway response, if the response body
+/// at `path` is certified. Responses that are not certified are served without the header.
+pub fn http_certificate_header(path: &str, body: &[u8]) -> Option<HeaderField> {
+    if is_rebuilding() {
+        return None;
+    }
+    CERTIFIED_DATA.with_borrow(|tree| {
+        let certified_hash = tree
+            .get(HTTP_ASSETS_LABEL)
+            .and_then(|subtree| subtree.get(path.as_bytes()))?;
+        if *certified_hash != hash_bytes(body) {
+            return None;
+        }
+
+        let certificate = ic_cdk::api::data_certificate()?;
+        let witness = tree.nested_witness(HTTP_ASSETS_LABEL, |subtree| {
+            subtree.witness(path.as_bytes())
+        });
+
+        Some((
+            "IC-Certificate".to_string(),
+            format!(
+                "certificate=:{}:, tree=:{}:",
+                BASE64.encode(certificate),
+                BASE64.encode(serialize_witness(&witness)),
+            ),
+        ))
+    })
+}
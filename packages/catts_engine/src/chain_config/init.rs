@@ -1,43 +1,90 @@
+use candid::Nat;
+
 use crate::{
     declarations::evm_rpc::{EthSepoliaService, L2MainnetService, RpcService, RpcServices},
-    CHAIN_CONFIGS,
+    CHAIN_CONFIGS, CHAIN_CONFIGS_INITIALISED,
 };
 
 use super::ChainConfig;
 
+fn default_chain_configs() -> Vec<ChainConfig> {
+    vec![
+        ChainConfig {
+            chain_id: 11155111, // Sepolia
+            eth_usd_price: "0x123".to_string(),
+            rpc_api_endpoint: "https://catts-evm-proxy-2.kristofer-977.workers.dev/eth-sepolia"
+                .to_string(),
+            eas_contract: "0xC2679fBD37d54388Ce493F1DB75320D236e1815e".to_string(),
+            payment_contract: "0xe498539Cad0E4325b88d6F6a1B89af7e4C8dF404".to_string(),
+            rpc_services: RpcServices::EthSepolia(Some(vec![
+                EthSepoliaService::Ankr,
+                EthSepoliaService::BlockPi,
+            ])),
+            default_rpc_service: RpcService::EthSepolia(EthSepoliaService::BlockPi),
+            min_user_fee: Some(Nat::from(500000000000000_u64)), // 0.0005 ETH
+            min_offchain_user_fee: Some(Nat::from(50000000000000_u64)), // 0.00005 ETH
+            fee_margin_percent: None,
+            disabled: None,
+            transaction_type: None,
+            block_time_ms: None,
+            confirmations: None,
+            evm_client: None,
+        },
+        ChainConfig {
+            chain_id: 10, // Optimism
+            eth_usd_price: "0x123".to_string(),
+            rpc_api_endpoint: "https://catts-evm-proxy-2.kristofer-977.workers.dev/opt-mainnet"
+                .to_string(),
+            eas_contract: "0x4200000000000000000000000000000000000021".to_string(),
+            payment_contract: "0x15a9a0f3bf24f9ff438f18f83ecc8b7cb2e15f9a".to_string(),
+            rpc_services: RpcServices::OptimismMainnet(Some(vec![
+                L2MainnetService::Ankr,
+                L2MainnetService::BlockPi,
+            ])),
+            default_rpc_service: RpcService::OptimismMainnet(L2MainnetService::BlockPi),
+            min_user_fee: Some(Nat::from(50000000000000_u64)), // 0.00005 ETH
+            min_offchain_user_fee: Some(Nat::from(5000000000000_u64)), // 0.000005 ETH
+            fee_margin_percent: None,
+            disabled: None,
+            transaction_type: None,
+            block_time_ms: None,
+            confirmations: None,
+            evm_client: None,
+        },
+    ]
+}
+
+/// Adds the default chain configs on first install, recorded in a flag so that configs
+/// removed at runtime are not added back. Configs changed at runtime are kept on upgrade,
+/// only missing minimum fees are filled in from the defaults. Canisters installed before the
+/// flag was introduced count as initialised once they hold a config.
 pub fn init_chain_configs() {
+    let is_first_install = !CHAIN_CONFIGS_INITIALISED.with_borrow(|initialised| *initialised.get())
+        && CHAIN_CONFIGS.with_borrow(|configs| configs.is_empty());
+
     CHAIN_CONFIGS.with_borrow_mut(|configs| {
-        configs.insert(
-            11155111, // Sepolia
-            ChainConfig {
-                chain_id: 11155111,
-                eth_usd_price: "0x123".to_string(),
-                rpc_api_endpoint: "https://catts-evm-proxy-2.kristofer-977.workers.dev/eth-sepolia"
-                    .to_string(),
-                eas_contract: "0xC2679fBD37d54388Ce493F1DB75320D236e1815e".to_string(),
-                payment_contract: "0xe498539Cad0E4325b88d6F6a1B89af7e4C8dF404".to_string(),
-                rpc_services: RpcServices::EthSepolia(Some(vec![
-                    EthSepoliaService::Ankr,
-                    EthSepoliaService::BlockPi,
-                ])),
-                default_rpc_service: RpcService::EthSepolia(EthSepoliaService::BlockPi),
-            },
-        );
-        configs.insert(
-            10, // Optimism
-            ChainConfig {
-                chain_id: 10,
-                eth_usd_price: "0x123".to_string(),
-                rpc_api_endpoint: "https://catts-evm-proxy-2.kristofer-977.workers.dev/opt-mainnet"
-                    .to_string(),
-                eas_contract: "0x4200000000000000000000000000000000000021".to_string(),
-                payment_contract: "0x15a9a0f3bf24f9ff438f18f83ecc8b7cb2e15f9a".to_string(),
-                rpc_services: RpcServices::OptimismMainnet(Some(vec![
-                    L2MainnetService::Ankr,
-                    L2MainnetService::BlockPi,
-                ])),
-                default_rpc_service: RpcService::OptimismMainnet(L2MainnetService::BlockPi),
-            },
-        );
+        for default in default_chain_configs() {
+            match configs.get(&default.chain_id) {
+                Some(mut config) => {
+                    if config.min_user_fee.is_none() || config.min_offchain_user_fee.is_none() {
+                        config.min_user_fee = config.min_user_fee.or(default.min_user_fee);
+                        config.min_offchain_user_fee = config
+                            .min_offchain_user_fee
+                            .or(default.min_offchain_user_fee);
+                        configs.insert(config.chain_id, config);
+                    }
+                }
+                None if is_first_install => {
+                    configs.insert(default.chain_id, default);
+                }
+                None => {}
+            }
+        }
+    });
+
+    CHAIN_CONFIGS_INITIALISED.with_borrow_mut(|initialised| {
+        initialised
+            .set(true)
+            .expect("Failed to save chain configs flag");
     });
 }
@@ -1,4 +1,5 @@
 pub mod init;
+pub mod rpc;
 pub mod state;
 pub mod types;
 
@@ -0,0 +1,15 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    http_error::HttpError,
+    role::controller_guard,
+};
+use ic_cdk::update;
+
+/// Disables or re-enables a chain. Disabled chains accept no new runs, runs in progress
+/// are still processed. Only controllers can disable chains.
+#[update]
+fn chain_config_disable(chain_id: u32, disabled: bool) -> Result<ChainConfig, HttpError> {
+    controller_guard()?;
+
+    chain_config::set_disabled(chain_id, disabled).map_err(HttpError::not_found)
+}
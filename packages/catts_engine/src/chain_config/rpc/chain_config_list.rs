@@ -0,0 +1,13 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    http_error::HttpError,
+    role::{require_role, Role},
+};
+use ic_cdk::query;
+
+#[query]
+fn chain_config_list() -> Result<Vec<ChainConfig>, HttpError> {
+    require_role(Role::Auditor)?;
+
+    Ok(chain_config::list())
+}
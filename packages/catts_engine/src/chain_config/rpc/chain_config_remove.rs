@@ -0,0 +1,22 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    http_error::HttpError,
+    role::controller_guard,
+    run,
+};
+use ic_cdk::update;
+
+/// Removes the config of a chain. Chains with runs that are not yet attested or cancelled
+/// can only be disabled. Only controllers can remove chains.
+#[update]
+fn chain_config_remove(chain_id: u32) -> Result<ChainConfig, HttpError> {
+    controller_guard()?;
+
+    if run::has_open_runs(chain_id) {
+        return Err(HttpError::conflict(
+            "Chain has runs that are not attested or cancelled, disable it instead",
+        ));
+    }
+
+    chain_config::remove(chain_id).map_err(HttpError::not_found)
+}
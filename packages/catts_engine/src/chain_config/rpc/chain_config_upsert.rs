@@ -0,0 +1,14 @@
+use crate::{
+    chain_config::{self, ChainConfig},
+    http_error::HttpError,
+    role::controller_guard,
+};
+use ic_cdk::update;
+
+/// Adds or replaces the config of a chain. Only controllers can change chain configs.
+#[update]
+fn chain_config_upsert(config: ChainConfig) -> Result<ChainConfig, HttpError> {
+    controller_guard()?;
+
+    chain_config::set(config).map_err(HttpError::bad_request)
+}
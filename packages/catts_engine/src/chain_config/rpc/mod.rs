@@ -0,0 +1,4 @@
+pub mod chain_config_disable;
+pub mod chain_config_list;
+pub mod chain_config_remove;
+pub mod chain_config_upsert;
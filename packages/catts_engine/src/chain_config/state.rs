@@ -7,8 +7,38 @@ pub fn get(chain_id: u32) -> Result<ChainConfig, ChainConfigError> {
         .ok_or(ChainConfigError::NotFound)
 }
 
-pub fn _set(config: ChainConfig) {
+/// Returns the chain config if the chain accepts new runs.
+pub fn get_enabled(chain_id: u32) -> Result<ChainConfig, ChainConfigError> {
+    let config = get(chain_id)?;
+    if config.is_disabled() {
+        return Err(ChainConfigError::Disabled);
+    }
+    Ok(config)
+}
+
+pub fn list() -> Vec<ChainConfig> {
+    CHAIN_CONFIGS.with_borrow(|configs| configs.iter().map(|(_, config)| config).collect())
+}
+
+pub fn set(config: ChainConfig) -> Result<ChainConfig, ChainConfigError> {
+    config.validate()?;
+    CHAIN_CONFIGS.with_borrow_mut(|configs| {
+        configs.insert(config.chain_id, config.clone());
+    });
+    Ok(config)
+}
+
+pub fn set_disabled(chain_id: u32, disabled: bool) -> Result<ChainConfig, ChainConfigError> {
+    let mut config = get(chain_id)?;
+    config.disabled = Some(disabled);
     CHAIN_CONFIGS.with_borrow_mut(|configs| {
-        configs.insert(config.chain_id, config);
+        configs.insert(chain_id, config.clone());
     });
+    Ok(config)
+}
+
+pub fn remove(chain_id: u32) -> Result<ChainConfig, ChainConfigError> {
+    CHAIN_CONFIGS
+        .with_borrow_mut(|configs| configs.remove(&chain_id))
+        .ok_or(ChainConfigError::NotFound)
 }
@@ -1,15 +1,51 @@
 use std::borrow::Cow;
 
-use candid::{CandidType, Decode, Deserialize, Encode};
+use candid::{CandidType, Decode, Deserialize, Encode, Nat};
 use ic_stable_structures::{storable::Bound, Storable};
 use thiserror::Error;
 
-use crate::declarations::evm_rpc::{RpcService, RpcServices};
+use crate::{
+    declarations::evm_rpc::{RpcService, RpcServices},
+    eth_address::EthAddress,
+    url::is_https_or_localhost,
+};
+
+/// Gas estimates are increased by this margin unless the chain config sets its own
+pub const DEFAULT_FEE_MARGIN_PERCENT: u32 = 20;
+
+/// Transactions and payments are polled at this interval unless the chain config sets a
+/// block time
+pub const DEFAULT_POLL_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const MIN_POLL_INTERVAL: u64 = 1_000_000_000; // 1 second
+const MAX_POLL_INTERVAL: u64 = 60_000_000_000; // 1 minute
 
 #[derive(Error, Debug)]
 pub enum ChainConfigError {
     #[error("Chain config not found")]
     NotFound,
+    #[error("Chain is disabled")]
+    Disabled,
+    #[error("Invalid chain config: {0}")]
+    Invalid(String),
+}
+
+/// Chains without EIP-1559 are priced using `eth_gasPrice`. Legacy transactions are signed
+/// with EIP-155 replay protection, EIP-2930 transactions include an access list.
+#[derive(CandidType, Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum TransactionType {
+    Legacy,
+    Eip2930,
+    Eip1559,
+}
+
+/// How the canister accesses the chain. `EvmRpc` goes through the evm_rpc canister, which
+/// queries all `rpc_services` and compares their responses. `JsonRpc` sends requests
+/// directly to `rpc_api_endpoint` using HTTPS outcalls, the endpoint has to return the same
+/// response to all replicas.
+#[derive(CandidType, Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum EvmClientType {
+    EvmRpc,
+    JsonRpc,
 }
 
 #[derive(CandidType, Clone, Deserialize)]
@@ -21,6 +57,109 @@ pub struct ChainConfig {
     pub payment_contract: String,
     pub rpc_services: RpcServices,
     pub default_rpc_service: RpcService,
+
+    /// Minimum fee for on-chain runs, in wei
+    pub min_user_fee: Option<Nat>,
+
+    /// Minimum fee for off-chain runs, in wei
+    pub min_offchain_user_fee: Option<Nat>,
+
+    /// Added to the estimated gas usage of on-chain runs, in percent
+    pub fee_margin_percent: Option<u32>,
+
+    /// Disabled chains accept no new runs, runs in progress are still processed
+    pub disabled: Option<bool>,
+
+    /// Type of the transactions sent to the chain, defaults to EIP-1559
+    pub transaction_type: Option<TransactionType>,
+
+    /// Average block time, used to poll for payments and attestation transactions
+    pub block_time_ms: Option<u64>,
+
+    /// Blocks that have to be built on top of a payment or attestation transaction before
+    /// it is considered final, defaults to 0
+    pub confirmations: Option<u32>,
+
+    /// Client used to access the chain, defaults to the evm_rpc canister
+    pub evm_client: Option<EvmClientType>,
+}
+
+impl ChainConfig {
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.unwrap_or(false)
+    }
+
+    pub fn fee_margin_percent(&self) -> u32 {
+        self.fee_margin_percent
+            .unwrap_or(DEFAULT_FEE_MARGIN_PERCENT)
+    }
+
+    pub fn transaction_type(&self) -> TransactionType {
+        self.transaction_type.unwrap_or(TransactionType::Eip1559)
+    }
+
+    pub fn evm_client(&self) -> EvmClientType {
+        self.evm_client.unwrap_or(EvmClientType::EvmRpc)
+    }
+
+    /// Polling interval in nanoseconds, derived from the block time.
+    pub fn poll_interval(&self) -> u64 {
+        match self.block_time_ms {
+            Some(block_time_ms) => block_time_ms
+                .saturating_mul(1_000_000)
+                .clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL),
+            None => DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn confirmations(&self) -> u32 {
+        self.confirmations.unwrap_or(0)
+    }
+
+    pub fn validate(&self) -> Result<(), ChainConfigError> {
+        let invalid = |message: &str| Err(ChainConfigError::Invalid(message.to_string()));
+
+        if self.chain_id == 0 {
+            return invalid("Chain id must be greater than 0");
+        }
+        if EthAddress::new(&self.eas_contract).is_err() {
+            return invalid("EAS contract is not a valid address");
+        }
+        if EthAddress::new(&self.payment_contract).is_err() {
+            return invalid("Payment contract is not a valid address");
+        }
+        if !is_https_or_localhost(&self.rpc_api_endpoint) {
+            return invalid("RPC API endpoint must use https");
+        }
+        if let RpcServices::Custom { chainId, services } = &self.rpc_services {
+            if *chainId != self.chain_id as u64 {
+                return invalid("Custom RPC services must use the chain id of the config");
+            }
+            if services.is_empty() {
+                return invalid("Custom RPC services must not be empty");
+            }
+            if services
+                .iter()
+                .any(|service| !is_https_or_localhost(&service.url))
+            {
+                return invalid("Custom RPC services must use https");
+            }
+        }
+        if self.min_user_fee.is_none() || self.min_offchain_user_fee.is_none() {
+            return invalid("Minimum fees are required");
+        }
+        if self.fee_margin_percent() > 100 {
+            return invalid("Fee margin must be at most 100 percent");
+        }
+        if self.block_time_ms == Some(0) {
+            return invalid("Block time must be greater than 0");
+        }
+        if self.confirmations() > 64 {
+            return invalid("Confirmations must be at most 64");
+        }
+
+        Ok(())
+    }
 }
 
 impl Storable for ChainConfig {
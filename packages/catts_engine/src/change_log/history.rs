@@ -0,0 +1,160 @@
+use super::{
+    entity_indices, first_index, get, replace, scrub_snapshot_entities, ChangeLogAction,
+    ChangeLogError, ChangeLogTypeName, EntityVersion,
+};
+use crate::{CHANGE_LOG_SNAPSHOTS, CHANGE_LOG_SNAPSHOT_ENTITIES};
+use json_patch::Patch;
+use serde_json::Value;
+
+fn snapshot_entity(snapshot_index: u64, type_name: &ChangeLogTypeName, id: &str) -> Option<Value> {
+    CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow(|entities| {
+        entities
+            .range((snapshot_index, 0)..(snapshot_index + 1, 0))
+            .find(|(_, entity)| {
+                entity.type_name == *type_name && entity.id.eq_ignore_ascii_case(id)
+            })
+            .and_then(|(_, entity)| serde_json::from_str(&entity.data).ok())
+    })
+}
+
+/// Returns the entity state the history of an entity has to start from. The full history
+/// is available until the change log is compacted, after that the history starts at the
+/// oldest retained snapshot.
+fn history_base(
+    type_name: &ChangeLogTypeName,
+    id: &str,
+) -> Result<Option<EntityVersion>, ChangeLogError> {
+    let first_index = first_index();
+    if first_index == 0 {
+        return Ok(None);
+    }
+
+    let is_snapshot =
+        CHANGE_LOG_SNAPSHOTS.with_borrow(|snapshots| snapshots.contains_key(&first_index));
+    if !is_snapshot {
+        return Err(ChangeLogError::Compacted(first_index));
+    }
+
+    Ok(
+        snapshot_entity(first_index, type_name, id).map(|data| EntityVersion {
+            index: first_index as u32,
+            action: None,
+            data: data.to_string(),
+            snapshot: true,
+        }),
+    )
+}
+
+fn apply(data: &mut Value, index: u64) -> Result<ChangeLogAction, ChangeLogError> {
+    let item = get(index).ok_or(ChangeLogError::InvalidPatch(index))?;
+    match item.action {
+        ChangeLogAction::Create => {
+            *data = serde_json::from_str(&item.patch)
+                .map_err(|_| ChangeLogError::InvalidPatch(index))?;
+        }
+        ChangeLogAction::Update => {
+            let patch: Patch = serde_json::from_str(&item.patch)
+                .map_err(|_| ChangeLogError::InvalidPatch(index))?;
+            json_patch::patch(data, &patch).map_err(|_| ChangeLogError::InvalidPatch(index))?;
+        }
+        ChangeLogAction::Delete => *data = Value::Null,
+    }
+    Ok(item.action)
+}
+
+/// Rebuilds all versions of an entity by applying its change log entries in order. With
+/// `until_index` set, only entries up to and including that index are applied.
+fn versions(
+    type_name: &ChangeLogTypeName,
+    id: &str,
+    until_index: Option<u64>,
+) -> Result<Vec<EntityVersion>, ChangeLogError> {
+    let entity_key = type_name.entity_key(id)?;
+    let base = history_base(type_name, id)?;
+
+    let mut data = base
+        .as_ref()
+        .and_then(|version| serde_json::from_str(&version.data).ok())
+        .unwrap_or(Value::Null);
+    let mut versions: Vec<EntityVersion> = base.into_iter().collect();
+
+    for index in entity_indices(&entity_key) {
+        if until_index.is_some_and(|until_index| index > until_index) {
+            break;
+        }
+        let action = apply(&mut data, index)?;
+        versions.push(EntityVersion {
+            index: index as u32,
+            action: Some(action),
+            data: data.to_string(),
+            snapshot: false,
+        });
+    }
+
+    Ok(versions)
+}
+
+/// Returns the ordered versions of an entity.
+pub fn entity_history(
+    type_name: &ChangeLogTypeName,
+    id: &str,
+) -> Result<Vec<EntityVersion>, ChangeLogError> {
+    let versions = versions(type_name, id, None)?;
+    if versions.is_empty() {
+        return Err(ChangeLogError::NotFound);
+    }
+    Ok(versions)
+}
+
+/// Returns the version of an entity as of a change log index, all entries up to and
+/// including `index` are applied.
+pub fn entity_at(
+    type_name: &ChangeLogTypeName,
+    id: &str,
+    index: u64,
+) -> Result<EntityVersion, ChangeLogError> {
+    let first_index = first_index();
+    if index < first_index {
+        return Err(ChangeLogError::Compacted(first_index));
+    }
+
+    versions(type_name, id, Some(index))?
+        .pop()
+        .ok_or(ChangeLogError::NotFound)
+}
+
+/// Removes data from the retained history of an entity by applying `scrub` to every
+/// version, snapshots included. Entries keep their index and action, updates are diffed
+/// again from the scrubbed versions so that the history can still be replayed.
+pub fn scrub_entity(
+    type_name: &ChangeLogTypeName,
+    id: &str,
+    scrub: impl Fn(&mut Value),
+) -> Result<(), ChangeLogError> {
+    let entity_key = type_name.entity_key(id)?;
+    let mut data = history_base(type_name, id)?
+        .and_then(|version| serde_json::from_str(&version.data).ok())
+        .unwrap_or(Value::Null);
+    let mut scrubbed = data.clone();
+    scrub(&mut scrubbed);
+
+    for index in entity_indices(&entity_key) {
+        let mut item = get(index).ok_or(ChangeLogError::InvalidPatch(index))?;
+        apply(&mut data, index)?;
+        let mut next = data.clone();
+        scrub(&mut next);
+
+        item.patch = match item.action {
+            ChangeLogAction::Create => next.to_string(),
+            ChangeLogAction::Update => {
+                serde_json::to_string(&json_patch::diff(&scrubbed, &next)).unwrap()
+            }
+            ChangeLogAction::Delete => item.patch,
+        };
+        replace(index, item);
+        scrubbed = next;
+    }
+
+    scrub_snapshot_entities(type_name, id, scrub);
+    Ok(())
+}
@@ -1,6 +1,11 @@
+pub mod history;
 pub mod rpc;
+pub mod snapshot;
 pub mod state;
+pub mod tasks;
 pub mod types;
 
+pub use history::*;
+pub use snapshot::*;
 pub use state::*;
 pub use types::*;
@@ -1,42 +1,65 @@
 use crate::{
-    change_log::{ChangeLogResponse, IndexedChangeLogItem},
+    change_log::{self, ChangeLogFilter, ChangeLogResponse, IndexedChangeLogItem},
     http_error::HttpError,
-    CHANGE_LOG,
 };
 use ic_cdk::query;
+use ic_stable_structures::Storable;
+
+/// Responses are kept well below the query response size limit, also when patches are large.
+const MAX_RESPONSE_BYTES: usize = 1_500_000;
+
+/// Filtered queries may need to skip many items, the scan is limited to keep the query
+/// within the instruction limit. Clients continue from `next_index`.
+const MAX_SCANNED_ITEMS: u64 = 10_000;
 
 fn safe_u64_to_u32(value: u64) -> u32 {
     value.try_into().expect("Value exceeds u32::MAX")
 }
 
 #[query]
-fn change_log(start_index: u32, limit: Option<u32>) -> Result<ChangeLogResponse, HttpError> {
-    CHANGE_LOG.with_borrow(|log| {
-        let start_index = start_index as u64;
-        let limit = limit.map(|l| l as u64);
-        let total_count = log.len();
-        let end_index = limit.map_or(total_count, |l| std::cmp::min(start_index + l, total_count));
-
-        if start_index >= total_count {
-            return Ok(ChangeLogResponse {
-                total_count: safe_u64_to_u32(total_count),
-                data: Vec::new(),
+fn change_log(
+    start_index: u32,
+    limit: Option<u32>,
+    filter: Option<ChangeLogFilter>,
+) -> Result<ChangeLogResponse, HttpError> {
+    let filter = filter.unwrap_or_default();
+
+    let first_index = change_log::first_index();
+    if (start_index as u64) < first_index {
+        return Err(HttpError::bad_request(format!(
+            "Entries before index {} have been compacted, start from the latest snapshot",
+            first_index
+        )));
+    }
+
+    let total_count = change_log::next_index();
+    let limit = limit.map_or(usize::MAX, |l| l as usize);
+    let scan_end = std::cmp::min(start_index as u64 + MAX_SCANNED_ITEMS, total_count);
+
+    let mut data: Vec<IndexedChangeLogItem> = Vec::new();
+    let mut response_bytes = 0;
+    let mut index = start_index as u64;
+
+    while index < scan_end && data.len() < limit {
+        let item = change_log::get(index).unwrap();
+        if filter.matches(&item) {
+            let item_bytes = item.to_bytes().len();
+            // Always return at least one item, so that clients can make progress
+            if !data.is_empty() && response_bytes + item_bytes > MAX_RESPONSE_BYTES {
+                break;
+            }
+            response_bytes += item_bytes;
+            data.push(IndexedChangeLogItem {
+                index: safe_u64_to_u32(index),
+                data: item,
             });
         }
+        index += 1;
+    }
 
-        let data: Vec<IndexedChangeLogItem> = (start_index..end_index)
-            .map(|index| {
-                let item = log.get(index).unwrap();
-                IndexedChangeLogItem {
-                    index: safe_u64_to_u32(index),
-                    data: item,
-                }
-            })
-            .collect();
-
-        Ok(ChangeLogResponse {
-            total_count: safe_u64_to_u32(total_count),
-            data,
-        })
+    Ok(ChangeLogResponse {
+        total_count: safe_u64_to_u32(total_count),
+        data,
+        next_index: (index < total_count).then(|| safe_u64_to_u32(index)),
     })
 }
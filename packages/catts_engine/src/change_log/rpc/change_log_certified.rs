@@ -0,0 +1,28 @@
+use crate::{
+    certification::{self, CertifiedResponse},
+    change_log,
+    http_error::HttpError,
+};
+use ic_cdk::query;
+
+/// Returns a certified range of the change log. When the range reaches the end of the
+/// log, the witness also certifies the total count.
+#[query]
+fn change_log_certified(start_index: u32, limit: u32) -> Result<CertifiedResponse, HttpError> {
+    if limit == 0 {
+        return Err(HttpError::bad_request("Limit must be greater than 0"));
+    }
+
+    let start_index = start_index as u64;
+    let total_count = change_log::next_index();
+    if start_index >= total_count || start_index < change_log::first_index() {
+        return Err(HttpError::not_found("Start index is out of range"));
+    }
+
+    let end_index = std::cmp::min(start_index + limit as u64, total_count);
+    let items: Vec<_> = (start_index..end_index)
+        .filter_map(change_log::get)
+        .collect();
+
+    certification::certified_change_log_items(start_index, &items, total_count)
+}
@@ -0,0 +1,31 @@
+use crate::{
+    change_log::{self, ChangeLogError, ChangeLogSnapshot},
+    http_error::HttpError,
+    role::{require_role, Role},
+};
+use ic_cdk::update;
+
+/// Creates a snapshot without waiting for the scheduled snapshot, the change log is
+/// compacted once it is complete. Larger states are copied in the background, the
+/// snapshot is listed once done. Requires the operator role.
+#[update]
+fn change_log_snapshot_create() -> Result<ChangeLogSnapshot, HttpError> {
+    require_role(Role::Operator)?;
+
+    let snapshot = change_log::create_snapshot().map_err(|err| match err {
+        ChangeLogError::SnapshotInProgress => HttpError::service_unavailable(err),
+        _ => HttpError::internal_server_error(err),
+    })?;
+    if let Some(snapshot) = snapshot {
+        return Ok(snapshot);
+    }
+    if change_log::is_creating_snapshot() {
+        return Err(HttpError::service_unavailable(
+            "The snapshot is being created in the background",
+        ));
+    }
+
+    change_log::latest_snapshot().ok_or(HttpError::internal_server_error(
+        "Failed to create snapshot",
+    ))
+}
@@ -0,0 +1,48 @@
+use crate::{
+    change_log::{self, ChangeLogSnapshotEntitiesResponse, ChangeLogSnapshotEntity},
+    http_error::HttpError,
+    CHANGE_LOG_SNAPSHOT_ENTITIES,
+};
+use ic_cdk::query;
+use ic_stable_structures::Storable;
+
+const MAX_RESPONSE_BYTES: usize = 1_500_000;
+
+/// Returns the entities of a snapshot, paged the same way as the change log.
+#[query]
+fn change_log_snapshot_entities(
+    snapshot_index: u32,
+    start_index: u32,
+    limit: Option<u32>,
+) -> Result<ChangeLogSnapshotEntitiesResponse, HttpError> {
+    let snapshot_index = snapshot_index as u64;
+    let snapshot = change_log::get_snapshot(snapshot_index)
+        .ok_or(HttpError::not_found("Snapshot not found"))?;
+    let limit = limit.map_or(usize::MAX, |l| l as usize);
+
+    let mut entities: Vec<ChangeLogSnapshotEntity> = Vec::new();
+    let mut response_bytes = 0;
+    let mut next_index = None;
+
+    CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow(|snapshot_entities| {
+        for ((_, entity_index), entity) in
+            snapshot_entities.range((snapshot_index, start_index as u64)..(snapshot_index + 1, 0))
+        {
+            let entity_bytes = entity.to_bytes().len();
+            if entities.len() >= limit
+                || (!entities.is_empty() && response_bytes + entity_bytes > MAX_RESPONSE_BYTES)
+            {
+                next_index = Some(entity_index as u32);
+                break;
+            }
+            response_bytes += entity_bytes;
+            entities.push(entity);
+        }
+    });
+
+    Ok(ChangeLogSnapshotEntitiesResponse {
+        snapshot,
+        entities,
+        next_index,
+    })
+}
@@ -0,0 +1,10 @@
+use crate::{
+    change_log::{self, ChangeLogSnapshot},
+    http_error::HttpError,
+};
+use ic_cdk::query;
+
+#[query]
+fn change_log_snapshot_latest() -> Result<ChangeLogSnapshot, HttpError> {
+    change_log::latest_snapshot().ok_or(HttpError::not_found("No snapshot has been created yet"))
+}
@@ -0,0 +1,21 @@
+use crate::{
+    change_log::{self, ChangeLogError, ChangeLogTypeName, EntityVersion},
+    http_error::HttpError,
+};
+use ic_cdk::query;
+
+/// Rebuilds the JSON state of an entity as of a change log index.
+#[query]
+fn entity_at(
+    type_name: ChangeLogTypeName,
+    id: String,
+    index: u32,
+) -> Result<EntityVersion, HttpError> {
+    change_log::entity_at(&type_name, &id, index as u64).map_err(|err| match err {
+        ChangeLogError::InvalidId => HttpError::bad_request(err),
+        ChangeLogError::NotFound | ChangeLogError::Compacted(_) => HttpError::not_found(err),
+        ChangeLogError::InvalidPatch(_)
+        | ChangeLogError::IndexOverflow
+        | ChangeLogError::SnapshotInProgress => HttpError::internal_server_error(err),
+    })
+}
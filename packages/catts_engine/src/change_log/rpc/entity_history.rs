@@ -0,0 +1,19 @@
+use crate::{
+    change_log::{self, ChangeLogError, ChangeLogTypeName, EntityVersion},
+    http_error::HttpError,
+};
+use ic_cdk::query;
+
+#[query]
+fn entity_history(
+    type_name: ChangeLogTypeName,
+    id: String,
+) -> Result<Vec<EntityVersion>, HttpError> {
+    change_log::entity_history(&type_name, &id).map_err(|err| match err {
+        ChangeLogError::InvalidId => HttpError::bad_request(err),
+        ChangeLogError::NotFound | ChangeLogError::Compacted(_) => HttpError::not_found(err),
+        ChangeLogError::InvalidPatch(_)
+        | ChangeLogError::IndexOverflow
+        | ChangeLogError::SnapshotInProgress => HttpError::internal_server_error(err),
+    })
+}
@@ -1 +1,7 @@
 pub mod change_log;
+pub mod change_log_certified;
+pub mod change_log_snapshot_create;
+pub mod change_log_snapshot_entities;
+pub mod change_log_snapshot_latest;
+pub mod entity_at;
+pub mod entity_history;
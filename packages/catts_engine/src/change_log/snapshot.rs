@@ -0,0 +1,371 @@
+use super::{
+    is_migrating_legacy_change_log, next_index, remove_before, ChangeLogEntityKey, ChangeLogError,
+    ChangeLogSnapshot, ChangeLogSnapshotEntity, ChangeLogTypeName,
+};
+use crate::{
+    certification,
+    json::{bytes_to_hex_string, ToJsonValue},
+    logger,
+    recipe::{Recipe, RecipeId},
+    run::{Run, RunId},
+    time::time,
+    user::User,
+    CHANGE_LOG_SNAPSHOTS, CHANGE_LOG_SNAPSHOT_ENTITIES, RECIPES, RUNS, USERS,
+};
+use ic_stable_structures::storable::Blob;
+use serde_json::Value;
+use std::{cell::RefCell, collections::BTreeSet, ops::Bound, time::Duration};
+
+/// Number of snapshots to keep. Change log entries older than the oldest retained
+/// snapshot are removed when the change log is compacted.
+pub const CHANGE_LOG_RETAINED_SNAPSHOTS: usize = 3;
+
+/// Number of entities copied to a snapshot per message.
+const SNAPSHOT_BATCH_SIZE: usize = 500;
+
+/// Position of the snapshot that is being created, the last key copied from each stable
+/// map.
+#[derive(Clone)]
+enum SnapshotCursor {
+    /// Entities left by a snapshot that was interrupted by an upgrade are removed first
+    Orphans,
+    Recipes(Option<RecipeId>),
+    Users(Option<Blob<29>>),
+    Runs(Option<RunId>),
+}
+
+struct SnapshotBuild {
+    index: u64,
+    created: u32,
+    cursor: SnapshotCursor,
+    /// Entities of earlier attempts at the same index are stored before this position
+    first_position: u64,
+    entity_count: u64,
+    /// Entities that are in the snapshot already, or that were created after its index
+    handled: BTreeSet<ChangeLogEntityKey>,
+}
+
+thread_local! {
+    // Lives on the heap, a snapshot interrupted by an upgrade is started over
+    static SNAPSHOT_BUILD: RefCell<Option<SnapshotBuild>> = RefCell::new(None);
+}
+
+pub fn latest_snapshot() -> Option<ChangeLogSnapshot> {
+    CHANGE_LOG_SNAPSHOTS.with_borrow(|snapshots| {
+        snapshots
+            .last_key_value()
+            .map(|(_, snapshot)| snapshot.clone())
+    })
+}
+
+pub fn get_snapshot(index: u64) -> Option<ChangeLogSnapshot> {
+    CHANGE_LOG_SNAPSHOTS.with_borrow(|snapshots| snapshots.get(&index))
+}
+
+pub fn is_creating_snapshot() -> bool {
+    SNAPSHOT_BUILD.with_borrow(|build| build.is_some())
+}
+
+fn after<K>(last_key: Option<K>) -> (Bound<K>, Bound<K>) {
+    match last_key {
+        Some(key) => (Bound::Excluded(key), Bound::Unbounded),
+        None => (Bound::Unbounded, Bound::Unbounded),
+    }
+}
+
+fn insert_entity(build: &mut SnapshotBuild, entity: ChangeLogSnapshotEntity) {
+    CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow_mut(|entities| {
+        entities.insert(
+            (build.index, build.first_position + build.entity_count),
+            entity,
+        );
+    });
+    build.entity_count += 1;
+}
+
+/// Copies an entity unless it has been copied before, or it changed after the snapshot
+/// index and the state it had at the index was copied instead.
+fn copy_entity(build: &mut SnapshotBuild, type_name: ChangeLogTypeName, id: String, data: Value) {
+    let Ok(entity_key) = type_name.entity_key(&id) else {
+        return;
+    };
+    if !build.handled.insert(entity_key) {
+        return;
+    }
+    insert_entity(
+        build,
+        ChangeLogSnapshotEntity {
+            type_name,
+            id,
+            data: data.to_string(),
+        },
+    );
+}
+
+/// Called before an entity change is recorded while a snapshot is being created. The state
+/// the entity had at the snapshot index is copied before it is changed, `None` for
+/// entities that are created.
+pub fn preserve_snapshot_entity(type_name: &ChangeLogTypeName, id: &[u8], data: Option<Value>) {
+    SNAPSHOT_BUILD.with_borrow_mut(|build| {
+        let Some(build) = build else {
+            return;
+        };
+        let id = bytes_to_hex_string(id);
+        match data {
+            Some(data) => copy_entity(build, type_name.clone(), id, data),
+            None => {
+                if let Ok(entity_key) = type_name.entity_key(&id) {
+                    build.handled.insert(entity_key);
+                }
+            }
+        }
+    });
+}
+
+/// Copies up to `limit` entities, or removes as many orphans, from the current stable map.
+/// Returns the number of items processed and `false` once all entities are copied.
+fn snapshot_batch(build: &mut SnapshotBuild, limit: usize) -> (usize, bool) {
+    match build.cursor.clone() {
+        SnapshotCursor::Orphans => {
+            let first_orphan = latest_snapshot().map_or(0, |snapshot| snapshot.index as u64 + 1);
+            let orphans: Vec<(u64, u64)> = CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow(|entities| {
+                entities
+                    .range((first_orphan, 0)..(build.index, build.first_position))
+                    .take(limit)
+                    .map(|(key, _)| key)
+                    .collect()
+            });
+            CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow_mut(|entities| {
+                for key in orphans.iter() {
+                    entities.remove(key);
+                }
+            });
+            if orphans.len() < limit {
+                build.cursor = SnapshotCursor::Recipes(None);
+            }
+            (orphans.len(), true)
+        }
+        SnapshotCursor::Recipes(last_id) => {
+            let batch: Vec<(RecipeId, Recipe)> =
+                RECIPES.with_borrow(|recipes| recipes.range(after(last_id)).take(limit).collect());
+            for (id, recipe) in batch.iter() {
+                copy_entity(
+                    build,
+                    ChangeLogTypeName::Recipe,
+                    bytes_to_hex_string(id),
+                    recipe.to_json_value(),
+                );
+            }
+            build.cursor = match batch.last() {
+                Some((id, _)) if batch.len() == limit => SnapshotCursor::Recipes(Some(*id)),
+                _ => SnapshotCursor::Users(None),
+            };
+            (batch.len(), true)
+        }
+        SnapshotCursor::Users(last_key) => {
+            let batch: Vec<(Blob<29>, User)> =
+                USERS.with_borrow(|users| users.range(after(last_key)).take(limit).collect());
+            for (_, user) in batch.iter() {
+                copy_entity(
+                    build,
+                    ChangeLogTypeName::User,
+                    user.eth_address.clone(),
+                    user.to_json_value(),
+                );
+            }
+            build.cursor = match batch.last() {
+                Some((key, _)) if batch.len() == limit => SnapshotCursor::Users(Some(*key)),
+                _ => SnapshotCursor::Runs(None),
+            };
+            (batch.len(), true)
+        }
+        SnapshotCursor::Runs(last_id) => {
+            let batch: Vec<(RunId, Run)> =
+                RUNS.with_borrow(|runs| runs.range(after(last_id)).take(limit).collect());
+            for (id, run) in batch.iter() {
+                copy_entity(
+                    build,
+                    ChangeLogTypeName::Run,
+                    bytes_to_hex_string(id),
+                    run.to_json_value(),
+                );
+            }
+            match batch.last() {
+                Some((id, _)) if batch.len() == limit => {
+                    build.cursor = SnapshotCursor::Runs(Some(*id));
+                    (batch.len(), true)
+                }
+                _ => (batch.len(), false),
+            }
+        }
+    }
+}
+
+/// Stores the snapshot once all entities are copied and compacts the change log.
+fn finish_snapshot(build: SnapshotBuild) -> Result<ChangeLogSnapshot, ChangeLogError> {
+    let snapshot = ChangeLogSnapshot {
+        index: u32::try_from(build.index).map_err(|_| ChangeLogError::IndexOverflow)?,
+        created: build.created,
+        entity_count: u32::try_from(build.entity_count)
+            .map_err(|_| ChangeLogError::IndexOverflow)?,
+    };
+    CHANGE_LOG_SNAPSHOTS.with_borrow_mut(|snapshots| {
+        snapshots.insert(build.index, snapshot.clone());
+    });
+
+    logger::info(
+        format!(
+            "Created change log snapshot at index {} with {} entities",
+            snapshot.index, snapshot.entity_count
+        )
+        .as_str(),
+    );
+
+    compact();
+
+    Ok(snapshot)
+}
+
+/// Copies up to `SNAPSHOT_BATCH_SIZE` entities, returns the snapshot once it is complete.
+fn continue_snapshot() -> Option<Result<ChangeLogSnapshot, ChangeLogError>> {
+    let mut build = SNAPSHOT_BUILD.with_borrow_mut(|build| build.take())?;
+    let mut remaining = SNAPSHOT_BATCH_SIZE;
+    while remaining > 0 {
+        let (processed, has_more) = snapshot_batch(&mut build, remaining);
+        if !has_more {
+            return Some(finish_snapshot(build));
+        }
+        remaining -= processed;
+    }
+    SNAPSHOT_BUILD.with_borrow_mut(|snapshot_build| *snapshot_build = Some(build));
+    None
+}
+
+fn schedule_snapshot_batch() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || match continue_snapshot() {
+        None if is_creating_snapshot() => schedule_snapshot_batch(),
+        Some(Err(err)) => {
+            logger::error(format!("Change log snapshot failed: {}", err).as_str());
+        }
+        _ => {}
+    });
+}
+
+/// Starts a snapshot of all recipes, users and runs at the current change log index. The
+/// entities are copied in batches from timers so that the work doesn't grow with the
+/// stored data. Entities that change in the meantime have their state at the snapshot
+/// index copied before the change is recorded. The snapshot is only listed once complete,
+/// the change log is compacted then.
+///
+/// Returns the snapshot if it was completed by the first batch, `None` if nothing has
+/// changed since the latest snapshot or the snapshot is completed from timers.
+pub fn create_snapshot() -> Result<Option<ChangeLogSnapshot>, ChangeLogError> {
+    if is_creating_snapshot() {
+        return Err(ChangeLogError::SnapshotInProgress);
+    }
+
+    let index = next_index();
+    if latest_snapshot().is_some_and(|snapshot| snapshot.index as u64 == index) {
+        return Ok(None);
+    }
+    u32::try_from(index).map_err(|_| ChangeLogError::IndexOverflow)?;
+
+    let first_position = CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow(|entities| {
+        entities
+            .iter_upper_bound(&(index + 1, 0))
+            .next()
+            .filter(|((snapshot_index, _), _)| *snapshot_index == index)
+            .map_or(0, |((_, position), _)| position + 1)
+    });
+    SNAPSHOT_BUILD.with_borrow_mut(|build| {
+        *build = Some(SnapshotBuild {
+            index,
+            created: time(),
+            cursor: SnapshotCursor::Orphans,
+            first_position,
+            entity_count: 0,
+            handled: BTreeSet::new(),
+        })
+    });
+
+    match continue_snapshot() {
+        Some(result) => result.map(Some),
+        None => {
+            schedule_snapshot_batch();
+            Ok(None)
+        }
+    }
+}
+
+/// Applies `scrub` to the data of an entity in all retained snapshots.
+pub fn scrub_snapshot_entities(
+    type_name: &ChangeLogTypeName,
+    id: &str,
+    scrub: impl Fn(&mut Value),
+) {
+    let matches: Vec<((u64, u64), ChangeLogSnapshotEntity)> = CHANGE_LOG_SNAPSHOT_ENTITIES
+        .with_borrow(|entities| {
+            entities
+                .iter()
+                .filter(|(_, entity)| {
+                    entity.type_name == *type_name && entity.id.eq_ignore_ascii_case(id)
+                })
+                .collect()
+        });
+
+    CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow_mut(|entities| {
+        for (key, mut entity) in matches {
+            let Ok(mut data) = serde_json::from_str::<Value>(&entity.data) else {
+                continue;
+            };
+            scrub(&mut data);
+            entity.data = data.to_string();
+            entities.insert(key, entity);
+        }
+    });
+}
+
+/// Removes snapshots outside of the retained window, along with the change log entries
+/// that precede the oldest retained snapshot. Indices of the remaining entries are kept.
+/// Skipped while legacy entries are being migrated below the snapshot indices.
+pub fn compact() {
+    if is_migrating_legacy_change_log() {
+        return;
+    }
+
+    let snapshot_indices: Vec<u64> = CHANGE_LOG_SNAPSHOTS
+        .with_borrow(|snapshots| snapshots.iter().map(|(index, _)| index).collect());
+    if snapshot_indices.len() <= CHANGE_LOG_RETAINED_SNAPSHOTS {
+        return;
+    }
+
+    let (removed_snapshots, retained_snapshots) =
+        snapshot_indices.split_at(snapshot_indices.len() - CHANGE_LOG_RETAINED_SNAPSHOTS);
+
+    for snapshot_index in removed_snapshots {
+        let entity_keys: Vec<(u64, u64)> = CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow(|entities| {
+            entities
+                .range((*snapshot_index, 0)..(snapshot_index + 1, 0))
+                .map(|(key, _)| key)
+                .collect()
+        });
+        CHANGE_LOG_SNAPSHOT_ENTITIES.with_borrow_mut(|entities| {
+            for key in entity_keys {
+                entities.remove(&key);
+            }
+        });
+        CHANGE_LOG_SNAPSHOTS.with_borrow_mut(|snapshots| snapshots.remove(snapshot_index));
+    }
+
+    let removed_entries = remove_before(retained_snapshots[0]);
+    certification::uncertify_change_log_items(&removed_entries);
+
+    logger::info(
+        format!(
+            "Compacted change log, removed {} snapshots and {} entries",
+            removed_snapshots.len(),
+            removed_entries.len()
+        )
+        .as_str(),
+    );
+}
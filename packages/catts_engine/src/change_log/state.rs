@@ -1,33 +1,181 @@
-use crate::{json::ToJsonValue, CHANGE_LOG};
+use crate::{
+    certification, json::ToJsonValue, logger, CHANGE_LOG, CHANGE_LOG_DATA_MEMORY_ID,
+    CHANGE_LOG_ENTITY_INDEX, CHANGE_LOG_INDEX_MEMORY_ID, CHANGE_LOG_LEGACY, CHANGE_LOG_SNAPSHOTS,
+    MEMORY_MANAGER,
+};
 
-use super::{ChangeLogItem, ChangeLogTypeName};
-use anyhow::{anyhow, Result};
+use super::{preserve_snapshot_entity, ChangeLogEntityKey, ChangeLogItem, ChangeLogTypeName};
+use anyhow::Result;
+use ic_stable_structures::Log;
+use std::time::Duration;
 
-pub fn create<T: ToJsonValue>(type_name: ChangeLogTypeName, id: [u8; 12], data: T) -> Result<u64> {
-    CHANGE_LOG.with_borrow_mut(|logs| {
-        let change_log_item = ChangeLogItem::create(type_name, id, data);
-        logs.append(&change_log_item)
-            .map_err(|err| anyhow!(format!("Failed to append to change log: {:?}", err)))
+/// Number of legacy change log entries migrated per message.
+const MIGRATE_BATCH_SIZE: usize = 500;
+
+/// The index the next change log entry will get. Compaction can remove all entries, the
+/// latest snapshot then keeps track of the index. Legacy entries that have not been
+/// migrated yet keep their indices.
+pub fn next_index() -> u64 {
+    let next_entry_index =
+        CHANGE_LOG.with_borrow(|log| log.last_key_value().map_or(0, |(index, _)| index + 1));
+    let snapshot_index = CHANGE_LOG_SNAPSHOTS
+        .with_borrow(|snapshots| snapshots.last_key_value().map_or(0, |(index, _)| index));
+    let legacy_count = CHANGE_LOG_LEGACY.with_borrow(|legacy| legacy.len());
+    next_entry_index.max(snapshot_index).max(legacy_count)
+}
+
+/// The index of the oldest entry that has not been compacted.
+pub fn first_index() -> u64 {
+    CHANGE_LOG
+        .with_borrow(|log| log.first_key_value().map(|(index, _)| index))
+        .unwrap_or_else(next_index)
+}
+
+pub fn get(index: u64) -> Option<ChangeLogItem> {
+    CHANGE_LOG.with_borrow(|log| log.get(&index))
+}
+
+fn index_entity(index: u64, change_log_item: &ChangeLogItem) {
+    if let Ok(entity_key) = change_log_item.entity_key() {
+        CHANGE_LOG_ENTITY_INDEX.with_borrow_mut(|entity_index| {
+            entity_index.insert((entity_key, index), ());
+        });
+    }
+}
+
+/// Returns the indices of the change log entries of an entity, in order.
+pub fn entity_indices(entity_key: &ChangeLogEntityKey) -> Vec<u64> {
+    CHANGE_LOG_ENTITY_INDEX.with_borrow(|entity_index| {
+        entity_index
+            .range((*entity_key, 0)..=(*entity_key, u64::MAX))
+            .map(|((_, index), _)| index)
+            .collect()
     })
 }
 
+fn append(change_log_item: ChangeLogItem) -> Result<u64> {
+    let index = next_index();
+    CHANGE_LOG.with_borrow_mut(|log| log.insert(index, change_log_item.clone()));
+    index_entity(index, &change_log_item);
+    certification::certify_change_log_item(index, &change_log_item);
+    Ok(index)
+}
+
+pub fn create<T: ToJsonValue>(type_name: ChangeLogTypeName, id: &[u8], data: T) -> Result<u64> {
+    preserve_snapshot_entity(&type_name, id, None);
+    append(ChangeLogItem::create(type_name, id, data))
+}
+
 pub fn update<T: ToJsonValue>(
     type_name: ChangeLogTypeName,
-    id: [u8; 12],
+    id: &[u8],
     old_data: T,
     new_data: T,
 ) -> Result<u64> {
-    CHANGE_LOG.with_borrow_mut(|logs| {
-        let change_log_item = ChangeLogItem::update(type_name, id, old_data, new_data);
-        logs.append(&change_log_item)
-            .map_err(|err| anyhow!(format!("Failed to append to change log: {:?}", err)))
-    })
+    preserve_snapshot_entity(&type_name, id, Some(old_data.to_json_value()));
+    append(ChangeLogItem::update(type_name, id, old_data, new_data))
 }
 
-pub fn delete(type_name: ChangeLogTypeName, id: [u8; 12]) -> Result<u64> {
-    CHANGE_LOG.with_borrow_mut(|logs| {
-        let change_log_item = ChangeLogItem::delete(type_name, id);
-        logs.append(&change_log_item)
-            .map_err(|err| anyhow!(format!("Failed to append to change log: {:?}", err)))
-    })
+/// Records the deletion of an entity, `old_data` is the state a snapshot that is being
+/// created keeps for it.
+pub fn delete<T: ToJsonValue>(type_name: ChangeLogTypeName, id: &[u8], old_data: T) -> Result<u64> {
+    preserve_snapshot_entity(&type_name, id, Some(old_data.to_json_value()));
+    append(ChangeLogItem::delete(type_name, id))
+}
+
+/// Rewrites an entry in place, the entry has to belong to the same entity.
+pub fn replace(index: u64, change_log_item: ChangeLogItem) {
+    CHANGE_LOG.with_borrow_mut(|log| log.insert(index, change_log_item.clone()));
+    certification::recertify_change_log_item(index, &change_log_item);
+}
+
+/// Removes all entries before `index`.
+pub fn remove_before(index: u64) -> Vec<u64> {
+    let removed: Vec<(u64, ChangeLogItem)> =
+        CHANGE_LOG.with_borrow(|log| log.range(..index).collect());
+
+    CHANGE_LOG.with_borrow_mut(|log| {
+        for (index, _) in removed.iter() {
+            log.remove(index);
+        }
+    });
+    CHANGE_LOG_ENTITY_INDEX.with_borrow_mut(|entity_index| {
+        for (index, item) in removed.iter() {
+            if let Ok(entity_key) = item.entity_key() {
+                entity_index.remove(&(entity_key, *index));
+            }
+        }
+    });
+
+    removed.into_iter().map(|(index, _)| index).collect()
+}
+
+pub fn is_migrating_legacy_change_log() -> bool {
+    CHANGE_LOG_LEGACY.with_borrow(|legacy| !legacy.is_empty())
+}
+
+/// Copies the next batch of legacy entries and returns `false` once all are copied. The
+/// next entry to copy follows the last copied one, entries appended since start at the
+/// legacy length.
+fn migrate_legacy_batch() -> bool {
+    let legacy_count = CHANGE_LOG_LEGACY.with_borrow(|legacy| legacy.len());
+    let start = CHANGE_LOG.with_borrow(|log| {
+        log.iter_upper_bound(&legacy_count)
+            .next()
+            .map_or(0, |(index, _)| index + 1)
+    });
+    let end = legacy_count.min(start + MIGRATE_BATCH_SIZE as u64);
+
+    for index in start..end {
+        let Some(item) = CHANGE_LOG_LEGACY.with_borrow(|legacy| legacy.get(index)) else {
+            continue;
+        };
+        CHANGE_LOG.with_borrow_mut(|log| log.insert(index, item.clone()));
+        index_entity(index, &item);
+        certification::recertify_change_log_item(index, &item);
+    }
+
+    if end < legacy_count {
+        return true;
+    }
+
+    CHANGE_LOG_LEGACY.with_borrow_mut(|legacy| {
+        *legacy = Log::new(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_INDEX_MEMORY_ID)),
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_DATA_MEMORY_ID)),
+        );
+    });
+
+    logger::info(format!("Migrated {} change log entries", legacy_count).as_str());
+    false
+}
+
+fn schedule_migrate_legacy_batch() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || {
+        if migrate_legacy_batch() {
+            schedule_migrate_legacy_batch();
+        }
+    });
+}
+
+/// Copies the entries of the append only change log used by earlier versions, indices are
+/// kept as they are. The copy runs in batches from timers so that large logs don't exceed
+/// the upgrade instruction limit, the change log is not compacted until it is done.
+pub fn migrate_legacy_change_log() {
+    if is_migrating_legacy_change_log() {
+        schedule_migrate_legacy_batch();
+    }
+}
+
+/// Builds the entity index for change logs created before the index was introduced.
+pub fn init_entity_index() {
+    let is_indexed = CHANGE_LOG_ENTITY_INDEX.with_borrow(|entity_index| !entity_index.is_empty());
+    if is_indexed {
+        return;
+    }
+
+    let items: Vec<(u64, ChangeLogItem)> = CHANGE_LOG.with_borrow(|log| log.iter().collect());
+    for (index, item) in items.iter() {
+        index_entity(*index, item);
+    }
 }
@@ -0,0 +1,40 @@
+use crate::{
+    change_log, logger,
+    tasks::{add_task, is_task_scheduled, Task, TaskError, TaskExecutor, TaskType},
+};
+use futures::Future;
+use std::pin::Pin;
+
+const CREATE_SNAPSHOT_INTERVAL: u64 = 24 * 60 * 60 * 1_000_000_000; // 24 hours
+
+/// Schedules the next snapshot, unless one is already scheduled.
+pub fn schedule_create_snapshot() {
+    if is_task_scheduled(&TaskType::CreateChangeLogSnapshot, &[]) {
+        return;
+    }
+
+    add_task(
+        ic_cdk::api::time() + CREATE_SNAPSHOT_INTERVAL,
+        Task {
+            task_type: TaskType::CreateChangeLogSnapshot,
+            args: vec![],
+            max_retries: 1,
+            execute_count: 0,
+            retry_interval: 0,
+        },
+    );
+}
+
+pub struct CreateChangeLogSnapshotExecutor {}
+
+impl TaskExecutor for CreateChangeLogSnapshotExecutor {
+    fn execute(&self, _: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            if let Err(err) = change_log::create_snapshot() {
+                logger::error(format!("Change log snapshot failed: {}", err).as_str());
+            }
+            schedule_create_snapshot();
+            Ok(())
+        })
+    }
+}
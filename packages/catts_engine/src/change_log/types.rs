@@ -5,11 +5,61 @@ use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
 
 use crate::json::{bytes_to_hex_string, ToJsonValue};
+use ethers_core::utils::hex;
+use thiserror::Error;
+
+/// Identifies an entity in the change log, the type tag followed by the entity id. Recipe
+/// and run ids are 12 bytes, users are identified by their 20 byte address, shorter ids are
+/// zero padded.
+pub type ChangeLogEntityKey = [u8; 21];
+
+#[derive(Error, Debug)]
+pub enum ChangeLogError {
+    #[error("Invalid entity id")]
+    InvalidId,
+    #[error("Entity history before index {0} has been compacted")]
+    Compacted(u64),
+    #[error("Entity not found")]
+    NotFound,
+    #[error("Invalid patch at index {0}")]
+    InvalidPatch(u64),
+    #[error("Change log index exceeds the supported range")]
+    IndexOverflow,
+    #[error("A snapshot is already being created")]
+    SnapshotInProgress,
+}
 
 #[derive(Serialize, Deserialize, CandidType, Clone)]
 pub struct ChangeLogResponse {
     pub total_count: u32,
     pub data: Vec<IndexedChangeLogItem>,
+    /// Index to continue from, `None` once the end of the change log has been reached
+    pub next_index: Option<u32>,
+}
+
+/// Filters change log queries, all set fields must match.
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, Default)]
+pub struct ChangeLogFilter {
+    pub type_name: Option<ChangeLogTypeName>,
+    /// Hex encoded entity id, `0x` prefixed
+    pub id: Option<String>,
+    pub action: Option<ChangeLogAction>,
+}
+
+impl ChangeLogFilter {
+    pub fn matches(&self, item: &ChangeLogItem) -> bool {
+        self.type_name
+            .as_ref()
+            .map_or(true, |type_name| *type_name == item.type_name)
+            && self
+                .id
+                .as_ref()
+                .map_or(true, |id| id.eq_ignore_ascii_case(&item.id))
+            && self
+                .action
+                .as_ref()
+                .map_or(true, |action| *action == item.action)
+    }
 }
 
 #[derive(Serialize, Deserialize, CandidType, Clone)]
@@ -18,14 +68,14 @@ pub struct IndexedChangeLogItem {
     pub data: ChangeLogItem,
 }
 
-#[derive(Serialize, Deserialize, CandidType, Clone)]
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, PartialEq)]
 pub enum ChangeLogTypeName {
     Recipe,
     Run,
     User,
 }
 
-#[derive(Serialize, Deserialize, CandidType, Clone)]
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, PartialEq)]
 pub enum ChangeLogAction {
     Create,
     Update,
@@ -52,11 +102,41 @@ impl Storable for ChangeLogItem {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl ChangeLogTypeName {
+    fn tag(&self) -> u8 {
+        match self {
+            ChangeLogTypeName::Recipe => 0,
+            ChangeLogTypeName::Run => 1,
+            ChangeLogTypeName::User => 2,
+        }
+    }
+
+    /// Parses a hex encoded entity id, `0x` prefixed, into an entity key.
+    pub fn entity_key(&self, id: &str) -> Result<ChangeLogEntityKey, ChangeLogError> {
+        let id_bytes = hex::decode(id).map_err(|_| ChangeLogError::InvalidId)?;
+        let expected_len = match self {
+            ChangeLogTypeName::Recipe | ChangeLogTypeName::Run => 12,
+            ChangeLogTypeName::User => 20,
+        };
+        if id_bytes.len() != expected_len {
+            return Err(ChangeLogError::InvalidId);
+        }
+        let mut key = [0u8; 21];
+        key[0] = self.tag();
+        key[1..=id_bytes.len()].copy_from_slice(&id_bytes);
+        Ok(key)
+    }
+}
+
 impl ChangeLogItem {
-    pub fn create<T: ToJsonValue>(type_name: ChangeLogTypeName, id: [u8; 12], data: T) -> Self {
+    pub fn entity_key(&self) -> Result<ChangeLogEntityKey, ChangeLogError> {
+        self.type_name.entity_key(&self.id)
+    }
+
+    pub fn create<T: ToJsonValue>(type_name: ChangeLogTypeName, id: &[u8], data: T) -> Self {
         Self {
             type_name,
-            id: bytes_to_hex_string(&id).to_string(),
+            id: bytes_to_hex_string(id).to_string(),
             action: ChangeLogAction::Create,
             patch: data.to_json_value().to_string(),
         }
@@ -64,7 +144,7 @@ impl ChangeLogItem {
 
     pub fn update<T: ToJsonValue>(
         type_name: ChangeLogTypeName,
-        id: [u8; 12],
+        id: &[u8],
         old_data: T,
         new_data: T,
     ) -> Self {
@@ -72,18 +152,81 @@ impl ChangeLogItem {
 
         Self {
             type_name,
-            id: bytes_to_hex_string(&id).to_string(),
+            id: bytes_to_hex_string(id).to_string(),
             action: ChangeLogAction::Update,
             patch: serde_json::to_string(&patch).unwrap(),
         }
     }
 
-    pub fn delete(type_name: ChangeLogTypeName, id: [u8; 12]) -> Self {
+    pub fn delete(type_name: ChangeLogTypeName, id: &[u8]) -> Self {
         Self {
             type_name,
             action: ChangeLogAction::Delete,
-            id: bytes_to_hex_string(&id).to_string(),
+            id: bytes_to_hex_string(id).to_string(),
             patch: serde_json::to_string(&serde_json::Value::Null).unwrap(),
         }
     }
 }
+
+/// The state of all entities as of a change log index. Clients start from the latest
+/// snapshot and replay the change log entries from `index` onwards.
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct ChangeLogSnapshot {
+    /// The index of the first change log entry that is not included in the snapshot
+    pub index: u32,
+    pub created: u32,
+    pub entity_count: u32,
+}
+
+impl Storable for ChangeLogSnapshot {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// An entity in a snapshot. `data` holds the full JSON state of the entity, the same
+/// format as the patch of a `Create` entry.
+#[derive(Serialize, Deserialize, CandidType, Clone)]
+pub struct ChangeLogSnapshotEntity {
+    pub type_name: ChangeLogTypeName,
+    pub id: String,
+    pub data: String,
+}
+
+impl Storable for ChangeLogSnapshotEntity {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone)]
+pub struct ChangeLogSnapshotEntitiesResponse {
+    pub snapshot: ChangeLogSnapshot,
+    pub entities: Vec<ChangeLogSnapshotEntity>,
+    /// Entity index to continue from, `None` once all entities have been returned
+    pub next_index: Option<u32>,
+}
+
+/// A version of an entity, `data` holds the full JSON state after the change, `null` once
+/// the entity has been deleted. Versions restored from a snapshot have no action.
+#[derive(Serialize, Deserialize, CandidType, Clone)]
+pub struct EntityVersion {
+    pub index: u32,
+    pub action: Option<ChangeLogAction>,
+    pub data: String,
+    /// Set for the version restored from the snapshot at `index`. It holds the state
+    /// before the change log entry with the same index.
+    pub snapshot: bool,
+}
@@ -1,8 +1,14 @@
 use ic_cdk::query;
 
-use crate::logger::{self, LogItem};
+use crate::{
+    http_error::HttpError,
+    logger::{self, LogItem},
+    role::{require_role, Role},
+};
 
 #[query]
-pub fn logs() -> Vec<LogItem> {
-    logger::get()
+pub fn logs() -> Result<Vec<LogItem>, HttpError> {
+    require_role(Role::Auditor)?;
+
+    Ok(logger::get())
 }
@@ -1,3 +1,4 @@
 pub mod canister_eth_address;
 pub mod logs;
+pub mod task_list;
 pub mod transform;
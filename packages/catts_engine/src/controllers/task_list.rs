@@ -0,0 +1,14 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    role::{require_role, Role},
+    tasks::{self, ScheduledTask},
+};
+
+#[query]
+fn task_list() -> Result<Vec<ScheduledTask>, HttpError> {
+    require_role(Role::Auditor)?;
+
+    Ok(tasks::list_tasks())
+}
@@ -3,6 +3,8 @@ use ic_cdk::{
     query,
 };
 
+use crate::evm::json_rpc_client;
+
 #[query]
 fn transform(raw: TransformArgs) -> HttpResponse {
     let mut res = HttpResponse {
@@ -19,3 +21,20 @@ fn transform(raw: TransformArgs) -> HttpResponse {
 
     res
 }
+
+/// Webhook receivers only report back a status, headers and body are dropped so that the
+/// responses of all replicas are identical.
+#[query]
+fn webhook_transform(raw: TransformArgs) -> HttpResponse {
+    HttpResponse {
+        status: raw.response.status,
+        ..Default::default()
+    }
+}
+
+/// JSON-RPC responses from chains that are accessed without the evm_rpc canister. The
+/// context holds the transaction hash for transaction submissions.
+#[query]
+fn json_rpc_transform(raw: TransformArgs) -> HttpResponse {
+    json_rpc_client::transform_response(raw.response, &raw.context)
+}
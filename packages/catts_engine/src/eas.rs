@@ -1,19 +1,27 @@
 use crate::{
     chain_config::{self, ChainConfig},
     eth_address::EthAddress,
-    evm::rpc::{eth_estimate_gas, eth_transaction},
+    evm::{
+        rpc::{
+            eth_call, eth_estimate_gas, eth_sign_contract_transaction, get_eth_address,
+            sign_message_hash, SignedTransaction,
+        },
+        types::{Attester, Signer},
+        util::nat_to_u128,
+    },
     graphql::insert_dynamic_variables,
     recipe::{Recipe, RecipeQuery},
     run::Run,
     ETH_DEFAULT_CALL_CYCLES, ETH_EAS_CONTRACT, THEGRAPH_QUERY_PROXY_URL,
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use blake2::{
     digest::{Update, VariableOutput},
     Blake2bVar,
 };
+use candid::Nat;
 use ethers_core::{
-    abi::{encode, encode_packed, ethereum_types::H160, Address, Token},
+    abi::{encode, encode_packed, ethereum_types::H160, parse_abi, Address, Token},
     types::U256,
     utils::{hex, keccak256},
 };
@@ -25,7 +33,7 @@ use ic_cdk::api::{
 };
 use javy::Runtime;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::{collections::HashMap, str::FromStr, sync::Arc};
 use thiserror::Error;
 
@@ -51,56 +59,53 @@ pub struct SchemaItem {
     pub value: SchemaValue,
 }
 
+pub fn schema_item_to_token(item: &SchemaItem) -> Token {
+    match item.type_field.as_str() {
+        _ if item.type_field == "address" => match &item.value {
+            SchemaValue::String(hex) => {
+                Token::Address(Address::from_str(hex).expect("Invalid address"))
+            }
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ if item.type_field == "string" => match &item.value {
+            SchemaValue::String(val) => Token::String(val.clone()),
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ if item.type_field == "bool" => match &item.value {
+            SchemaValue::Bool(val) => Token::Bool(*val),
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ if item.type_field == "bytes" => match &item.value {
+            SchemaValue::String(hex) => Token::Bytes(hex::decode(hex).expect("Invalid hex value")),
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ if item.type_field == "bytes32" => match &item.value {
+            SchemaValue::String(hex) => {
+                Token::FixedBytes(hex::decode(hex).expect("Invalid hex value"))
+            }
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ if item.type_field.starts_with("uint") => match &item.value {
+            SchemaValue::String(hex) => {
+                Token::Uint(U256::from_str(hex).expect("Invalid hex value"))
+            }
+            SchemaValue::Number(num) => Token::Uint({ *num }.into()),
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ if item.type_field.starts_with("int") => match &item.value {
+            SchemaValue::String(hex) => Token::Int(U256::from_str(hex).expect("Invalid hex value")),
+            SchemaValue::Number(num) => Token::Int({ *num }.into()),
+            _ => panic!("Unsupported value: {:?}", item.value),
+        },
+        _ => panic!("Unsupported type: {}", item.type_field),
+    }
+}
+
 pub fn encode_abi_data(json_data: &str) -> Vec<u8> {
     let schema_items: Vec<SchemaItem> =
         serde_json::from_str(json_data).expect("Failed to parse JSON");
 
-    let tokens: Vec<Token> = schema_items
-        .iter()
-        .map(|item| match item.type_field.as_str() {
-            _ if item.type_field == "address" => match &item.value {
-                SchemaValue::String(hex) => {
-                    Token::Address(Address::from_str(hex).expect("Invalid address"))
-                }
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ if item.type_field == "string" => match &item.value {
-                SchemaValue::String(val) => Token::String(val.clone()),
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ if item.type_field == "bool" => match &item.value {
-                SchemaValue::Bool(val) => Token::Bool(*val),
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ if item.type_field == "bytes" => match &item.value {
-                SchemaValue::String(hex) => {
-                    Token::Bytes(hex::decode(hex).expect("Invalid hex value"))
-                }
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ if item.type_field == "bytes32" => match &item.value {
-                SchemaValue::String(hex) => {
-                    Token::FixedBytes(hex::decode(hex).expect("Invalid hex value"))
-                }
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ if item.type_field.starts_with("uint") => match &item.value {
-                SchemaValue::String(hex) => {
-                    Token::Uint(U256::from_str(hex).expect("Invalid hex value"))
-                }
-                SchemaValue::Number(num) => Token::Uint({ *num }.into()),
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ if item.type_field.starts_with("int") => match &item.value {
-                SchemaValue::String(hex) => {
-                    Token::Int(U256::from_str(hex).expect("Invalid hex value"))
-                }
-                SchemaValue::Number(num) => Token::Int({ *num }.into()),
-                _ => panic!("Unsupported value: {:?}", item.value),
-            },
-            _ => panic!("Unsupported type: {}", item.type_field),
-        })
-        .collect();
+    let tokens: Vec<Token> = schema_items.iter().map(schema_item_to_token).collect();
 
     // Encode the tokens
     encode(&tokens)
@@ -262,41 +267,173 @@ pub fn create_attest_request(
     Ok(Token::Tuple(vec![schema_token, attestation_request_data]))
 }
 
-pub async fn create_attestation(
-    recipe: &Recipe,
-    run: &Run,
-    attestation_data: &str,
-    recipient: &EthAddress,
-    chain_id: u32,
-) -> Result<String> {
-    let attest_request = create_attest_request(recipe, attestation_data, recipient)?;
+/// Converts a list of `attest` requests into the `MultiAttestationRequest[]` argument of
+/// `multiAttest`. The UIDs of the resulting attestations are emitted in request order.
+pub fn create_multi_attest_request(attest_requests: Vec<Token>) -> Result<Token> {
+    let multi_attest_requests = attest_requests
+        .into_iter()
+        .map(|attest_request| match attest_request {
+            Token::Tuple(mut request) if request.len() == 2 => {
+                let attestation_request_data = request.pop().unwrap();
+                let schema_token = request.pop().unwrap();
+                Ok(Token::Tuple(vec![
+                    schema_token,
+                    Token::Array(vec![attestation_request_data]),
+                ]))
+            }
+            _ => Err(anyhow!("Invalid attest request")),
+        })
+        .collect::<Result<Vec<Token>>>()?;
+
+    Ok(Token::Array(multi_attest_requests))
+}
+
+// Runs are only batched with runs whose max fee per gas is at least 90% of the highest fee
+// in the batch
+const BATCH_FEE_NUMERATOR: u8 = 9;
+const BATCH_FEE_DENOMINATOR: u8 = 10;
+
+/// Gas limit and fees of a batch attestation transaction.
+pub struct BatchFees {
+    pub gas: Nat,
+    pub max_fee_per_gas: Nat,
+    pub max_priority_fee_per_gas: Nat,
+}
+
+/// The max fee per gas the run was quoted, `base_fee_per_gas + max_priority_fee_per_gas`.
+fn run_max_fee_per_gas(run: &Run) -> Option<Nat> {
+    Some(run.base_fee_per_gas.clone()? + run.max_priority_fee_per_gas.clone()?)
+}
+
+/// Groups runs into batches of runs with compatible fees, starting with the runs that pay
+/// the highest fees. A run that pays a low fee is batched with runs paying similar fees
+/// instead of underpricing a batch of runs that paid more.
+pub fn fee_compatible_batches<T>(mut runs: Vec<(Run, T)>) -> Vec<Vec<(Run, T)>> {
+    runs.sort_by(|(a, _), (b, _)| run_max_fee_per_gas(b).cmp(&run_max_fee_per_gas(a)));
+
+    let mut batches: Vec<Vec<(Run, T)>> = Vec::new();
+    let mut batch_max_fee_per_gas = None;
+    for (run, item) in runs {
+        let max_fee_per_gas = run_max_fee_per_gas(&run);
+        let compatible = match (&batch_max_fee_per_gas, &max_fee_per_gas) {
+            (Some(batch_fee), Some(fee)) => {
+                fee.clone() * BATCH_FEE_DENOMINATOR >= batch_fee.clone() * BATCH_FEE_NUMERATOR
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        match batches.last_mut() {
+            Some(batch) if compatible => batch.push((run, item)),
+            _ => {
+                batch_max_fee_per_gas = max_fee_per_gas;
+                batches.push(vec![(run, item)]);
+            }
+        }
+    }
+
+    batches
+}
 
-    let gas = run
-        .gas
-        .clone()
-        .ok_or(anyhow!("Recipe don't have a gas amount specified"))?;
+/// The gas limit of a batch is the sum of the gas estimated for each run and the fees are
+/// the lowest fees paid by any of the runs. Runs are batched with runs that paid
+/// compatible fees, see `fee_compatible_batches`, so the batch is priced close to what
+/// each run paid and no run pays more than its share of the transaction.
+pub fn batch_fees(runs: &[Run]) -> Result<BatchFees> {
+    let mut gas = Nat::from(0_u8);
+    let mut max_fee_per_gas: Option<Nat> = None;
+    let mut max_priority_fee_per_gas: Option<Nat> = None;
+
+    for run in runs {
+        let run_gas = run
+            .gas
+            .clone()
+            .ok_or(anyhow!("Run don't have a gas amount specified"))?;
+
+        let base_fee_per_gas = run.base_fee_per_gas.clone().ok_or(anyhow!(
+            "Run don't have a base_fee_per_gas amount specified"
+        ))?;
+
+        let run_max_priority_fee_per_gas = run.max_priority_fee_per_gas.clone().ok_or(anyhow!(
+            "Run don't have a max_priority_fee_per_gas amount specified"
+        ))?;
+
+        let run_max_fee_per_gas = base_fee_per_gas + run_max_priority_fee_per_gas.clone();
+
+        gas += run_gas;
+        max_fee_per_gas = Some(match max_fee_per_gas {
+            Some(fee) => fee.min(run_max_fee_per_gas),
+            None => run_max_fee_per_gas,
+        });
+        max_priority_fee_per_gas = Some(match max_priority_fee_per_gas {
+            Some(fee) => fee.min(run_max_priority_fee_per_gas),
+            None => run_max_priority_fee_per_gas,
+        });
+    }
 
-    let base_fee_per_gas = run.base_fee_per_gas.clone().ok_or(anyhow!(
-        "Run don't have a base_fee_per_gas amount specified"
-    ))?;
+    Ok(BatchFees {
+        gas,
+        max_fee_per_gas: max_fee_per_gas.ok_or(anyhow!("No max_fee_per_gas for batch"))?,
+        max_priority_fee_per_gas: max_priority_fee_per_gas
+            .ok_or(anyhow!("No max_priority_fee_per_gas for batch"))?,
+    })
+}
 
-    let max_priority_fee_per_gas = run.max_priority_fee_per_gas.clone().ok_or(anyhow!(
-        "Run don't have a max_priority_fee_per_gas amount specified"
-    ))?;
+/// The highest max fee per gas a batch can be resent with. The cost of the batch is split
+/// across the runs by their gas, each run's share stays within the user fee it paid.
+pub fn batch_max_fee_per_gas_cap(runs: &[Run], max_fee_per_gas: u128) -> u128 {
+    runs.iter()
+        .map(|run| match (&run.user_fee, &run.gas) {
+            (Some(user_fee), Some(gas)) => match (nat_to_u128(user_fee), nat_to_u128(gas)) {
+                (Some(user_fee), Some(gas)) if gas > 0 => user_fee / gas,
+                _ => max_fee_per_gas,
+            },
+            _ => max_fee_per_gas,
+        })
+        .min()
+        .unwrap_or(max_fee_per_gas)
+        .max(max_fee_per_gas)
+}
 
-    let max_fee_per_gas = base_fee_per_gas + max_priority_fee_per_gas.clone();
+/// Signs one attestation transaction for a batch of runs on the same chain, to be sent from
+/// the signer address. A single run is attested using `attest`, multiple runs are grouped
+/// into one `multiAttest` call. The fees are set by `batch_fees`.
+pub async fn sign_batch_attestation(
+    runs: &[Run],
+    attest_requests: Vec<Token>,
+    chain_id: u32,
+    signer: &Signer,
+) -> Result<SignedTransaction> {
+    if runs.is_empty() || runs.len() != attest_requests.len() {
+        bail!("Each run in the batch needs exactly one attest request");
+    }
+
+    let BatchFees {
+        gas,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    } = batch_fees(runs)?;
+
+    let (function_name, args) = if attest_requests.len() == 1 {
+        ("attest", attest_requests)
+    } else {
+        (
+            "multiAttest",
+            vec![create_multi_attest_request(attest_requests)?],
+        )
+    };
 
     let chain_config = chain_config::get(chain_id)?;
 
-    Ok(eth_transaction(
+    Ok(eth_sign_contract_transaction(
         chain_config.eas_contract.clone(),
         &Arc::clone(&ETH_EAS_CONTRACT),
-        "attest",
-        &[attest_request],
+        function_name,
+        &args,
         gas,
         max_fee_per_gas,
         max_priority_fee_per_gas,
         &chain_config,
+        signer,
     )
     .await?)
 }
@@ -320,3 +457,311 @@ pub async fn estimate_attestation_gas_usage(
     )
     .await?)
 }
+
+const EAS_OFFCHAIN_ATTESTATION_VERSION: u16 = 1;
+
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+const EAS_OFFCHAIN_ATTEST_TYPE: &str = "Attest(uint16 version,bytes32 schema,address recipient,uint64 time,uint64 expirationTime,bool revocable,bytes32 refUID,bytes data)";
+
+/// A signed off-chain attestation, `package` is the shareable JSON package that eas-sdk
+/// can verify.
+pub struct OffchainAttestation {
+    pub uid: String,
+    pub package: String,
+    /// The address the attestation was signed with
+    pub signer: String,
+}
+
+/// Reads the version of the EAS contract, it is part of the EIP-712 domain. Older
+/// contracts expose `VERSION`, newer ones `version()`.
+async fn get_eas_version(chain_config: &ChainConfig) -> Result<String> {
+    let tokens = match eth_call(
+        &chain_config.eas_contract,
+        &Arc::clone(&ETH_EAS_CONTRACT),
+        "VERSION",
+        &[],
+        chain_config,
+    )
+    .await
+    {
+        Ok(tokens) => tokens,
+        Err(_) => {
+            let semver_contract = parse_abi(&["function version() view returns (string)"])?;
+            eth_call(
+                &chain_config.eas_contract,
+                &semver_contract,
+                "version",
+                &[],
+                chain_config,
+            )
+            .await?
+        }
+    };
+
+    match tokens.into_iter().next() {
+        Some(Token::String(version)) => Ok(version),
+        _ => bail!("EAS contract returned no version"),
+    }
+}
+
+/// Computes the UID of an off-chain attestation the way the EAS SDK does, a packed keccak256
+/// hash of the attestation fields. The SDK packs the schema UID as its hex string, not as
+/// the 32 bytes.
+fn get_offchain_uid(
+    schema_uid: &[u8; 32],
+    recipient: &EthAddress,
+    time: u64,
+    revocable: bool,
+    data: &[u8],
+) -> [u8; 32] {
+    let mut packed = Vec::new();
+    packed.extend_from_slice(&EAS_OFFCHAIN_ATTESTATION_VERSION.to_be_bytes());
+    packed.extend_from_slice(format!("0x{}", hex::encode(schema_uid)).as_bytes());
+    packed.extend_from_slice(&recipient.as_byte_array());
+    packed.extend_from_slice(&[0u8; 20]); // attester, not part of the off-chain UID
+    packed.extend_from_slice(&time.to_be_bytes());
+    packed.extend_from_slice(&0_u64.to_be_bytes()); // expirationTime
+    packed.push(revocable as u8);
+    packed.extend_from_slice(&[0u8; 32]); // refUID
+    packed.extend_from_slice(data);
+    packed.extend_from_slice(&0_u32.to_be_bytes()); // bump
+    keccak256(packed)
+}
+
+/// Creates an EAS off-chain attestation, an EIP-712 signature by the recipe's attester over
+/// the attestation fields. No transaction is sent.
+pub async fn create_offchain_attestation(
+    recipe: &Recipe,
+    attestation_data: &str,
+    recipient: &EthAddress,
+    chain_config: &ChainConfig,
+) -> Result<OffchainAttestation> {
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+    let data = encode_abi_data(attestation_data);
+    let time = ic_cdk::api::time() / 1_000_000_000;
+    let eas_contract = Address::from_str(&chain_config.eas_contract)?;
+    let eas_version = get_eas_version(chain_config).await?;
+
+    let domain_separator = keccak256(encode(&[
+        Token::FixedBytes(keccak256(EIP712_DOMAIN_TYPE).to_vec()),
+        Token::FixedBytes(keccak256("EAS Attestation").to_vec()),
+        Token::FixedBytes(keccak256(eas_version.as_bytes()).to_vec()),
+        Token::Uint(chain_config.chain_id.into()),
+        Token::Address(eas_contract),
+    ]));
+
+    let struct_hash = keccak256(encode(&[
+        Token::FixedBytes(keccak256(EAS_OFFCHAIN_ATTEST_TYPE).to_vec()),
+        Token::Uint(EAS_OFFCHAIN_ATTESTATION_VERSION.into()),
+        Token::FixedBytes(schema_uid.to_vec()),
+        Token::Address(H160(recipient.as_byte_array())),
+        Token::Uint(time.into()),
+        Token::Uint(0.into()), // expirationTime
+        Token::Bool(recipe.revokable),
+        Token::FixedBytes([0u8; 32].to_vec()), // refUID
+        Token::FixedBytes(keccak256(&data).to_vec()),
+    ]));
+
+    let digest = keccak256([&[0x19, 0x01][..], &domain_separator[..], &struct_hash[..]].concat());
+    let signer = Signer::active(Attester::for_recipe(recipe));
+    let signer_address = get_eth_address(&signer)
+        .await
+        .map_err(|err| anyhow!("Couldn't derive attester address: {}", err.1))?;
+    let signature = sign_message_hash(digest, &signer).await;
+
+    let uid = format!(
+        "0x{}",
+        hex::encode(get_offchain_uid(
+            &schema_uid,
+            recipient,
+            time,
+            recipe.revokable,
+            &data
+        ))
+    );
+
+    let package = json!({
+        "sig": {
+            "domain": {
+                "name": "EAS Attestation",
+                "version": eas_version,
+                "chainId": chain_config.chain_id.to_string(),
+                "verifyingContract": chain_config.eas_contract,
+            },
+            "primaryType": "Attest",
+            "types": {
+                "Attest": [
+                    { "name": "version", "type": "uint16" },
+                    { "name": "schema", "type": "bytes32" },
+                    { "name": "recipient", "type": "address" },
+                    { "name": "time", "type": "uint64" },
+                    { "name": "expirationTime", "type": "uint64" },
+                    { "name": "revocable", "type": "bool" },
+                    { "name": "refUID", "type": "bytes32" },
+                    { "name": "data", "type": "bytes" },
+                ],
+            },
+            "message": {
+                "version": EAS_OFFCHAIN_ATTESTATION_VERSION,
+                "schema": format!("0x{}", hex::encode(schema_uid)),
+                "recipient": recipient.as_str(),
+                "time": time.to_string(),
+                "expirationTime": "0",
+                "revocable": recipe.revokable,
+                "refUID": format!("0x{}", hex::encode([0u8; 32])),
+                "data": format!("0x{}", hex::encode(&data)),
+            },
+            "uid": uid,
+            "signature": {
+                "v": signature.v,
+                "r": format!("0x{:064x}", signature.r),
+                "s": format!("0x{:064x}", signature.s),
+            },
+        },
+        "signer": signer_address,
+    });
+
+    Ok(OffchainAttestation {
+        uid,
+        package: package.to_string(),
+        signer: signer_address,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: u8, gas: u64, base_fee_per_gas: u64, max_priority_fee_per_gas: u64) -> Run {
+        Run {
+            id: [id; 12],
+            recipe_id: [0; 12],
+            creator: "0x5B38Da6a701c568545dCfcB03FcB875f56beddC4".to_string(),
+            recipient: None,
+            created: 0,
+            chain_id: 10,
+            gas: Some(Nat::from(gas)),
+            base_fee_per_gas: Some(Nat::from(base_fee_per_gas)),
+            max_priority_fee_per_gas: Some(Nat::from(max_priority_fee_per_gas)),
+            user_fee: Some(Nat::from(
+                gas * (base_fee_per_gas + max_priority_fee_per_gas),
+            )),
+            payment_transaction_hash: None,
+            payment_block_number: None,
+            payment_log_index: None,
+            attestation_transaction_hash: None,
+            attestation_transaction_hashes: None,
+            attestation_batch_index: None,
+            attestation_nonce: None,
+            attester_addresses: None,
+            attestation_uid: None,
+            mode: None,
+            offchain_attestation: None,
+            is_cancelled: false,
+            error: None,
+        }
+    }
+
+    fn batch_ids(batches: Vec<Vec<(Run, ())>>) -> Vec<Vec<u8>> {
+        batches
+            .into_iter()
+            .map(|batch| batch.into_iter().map(|(run, _)| run.id[0]).collect())
+            .collect()
+    }
+
+    #[test]
+    fn low_fee_runs_are_batched_separately() {
+        let runs = vec![
+            (run(1, 100_000, 95, 5), ()),
+            (run(2, 100_000, 10, 1), ()),
+            (run(3, 100_000, 90, 2), ()),
+            (run(4, 100_000, 100, 5), ()),
+            (run(5, 100_000, 9, 1), ()),
+        ];
+
+        // Max fees are 105, 100, 92, 11 and 10, only runs within 10% of the highest fee in
+        // the batch are batched together
+        assert_eq!(
+            batch_ids(fee_compatible_batches(runs)),
+            vec![vec![4, 1], vec![3], vec![2, 5]]
+        );
+    }
+
+    #[test]
+    fn batch_fees_sum_gas_and_use_lowest_fees() {
+        let runs = [run(1, 100_000, 95, 5), run(2, 50_000, 90, 7)];
+        let fees = batch_fees(&runs).unwrap();
+        assert_eq!(fees.gas, Nat::from(150_000_u64));
+        assert_eq!(fees.max_fee_per_gas, Nat::from(97_u64));
+        assert_eq!(fees.max_priority_fee_per_gas, Nat::from(5_u64));
+
+        let mut unquoted = run(3, 100_000, 95, 5);
+        unquoted.gas = None;
+        assert!(batch_fees(&[unquoted]).is_err());
+        assert!(batch_fees(&[]).is_err());
+    }
+
+    #[test]
+    fn batch_cost_split_stays_within_user_fees() {
+        let mut runs = vec![run(1, 100_000, 95, 5), run(2, 50_000, 90, 7)];
+        runs[1].user_fee = Some(Nat::from(50_000_u64 * 110));
+        let fees = batch_fees(&runs).unwrap();
+        let max_fee_per_gas = nat_to_u128(&fees.max_fee_per_gas).unwrap();
+
+        // Each run's share of the batch is its gas at the batch fee
+        let cap = batch_max_fee_per_gas_cap(&runs, max_fee_per_gas);
+        assert_eq!(cap, 100);
+        for run in runs.iter() {
+            let share = nat_to_u128(run.gas.as_ref().unwrap()).unwrap() * cap;
+            assert!(share <= nat_to_u128(run.user_fee.as_ref().unwrap()).unwrap());
+        }
+
+        // The cap never lowers the fee the batch was sent with
+        runs[0].user_fee = Some(Nat::from(0_u8));
+        assert_eq!(
+            batch_max_fee_per_gas_cap(&runs, max_fee_per_gas),
+            max_fee_per_gas
+        );
+    }
+
+    // Reference UIDs computed with the EAS SDK `getOffchainUID`, attestation version 1
+    #[test]
+    fn offchain_uid() {
+        let schema_uid = get_schema_uid(
+            "uint256 score",
+            "0x0000000000000000000000000000000000000000",
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(schema_uid),
+            "ef2dbf5e8da46ea760bb4c6eb2635bf04adfc1ade6158e594263363db2a55bcf"
+        );
+
+        let recipient = EthAddress::new("0x5B38Da6a701c568545dCfcB03FcB875f56beddC4").unwrap();
+        let data = encode_abi_data(r#"[{"name":"score","type":"uint256","value":42}]"#);
+
+        assert_eq!(
+            hex::encode(get_offchain_uid(
+                &schema_uid,
+                &recipient,
+                1_700_000_000,
+                true,
+                &data
+            )),
+            "6102f5357150a42a95b9040aa95e73862adda644c72947e605a15c33c45f0c80"
+        );
+        assert_eq!(
+            hex::encode(get_offchain_uid(
+                &schema_uid,
+                &recipient,
+                1_700_000_000,
+                false,
+                &data
+            )),
+            "d72eafc6369311be081a1048ac9f9c11f830e5e328a71a5972d253e702683803"
+        );
+    }
+}
@@ -94,15 +94,6 @@ impl TryFrom<Vec<u8>> for EthAddress {
     }
 }
 
-pub fn remove_address_padding(padded_address: &str) -> String {
-    if padded_address.starts_with("0x") && padded_address.len() >= 42 {
-        let start_index = padded_address.len() - 40;
-        format!("0x{}", &padded_address[start_index..])
-    } else {
-        padded_address.to_string()
-    }
-}
-
 #[macro_export]
 macro_rules! include_abi {
     ($file:expr $(,)?) => {{
@@ -0,0 +1,47 @@
+use crate::{
+    chain_config::{ChainConfig, EvmClientType},
+    declarations::evm_rpc::{
+        Block, BlockTag, FeeHistory, FeeHistoryArgs, GetLogsArgs, GetTransactionCountArgs,
+        LogEntry, SendRawTransactionStatus, TransactionReceipt,
+    },
+};
+use candid::Nat;
+use std::{future::Future, pin::Pin};
+
+use super::{evm_rpc_client::EvmRpcClient, json_rpc_client::JsonRpcClient, rpc::EvmRpcError};
+
+pub type EvmClientResult<'a, T> = Pin<Box<dyn Future<Output = Result<T, EvmRpcError>> + Send + 'a>>;
+
+/// Read and write access to an EVM chain. Results use the evm_rpc canister types for
+/// both implementations.
+pub trait EvmClient: Send + Sync {
+    /// Sends a JSON-RPC request as is and returns the raw JSON-RPC response.
+    fn request(&self, json_rpc_payload: String) -> EvmClientResult<'_, String>;
+
+    fn send_raw_transaction(
+        &self,
+        signed_transaction: String,
+    ) -> EvmClientResult<'_, SendRawTransactionStatus>;
+
+    /// Returns `None` while the transaction has not been included in a block.
+    fn get_transaction_receipt(
+        &self,
+        hash: String,
+    ) -> EvmClientResult<'_, Option<TransactionReceipt>>;
+
+    fn get_logs(&self, args: GetLogsArgs) -> EvmClientResult<'_, Vec<LogEntry>>;
+
+    fn get_block_by_number(&self, block_tag: BlockTag) -> EvmClientResult<'_, Block>;
+
+    fn fee_history(&self, args: FeeHistoryArgs) -> EvmClientResult<'_, FeeHistory>;
+
+    fn get_transaction_count(&self, args: GetTransactionCountArgs) -> EvmClientResult<'_, Nat>;
+}
+
+/// Returns the client the chain is configured to use.
+pub fn evm_client(chain_config: &ChainConfig) -> Box<dyn EvmClient> {
+    match chain_config.evm_client() {
+        EvmClientType::EvmRpc => Box::new(EvmRpcClient::new(chain_config)),
+        EvmClientType::JsonRpc => Box::new(JsonRpcClient::new(chain_config)),
+    }
+}
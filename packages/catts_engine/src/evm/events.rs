@@ -0,0 +1,172 @@
+use crate::{
+    chain_config::ChainConfig,
+    declarations::evm_rpc::{LogEntry, TransactionReceipt},
+    ETH_EAS_CONTRACT, ETH_PAYMENT_CONTRACT,
+};
+use candid::Nat;
+use ethers_core::{
+    abi::{Address, Contract, Log, RawLog, Token},
+    types::{H256, U256},
+    utils::hex,
+};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EventDecodingError {
+    #[error("Log entry was not emitted by the expected contract")]
+    UnexpectedContract,
+
+    #[error("Invalid log entry: {0}")]
+    InvalidLogEntry(String),
+
+    #[error("Abi error: {0}")]
+    Abi(#[from] ethers_core::abi::Error),
+
+    #[error("Event parameter {0} is missing or has the wrong type")]
+    InvalidParam(String),
+
+    #[error("Transaction reverted")]
+    TransactionReverted,
+}
+
+/// `RunPayment(address indexed payer, uint256 amount, bytes12 runId)`, emitted by the
+/// payment contract.
+#[derive(Debug)]
+pub struct RunPaymentEvent {
+    pub payer: Address,
+    pub amount: U256,
+    pub run_id: Vec<u8>,
+}
+
+/// `Attested(address indexed recipient, address indexed attester, bytes32 uid,
+/// bytes32 indexed schema)`, emitted by the EAS contract.
+#[derive(Debug)]
+pub struct AttestedEvent {
+    pub recipient: Address,
+    pub attester: Address,
+    pub uid: Vec<u8>,
+    pub schema: Vec<u8>,
+}
+
+fn to_raw_log(entry: &LogEntry) -> Result<RawLog, EventDecodingError> {
+    let topics = entry
+        .topics
+        .iter()
+        .map(|topic| {
+            H256::from_str(topic)
+                .map_err(|_| EventDecodingError::InvalidLogEntry(format!("Invalid topic {topic}")))
+        })
+        .collect::<Result<Vec<H256>, EventDecodingError>>()?;
+
+    let data = hex::decode(&entry.data)
+        .map_err(|_| EventDecodingError::InvalidLogEntry("Invalid data".to_string()))?;
+
+    Ok(RawLog { topics, data })
+}
+
+/// Decodes a log entry using the event definition from the contract ABI. The event
+/// signature is checked against the first topic.
+fn decode_log(
+    contract: &Contract,
+    event_name: &str,
+    contract_address: &str,
+    entry: &LogEntry,
+) -> Result<Log, EventDecodingError> {
+    if !entry.address.eq_ignore_ascii_case(contract_address) {
+        return Err(EventDecodingError::UnexpectedContract);
+    }
+
+    let event = contract.event(event_name)?;
+    Ok(event.parse_log(to_raw_log(entry)?)?)
+}
+
+fn get_param(log: &Log, name: &str) -> Result<Token, EventDecodingError> {
+    log.params
+        .iter()
+        .find(|param| param.name == name)
+        .map(|param| param.value.clone())
+        .ok_or_else(|| EventDecodingError::InvalidParam(name.to_string()))
+}
+
+fn get_address_param(log: &Log, name: &str) -> Result<Address, EventDecodingError> {
+    get_param(log, name)?
+        .into_address()
+        .ok_or_else(|| EventDecodingError::InvalidParam(name.to_string()))
+}
+
+fn get_fixed_bytes_param(log: &Log, name: &str) -> Result<Vec<u8>, EventDecodingError> {
+    get_param(log, name)?
+        .into_fixed_bytes()
+        .ok_or_else(|| EventDecodingError::InvalidParam(name.to_string()))
+}
+
+pub fn decode_run_payment_event(
+    entry: &LogEntry,
+    chain_config: &ChainConfig,
+) -> Result<RunPaymentEvent, EventDecodingError> {
+    let log = decode_log(
+        &ETH_PAYMENT_CONTRACT,
+        "RunPayment",
+        &chain_config.payment_contract,
+        entry,
+    )?;
+
+    Ok(RunPaymentEvent {
+        payer: get_address_param(&log, "payer")?,
+        amount: get_param(&log, "amount")?
+            .into_uint()
+            .ok_or_else(|| EventDecodingError::InvalidParam("amount".to_string()))?,
+        run_id: get_fixed_bytes_param(&log, "runId")?,
+    })
+}
+
+pub fn decode_attested_event(
+    entry: &LogEntry,
+    chain_config: &ChainConfig,
+) -> Result<AttestedEvent, EventDecodingError> {
+    let log = decode_log(
+        &ETH_EAS_CONTRACT,
+        "Attested",
+        &chain_config.eas_contract,
+        entry,
+    )?;
+
+    Ok(AttestedEvent {
+        recipient: get_address_param(&log, "recipient")?,
+        attester: get_address_param(&log, "attester")?,
+        uid: get_fixed_bytes_param(&log, "uid")?,
+        schema: get_fixed_bytes_param(&log, "schema")?,
+    })
+}
+
+/// Returns the `Attested` events emitted by the chain's EAS contract, in the order they
+/// were emitted. Fails if the transaction reverted.
+pub fn decode_attested_events(
+    receipt: &TransactionReceipt,
+    chain_config: &ChainConfig,
+) -> Result<Vec<AttestedEvent>, EventDecodingError> {
+    if receipt.status != Nat::from(1_u8) {
+        return Err(EventDecodingError::TransactionReverted);
+    }
+
+    let signature = ETH_EAS_CONTRACT.event("Attested")?.signature();
+
+    receipt
+        .logs
+        .iter()
+        .filter(|entry| {
+            entry
+                .address
+                .eq_ignore_ascii_case(&chain_config.eas_contract)
+        })
+        .filter(|entry| {
+            entry
+                .topics
+                .first()
+                .and_then(|topic| H256::from_str(topic).ok())
+                .is_some_and(|topic| topic == signature)
+        })
+        .map(|entry| decode_attested_event(entry, chain_config))
+        .collect()
+}
@@ -0,0 +1,244 @@
+use crate::{
+    chain_config::ChainConfig,
+    declarations::evm_rpc::{
+        evm_rpc, Block, BlockTag, FeeHistory, FeeHistoryArgs, FeeHistoryResult,
+        GetBlockByNumberResult, GetLogsArgs, GetLogsResult, GetTransactionCountArgs,
+        GetTransactionCountResult, GetTransactionReceiptResult, LogEntry, MultiFeeHistoryResult,
+        MultiGetBlockByNumberResult, MultiGetLogsResult, MultiGetTransactionCountResult,
+        MultiGetTransactionReceiptResult, MultiSendRawTransactionResult, RequestResult, RpcConfig,
+        RpcService, RpcServices, SendRawTransactionResult, SendRawTransactionStatus,
+        TransactionReceipt,
+    },
+    ETH_DEFAULT_CALL_CYCLES,
+};
+use candid::Nat;
+use ic_cdk::api::call::{call_with_payment128, CallResult};
+
+use super::{
+    client::{EvmClient, EvmClientResult},
+    rpc::EvmRpcError,
+};
+
+/// Max size of the responses to raw JSON-RPC requests sent through the evm_rpc canister
+const REQUEST_MAX_RESPONSE_BYTES: u64 = 2048;
+
+/// Accesses the chain through the evm_rpc canister. Requests are sent to all of the chain's
+/// `rpc_services` and the responses are compared, raw requests go to `default_rpc_service`.
+pub struct EvmRpcClient {
+    rpc_services: RpcServices,
+    default_rpc_service: RpcService,
+}
+
+impl EvmRpcClient {
+    pub fn new(chain_config: &ChainConfig) -> Self {
+        Self {
+            rpc_services: chain_config.rpc_services.clone(),
+            default_rpc_service: chain_config.default_rpc_service.clone(),
+        }
+    }
+}
+
+impl EvmClient for EvmRpcClient {
+    fn request(&self, json_rpc_payload: String) -> EvmClientResult<'_, String> {
+        Box::pin(async move {
+            let (result,): (RequestResult,) = call_with_payment128(
+                evm_rpc.0,
+                "request",
+                (
+                    self.default_rpc_service.clone(),
+                    json_rpc_payload,
+                    REQUEST_MAX_RESPONSE_BYTES,
+                ),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await
+            .map_err(|e| EvmRpcError::Ic(e.1))?;
+
+            match result {
+                RequestResult::Ok(response) => Ok(response),
+                RequestResult::Err(e) => Err(EvmRpcError::Rpc(format!("{:?}", e))),
+            }
+        })
+    }
+
+    fn send_raw_transaction(
+        &self,
+        signed_transaction: String,
+    ) -> EvmClientResult<'_, SendRawTransactionStatus> {
+        Box::pin(async move {
+            let (result,): (MultiSendRawTransactionResult,) = call_with_payment128(
+                evm_rpc.0,
+                "eth_sendRawTransaction",
+                (
+                    self.rpc_services.clone(),
+                    None::<RpcConfig>,
+                    signed_transaction,
+                ),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await
+            .map_err(|e| EvmRpcError::Ic(e.1))?;
+
+            match result {
+                MultiSendRawTransactionResult::Consistent(SendRawTransactionResult::Ok(status)) => {
+                    Ok(status)
+                }
+                MultiSendRawTransactionResult::Consistent(SendRawTransactionResult::Err(e)) => {
+                    Err(EvmRpcError::Rpc(format!("{:?}", e)))
+                }
+                MultiSendRawTransactionResult::Inconsistent(_) => Err(EvmRpcError::Inconsistent),
+            }
+        })
+    }
+
+    fn get_transaction_receipt(
+        &self,
+        hash: String,
+    ) -> EvmClientResult<'_, Option<TransactionReceipt>> {
+        Box::pin(async move {
+            let (result,): (MultiGetTransactionReceiptResult,) = call_with_payment128(
+                evm_rpc.0,
+                "eth_getTransactionReceipt",
+                (self.rpc_services.clone(), None::<RpcConfig>, hash),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await
+            .map_err(|e| EvmRpcError::Ic(e.1))?;
+
+            match result {
+                MultiGetTransactionReceiptResult::Consistent(GetTransactionReceiptResult::Ok(
+                    receipt,
+                )) => Ok(receipt),
+                MultiGetTransactionReceiptResult::Consistent(GetTransactionReceiptResult::Err(
+                    e,
+                )) => Err(EvmRpcError::Rpc(format!("{:?}", e))),
+                MultiGetTransactionReceiptResult::Inconsistent(_) => Err(EvmRpcError::Inconsistent),
+            }
+        })
+    }
+
+    fn get_logs(&self, args: GetLogsArgs) -> EvmClientResult<'_, Vec<LogEntry>> {
+        Box::pin(async move {
+            let (result,): (MultiGetLogsResult,) = call_with_payment128(
+                evm_rpc.0,
+                "eth_getLogs",
+                (self.rpc_services.clone(), None::<RpcConfig>, args),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await
+            .map_err(|e| EvmRpcError::Ic(e.1))?;
+
+            match result {
+                MultiGetLogsResult::Consistent(GetLogsResult::Ok(entries)) => Ok(entries),
+                MultiGetLogsResult::Consistent(GetLogsResult::Err(e)) => {
+                    Err(EvmRpcError::Rpc(format!("{:?}", e)))
+                }
+                MultiGetLogsResult::Inconsistent(_) => Err(EvmRpcError::Inconsistent),
+            }
+        })
+    }
+
+    /// Returns the block with one caveat: if multiple Rpc services are specified and they
+    /// return inconsistent responses, the earliest block is returned - the "smallest
+    /// common" block.
+    fn get_block_by_number(&self, block_tag: BlockTag) -> EvmClientResult<'_, Block> {
+        Box::pin(async move {
+            let call_result: CallResult<(MultiGetBlockByNumberResult,)> = call_with_payment128(
+                evm_rpc.0,
+                "eth_getBlockByNumber",
+                (
+                    self.rpc_services.clone(),
+                    None::<RpcConfig>,
+                    block_tag,
+                    false,
+                ),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await;
+
+            let block: Block = match call_result {
+                Ok((MultiGetBlockByNumberResult::Consistent(block),)) => match block {
+                    GetBlockByNumberResult::Ok(block) => block,
+                    GetBlockByNumberResult::Err(e) => {
+                        return Err(EvmRpcError::Rpc(format!("{:?}", e)));
+                    }
+                },
+                Ok((MultiGetBlockByNumberResult::Inconsistent(res),)) => {
+                    let mut maybe_earliest_block: Option<Block> = None;
+                    for r in res {
+                        if let (_, GetBlockByNumberResult::Ok(b)) = r {
+                            match maybe_earliest_block {
+                                Some(ref earliest_block) => {
+                                    if b.number < earliest_block.number {
+                                        maybe_earliest_block = Some(b);
+                                    }
+                                }
+                                None => {
+                                    maybe_earliest_block = Some(b);
+                                }
+                            }
+                        }
+                    }
+                    match maybe_earliest_block {
+                        Some(earliest_block) => earliest_block,
+                        None => {
+                            return Err(EvmRpcError::Unexpected("No block found".to_string()));
+                        }
+                    }
+                }
+                Err(e) => {
+                    return Err(EvmRpcError::Ic(e.1));
+                }
+            };
+
+            Ok(block)
+        })
+    }
+
+    fn fee_history(&self, args: FeeHistoryArgs) -> EvmClientResult<'_, FeeHistory> {
+        Box::pin(async move {
+            let call_result: CallResult<(MultiFeeHistoryResult,)> = call_with_payment128(
+                evm_rpc.0,
+                "eth_feeHistory",
+                (self.rpc_services.clone(), None::<RpcConfig>, args),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await;
+
+            match call_result {
+                Ok((MultiFeeHistoryResult::Consistent(fee_history),)) => match fee_history {
+                    FeeHistoryResult::Ok(Some(fee_history)) => Ok(fee_history),
+                    FeeHistoryResult::Ok(None) => {
+                        Err(EvmRpcError::Unexpected("No fee history found".to_string()))
+                    }
+                    FeeHistoryResult::Err(e) => Err(EvmRpcError::Rpc(format!("{:?}", e))),
+                },
+                Ok((MultiFeeHistoryResult::Inconsistent(_),)) => Err(EvmRpcError::Inconsistent),
+                Err(e) => Err(EvmRpcError::Ic(e.1)),
+            }
+        })
+    }
+
+    fn get_transaction_count(&self, args: GetTransactionCountArgs) -> EvmClientResult<'_, Nat> {
+        Box::pin(async move {
+            let call_result: CallResult<(MultiGetTransactionCountResult,)> = call_with_payment128(
+                evm_rpc.0,
+                "eth_getTransactionCount",
+                (self.rpc_services.clone(), None::<RpcConfig>, args),
+                ETH_DEFAULT_CALL_CYCLES,
+            )
+            .await;
+
+            match call_result {
+                Ok((MultiGetTransactionCountResult::Consistent(result),)) => match result {
+                    GetTransactionCountResult::Ok(count) => Ok(count),
+                    GetTransactionCountResult::Err(e) => Err(EvmRpcError::Rpc(format!("{:?}", e))),
+                },
+                Ok((MultiGetTransactionCountResult::Inconsistent(_),)) => {
+                    Err(EvmRpcError::Inconsistent)
+                }
+                Err(e) => Err(EvmRpcError::Ic(e.1)),
+            }
+        })
+    }
+}
@@ -0,0 +1,623 @@
+use crate::{
+    chain_config::ChainConfig,
+    declarations::evm_rpc::{
+        Block, BlockTag, FeeHistory, FeeHistoryArgs, GetLogsArgs, GetTransactionCountArgs,
+        LogEntry, SendRawTransactionStatus, TransactionReceipt,
+    },
+    ETH_DEFAULT_CALL_CYCLES,
+};
+use candid::Nat;
+use ethers_core::utils::{hex, keccak256};
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse,
+    TransformContext,
+};
+use serde_bytes::ByteBuf;
+use serde_json::{json, Value};
+
+use super::{
+    client::{EvmClient, EvmClientResult},
+    rpc::EvmRpcError,
+};
+
+/// Blocks are requested without transaction bodies, logs are requested for single blocks
+const JSON_RPC_MAX_RESPONSE_BYTES: u64 = 256 * 1024;
+
+/// Accesses the chain by sending JSON-RPC requests to the chain's `rpc_api_endpoint` using
+/// HTTPS outcalls. Responses are passed through `json_rpc_transform`.
+pub struct JsonRpcClient {
+    url: String,
+}
+
+impl JsonRpcClient {
+    pub fn new(chain_config: &ChainConfig) -> Self {
+        Self {
+            url: chain_config.rpc_api_endpoint.clone(),
+        }
+    }
+
+    /// Posts the payload and returns the transformed response body. The transaction hash
+    /// is passed on to the transform for transaction submissions.
+    async fn post(
+        &self,
+        json_rpc_payload: String,
+        transaction_hash: Option<&str>,
+    ) -> Result<String, EvmRpcError> {
+        let request = CanisterHttpRequestArgument {
+            url: self.url.clone(),
+            method: HttpMethod::POST,
+            headers: vec![HttpHeader {
+                name: "Content-Type".to_string(),
+                value: "application/json".to_string(),
+            }],
+            body: Some(json_rpc_payload.into_bytes()),
+            max_response_bytes: Some(JSON_RPC_MAX_RESPONSE_BYTES),
+            transform: Some(TransformContext::from_name(
+                "json_rpc_transform".to_string(),
+                transaction_hash.unwrap_or_default().as_bytes().to_vec(),
+            )),
+        };
+
+        let (response,) = http_request(request, ETH_DEFAULT_CALL_CYCLES)
+            .await
+            .map_err(|e| EvmRpcError::Ic(e.1))?;
+
+        let status = u16::try_from(&response.status.0).unwrap_or_default();
+        if !(200..300).contains(&status) {
+            return Err(EvmRpcError::Rpc(format!(
+                "RPC endpoint responded with status {}",
+                status
+            )));
+        }
+
+        String::from_utf8(response.body)
+            .map_err(|_| EvmRpcError::Unexpected("Response is not UTF-8 encoded".to_string()))
+    }
+
+    /// Returns the result of the call, errors returned by the node are mapped to
+    /// `EvmRpcError::Rpc` with the error message.
+    async fn call(
+        &self,
+        method: &str,
+        params: Value,
+        transaction_hash: Option<&str>,
+    ) -> Result<Value, EvmRpcError> {
+        let json_rpc_payload = json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+
+        let response = self.post(json_rpc_payload, transaction_hash).await?;
+        let mut response: Value = serde_json::from_str(&response)
+            .map_err(|e| EvmRpcError::Unexpected(format!("Invalid JSON-RPC response: {}", e)))?;
+
+        match response.get("error") {
+            Some(error) => Err(EvmRpcError::Rpc(
+                error["message"].as_str().unwrap_or_default().to_string(),
+            )),
+            None => Ok(response["result"].take()),
+        }
+    }
+}
+
+impl EvmClient for JsonRpcClient {
+    fn request(&self, json_rpc_payload: String) -> EvmClientResult<'_, String> {
+        Box::pin(async move { self.post(json_rpc_payload, None).await })
+    }
+
+    fn send_raw_transaction(
+        &self,
+        signed_transaction: String,
+    ) -> EvmClientResult<'_, SendRawTransactionStatus> {
+        Box::pin(async move {
+            let transaction_hash = transaction_hash(&signed_transaction)?;
+            let result = self
+                .call(
+                    "eth_sendRawTransaction",
+                    json!([signed_transaction]),
+                    Some(&transaction_hash),
+                )
+                .await;
+
+            send_raw_transaction_status(result, transaction_hash)
+        })
+    }
+
+    fn get_transaction_receipt(
+        &self,
+        hash: String,
+    ) -> EvmClientResult<'_, Option<TransactionReceipt>> {
+        Box::pin(async move {
+            let result = self
+                .call("eth_getTransactionReceipt", json!([hash]), None)
+                .await?;
+
+            optional_transaction_receipt(&result)
+        })
+    }
+
+    fn get_logs(&self, args: GetLogsArgs) -> EvmClientResult<'_, Vec<LogEntry>> {
+        Box::pin(async move {
+            let mut filter = json!({ "address": args.addresses });
+            if let Some(from_block) = &args.fromBlock {
+                filter["fromBlock"] = block_tag(from_block);
+            }
+            if let Some(to_block) = &args.toBlock {
+                filter["toBlock"] = block_tag(to_block);
+            }
+            if let Some(topics) = &args.topics {
+                filter["topics"] = json!(topics);
+            }
+
+            let result = self.call("eth_getLogs", json!([filter]), None).await?;
+
+            result
+                .as_array()
+                .ok_or_else(|| EvmRpcError::Unexpected("Invalid logs".to_string()))?
+                .iter()
+                .map(log_entry)
+                .collect()
+        })
+    }
+
+    fn get_block_by_number(&self, tag: BlockTag) -> EvmClientResult<'_, Block> {
+        Box::pin(async move {
+            let result = self
+                .call(
+                    "eth_getBlockByNumber",
+                    json!([block_tag(&tag), false]),
+                    None,
+                )
+                .await?;
+
+            match result {
+                Value::Null => Err(EvmRpcError::Unexpected("No block found".to_string())),
+                result => block(&result),
+            }
+        })
+    }
+
+    fn fee_history(&self, args: FeeHistoryArgs) -> EvmClientResult<'_, FeeHistory> {
+        Box::pin(async move {
+            let reward_percentiles = args
+                .rewardPercentiles
+                .map(ByteBuf::into_vec)
+                .unwrap_or_default();
+
+            let result = self
+                .call(
+                    "eth_feeHistory",
+                    json!([
+                        quantity_param(&args.blockCount),
+                        block_tag(&args.newestBlock),
+                        reward_percentiles
+                    ]),
+                    None,
+                )
+                .await?;
+
+            match result {
+                Value::Null => Err(EvmRpcError::Unexpected("No fee history found".to_string())),
+                result => fee_history(&result),
+            }
+        })
+    }
+
+    fn get_transaction_count(&self, args: GetTransactionCountArgs) -> EvmClientResult<'_, Nat> {
+        Box::pin(async move {
+            let result = self
+                .call(
+                    "eth_getTransactionCount",
+                    json!([args.address, block_tag(&args.block)]),
+                    None,
+                )
+                .await?;
+
+            quantity(&result)
+                .ok_or_else(|| EvmRpcError::Unexpected("Invalid transaction count".to_string()))
+        })
+    }
+}
+
+/// Maps the node's errors for rejected transactions to the statuses used by the evm_rpc
+/// canister.
+fn send_raw_transaction_status(
+    result: Result<Value, EvmRpcError>,
+    transaction_hash: String,
+) -> Result<SendRawTransactionStatus, EvmRpcError> {
+    match result {
+        Ok(_) => Ok(SendRawTransactionStatus::Ok(Some(transaction_hash))),
+        Err(EvmRpcError::Rpc(message)) => {
+            let lowercase_message = message.to_lowercase();
+            if lowercase_message.contains("nonce too low") {
+                Ok(SendRawTransactionStatus::NonceTooLow)
+            } else if lowercase_message.contains("nonce too high") {
+                Ok(SendRawTransactionStatus::NonceTooHigh)
+            } else if lowercase_message.contains("insufficient funds") {
+                Ok(SendRawTransactionStatus::InsufficientFunds)
+            } else {
+                Err(EvmRpcError::Rpc(message))
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Reduces a JSON-RPC response to its result or error, headers and the request id are
+/// dropped so that the responses of all replicas are identical. For transaction
+/// submissions, `transaction_hash` holds the hash of the submitted transaction: every
+/// replica sends the same transaction and all but the first are reported as already
+/// known by the node, these are reported as successful instead.
+pub fn transform_response(response: HttpResponse, transaction_hash: &[u8]) -> HttpResponse {
+    let status = u16::try_from(&response.status.0).unwrap_or_default();
+    if !(200..300).contains(&status) {
+        return HttpResponse {
+            status: response.status,
+            ..Default::default()
+        };
+    }
+
+    let Ok(body) = serde_json::from_slice::<Value>(&response.body) else {
+        return HttpResponse {
+            status: response.status,
+            body: response.body,
+            ..Default::default()
+        };
+    };
+
+    let transaction_hash = String::from_utf8_lossy(transaction_hash);
+    let mut canonical = json!({ "id": 1, "jsonrpc": "2.0" });
+    match body.get("error") {
+        Some(error) => {
+            let message = error["message"].as_str().unwrap_or_default();
+            let lowercase_message = message.to_lowercase();
+            if !transaction_hash.is_empty()
+                && (lowercase_message.contains("already known")
+                    || lowercase_message.contains("known transaction"))
+            {
+                canonical["result"] = json!(transaction_hash);
+            } else {
+                canonical["error"] = json!({
+                    "code": error["code"].as_i64().unwrap_or_default(),
+                    "message": message,
+                });
+            }
+        }
+        None if !transaction_hash.is_empty() => canonical["result"] = json!(transaction_hash),
+        None => canonical["result"] = body["result"].clone(),
+    }
+
+    HttpResponse {
+        status: response.status,
+        body: canonical.to_string().into_bytes(),
+        ..Default::default()
+    }
+}
+
+/// Hash of a signed transaction, as returned by `eth_sendRawTransaction`.
+pub fn transaction_hash(signed_transaction: &str) -> Result<String, EvmRpcError> {
+    let bytes = hex::decode(
+        signed_transaction
+            .strip_prefix("0x")
+            .unwrap_or(signed_transaction),
+    )
+    .map_err(|_| EvmRpcError::Unexpected("Invalid signed transaction".to_string()))?;
+
+    Ok(format!("0x{}", hex::encode(keccak256(bytes))))
+}
+
+fn block_tag(block_tag: &BlockTag) -> Value {
+    match block_tag {
+        BlockTag::Earliest => json!("earliest"),
+        BlockTag::Safe => json!("safe"),
+        BlockTag::Finalized => json!("finalized"),
+        BlockTag::Latest => json!("latest"),
+        BlockTag::Pending => json!("pending"),
+        BlockTag::Number(number) => quantity_param(number),
+    }
+}
+
+fn quantity_param(quantity: &Nat) -> Value {
+    json!(format!("0x{:x}", quantity.0))
+}
+
+fn quantity(value: &Value) -> Option<Nat> {
+    value
+        .as_str()?
+        .strip_prefix("0x")
+        .and_then(|quantity| u128::from_str_radix(quantity, 16).ok())
+        .map(Nat::from)
+}
+
+fn required_quantity(value: &Value, field: &str) -> Result<Nat, EvmRpcError> {
+    quantity(&value[field]).ok_or_else(|| EvmRpcError::Unexpected(format!("Invalid {}", field)))
+}
+
+/// Fields that are missing on some chains, for instance `baseFeePerGas` before EIP-1559 or
+/// `totalDifficulty` after the merge, default to 0.
+fn quantity_or_zero(value: &Value, field: &str) -> Nat {
+    quantity(&value[field]).unwrap_or_default()
+}
+
+fn string(value: &Value, field: &str) -> String {
+    value[field].as_str().unwrap_or_default().to_string()
+}
+
+fn optional_string(value: &Value, field: &str) -> Option<String> {
+    value[field].as_str().map(str::to_string)
+}
+
+fn strings(value: &Value, field: &str) -> Vec<String> {
+    value[field]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn log_entry(value: &Value) -> Result<LogEntry, EvmRpcError> {
+    Ok(LogEntry {
+        transactionHash: optional_string(value, "transactionHash"),
+        blockNumber: quantity(&value["blockNumber"]),
+        data: string(value, "data"),
+        blockHash: optional_string(value, "blockHash"),
+        transactionIndex: quantity(&value["transactionIndex"]),
+        topics: strings(value, "topics"),
+        address: string(value, "address"),
+        logIndex: quantity(&value["logIndex"]),
+        removed: value["removed"].as_bool().unwrap_or(false),
+    })
+}
+
+/// Nodes return `null` for transactions that have not been included in a block yet.
+fn optional_transaction_receipt(value: &Value) -> Result<Option<TransactionReceipt>, EvmRpcError> {
+    match value {
+        Value::Null => Ok(None),
+        receipt => transaction_receipt(receipt).map(Some),
+    }
+}
+
+fn transaction_receipt(value: &Value) -> Result<TransactionReceipt, EvmRpcError> {
+    let logs = match value["logs"].as_array() {
+        Some(logs) => logs.iter().map(log_entry).collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+
+    Ok(TransactionReceipt {
+        to: string(value, "to"),
+        status: required_quantity(value, "status")?,
+        transactionHash: string(value, "transactionHash"),
+        blockNumber: required_quantity(value, "blockNumber")?,
+        from: string(value, "from"),
+        logs,
+        blockHash: string(value, "blockHash"),
+        r#type: string(value, "type"),
+        transactionIndex: required_quantity(value, "transactionIndex")?,
+        effectiveGasPrice: quantity_or_zero(value, "effectiveGasPrice"),
+        logsBloom: string(value, "logsBloom"),
+        contractAddress: optional_string(value, "contractAddress"),
+        gasUsed: required_quantity(value, "gasUsed")?,
+    })
+}
+
+fn block(value: &Value) -> Result<Block, EvmRpcError> {
+    Ok(Block {
+        miner: string(value, "miner"),
+        totalDifficulty: quantity_or_zero(value, "totalDifficulty"),
+        receiptsRoot: string(value, "receiptsRoot"),
+        stateRoot: string(value, "stateRoot"),
+        hash: string(value, "hash"),
+        difficulty: quantity_or_zero(value, "difficulty"),
+        size: quantity_or_zero(value, "size"),
+        uncles: strings(value, "uncles"),
+        baseFeePerGas: quantity_or_zero(value, "baseFeePerGas"),
+        extraData: string(value, "extraData"),
+        transactionsRoot: optional_string(value, "transactionsRoot"),
+        sha3Uncles: string(value, "sha3Uncles"),
+        nonce: quantity_or_zero(value, "nonce"),
+        number: required_quantity(value, "number")?,
+        timestamp: required_quantity(value, "timestamp")?,
+        transactions: strings(value, "transactions"),
+        gasLimit: required_quantity(value, "gasLimit")?,
+        logsBloom: string(value, "logsBloom"),
+        parentHash: string(value, "parentHash"),
+        gasUsed: required_quantity(value, "gasUsed")?,
+        mixHash: string(value, "mixHash"),
+    })
+}
+
+fn fee_history(value: &Value) -> Result<FeeHistory, EvmRpcError> {
+    let quantities = |values: &Value| -> Result<Vec<Nat>, EvmRpcError> {
+        values
+            .as_array()
+            .map(|values| values.iter().map(quantity).collect::<Option<Vec<_>>>())
+            .unwrap_or(Some(vec![]))
+            .ok_or_else(|| EvmRpcError::Unexpected("Invalid fee history".to_string()))
+    };
+
+    let reward = match value["reward"].as_array() {
+        Some(rewards) => rewards
+            .iter()
+            .map(quantities)
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+
+    let gas_used_ratio = value["gasUsedRatio"]
+        .as_array()
+        .map(|ratios| ratios.iter().filter_map(Value::as_f64).collect())
+        .unwrap_or_default();
+
+    Ok(FeeHistory {
+        reward,
+        gasUsedRatio: gas_used_ratio,
+        oldestBlock: required_quantity(value, "oldestBlock")?,
+        baseFeePerGas: quantities(&value["baseFeePerGas"])?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+
+    fn response(status: u64, body: &str) -> HttpResponse {
+        HttpResponse {
+            status: Nat::from(status),
+            headers: vec![HttpHeader {
+                name: "Date".to_string(),
+                value: "Mon, 19 Oct 2026 10:00:00 GMT".to_string(),
+            }],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn body(response: &HttpResponse) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    #[test]
+    fn transform_strips_id_and_headers() {
+        let transformed = transform_response(
+            response(200, r#"{"jsonrpc":"2.0","id":83,"result":"0x4b7"}"#),
+            b"",
+        );
+        assert!(transformed.headers.is_empty());
+        assert_eq!(
+            body(&transformed),
+            json!({ "id": 1, "jsonrpc": "2.0", "result": "0x4b7" })
+        );
+    }
+
+    #[test]
+    fn transform_canonicalises_errors() {
+        // Nodes add fields such as `data` that can differ between replicas
+        let transformed = transform_response(
+            response(
+                200,
+                r#"{"id":7,"jsonrpc":"2.0","error":{"code":-32000,"message":"nonce too low","data":{"node":"a"}}}"#,
+            ),
+            b"",
+        );
+        assert_eq!(
+            body(&transformed),
+            json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "error": { "code": -32000, "message": "nonce too low" },
+            })
+        );
+
+        let transformed = transform_response(response(503, "Service Unavailable"), b"");
+        assert_eq!(transformed.status, Nat::from(503_u64));
+        assert!(transformed.body.is_empty());
+    }
+
+    #[test]
+    fn transform_maps_already_known_to_hash() {
+        for message in ["already known", "Known transaction: 5c504ed4"] {
+            let error = json!({
+                "id": 2,
+                "jsonrpc": "2.0",
+                "error": { "code": -32000, "message": message },
+            });
+            let transformed =
+                transform_response(response(200, &error.to_string()), HASH.as_bytes());
+            assert_eq!(
+                body(&transformed),
+                json!({ "id": 1, "jsonrpc": "2.0", "result": HASH })
+            );
+        }
+
+        // The first replica to submit gets the hash from the node
+        let transformed = transform_response(
+            response(
+                200,
+                &format!(r#"{{"id":3,"jsonrpc":"2.0","result":"{}"}}"#, HASH),
+            ),
+            HASH.as_bytes(),
+        );
+        assert_eq!(
+            body(&transformed),
+            json!({ "id": 1, "jsonrpc": "2.0", "result": HASH })
+        );
+
+        // Without a transaction hash the error is kept
+        let transformed = transform_response(
+            response(
+                200,
+                r#"{"id":4,"jsonrpc":"2.0","error":{"code":-32000,"message":"already known"}}"#,
+            ),
+            b"",
+        );
+        assert_eq!(body(&transformed)["error"]["message"], "already known");
+    }
+
+    #[test]
+    fn send_raw_transaction_status_from_node_errors() {
+        let status = |message: &str| {
+            send_raw_transaction_status(Err(EvmRpcError::Rpc(message.to_string())), HASH.into())
+        };
+        assert!(matches!(
+            status("Nonce too low: next nonce 5, tx nonce 4"),
+            Ok(SendRawTransactionStatus::NonceTooLow)
+        ));
+        assert!(matches!(
+            status("nonce too high"),
+            Ok(SendRawTransactionStatus::NonceTooHigh)
+        ));
+        assert!(matches!(
+            status("insufficient funds for gas * price + value"),
+            Ok(SendRawTransactionStatus::InsufficientFunds)
+        ));
+        assert!(matches!(
+            status("replacement transaction underpriced"),
+            Err(EvmRpcError::Rpc(_))
+        ));
+
+        match send_raw_transaction_status(Ok(json!(HASH)), HASH.into()) {
+            Ok(SendRawTransactionStatus::Ok(Some(hash))) => assert_eq!(hash, HASH),
+            other => panic!("Unexpected status: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn receipt_is_none_until_included() {
+        assert!(optional_transaction_receipt(&Value::Null)
+            .unwrap()
+            .is_none());
+
+        let receipt = optional_transaction_receipt(&json!({
+            "transactionHash": HASH,
+            "status": "0x1",
+            "blockNumber": "0x10",
+            "transactionIndex": "0x0",
+            "gasUsed": "0x5208",
+            "logs": [{
+                "address": "0x4200000000000000000000000000000000000021",
+                "topics": ["0x8bf46bf4cfd674fa735a3d63ec1c9ad4153f033c290341f3a588b75685141b35"],
+                "data": "0x",
+                "logIndex": "0x0",
+            }],
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(receipt.transactionHash, HASH);
+        assert_eq!(receipt.status, Nat::from(1_u8));
+        assert_eq!(receipt.blockNumber, Nat::from(16_u8));
+        assert_eq!(receipt.effectiveGasPrice, Nat::from(0_u8));
+        assert_eq!(receipt.logs.len(), 1);
+        assert_eq!(receipt.logs[0].logIndex, Some(Nat::from(0_u8)));
+
+        // Receipts without a status are rejected, the run can't be confirmed from them
+        assert!(optional_transaction_receipt(&json!({ "blockNumber": "0x10" })).is_err());
+    }
+}
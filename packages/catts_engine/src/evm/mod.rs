@@ -1,3 +1,8 @@
+pub mod client;
+pub mod events;
+pub mod evm_rpc_client;
+pub mod json_rpc_client;
+pub mod nonce;
 pub mod rpc;
 pub mod types;
 pub mod util;
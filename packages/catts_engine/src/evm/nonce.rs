@@ -0,0 +1,204 @@
+use crate::{
+    chain_config::ChainConfig, declarations::evm_rpc::BlockTag, logger, NONCES, RECIPE_NONCES,
+};
+
+use super::{
+    rpc::{eth_get_transaction_count, EvmRpcError},
+    types::{Attester, NonceState, Signer},
+};
+
+fn get_state(chain_id: u32, attester: &Attester) -> Option<NonceState> {
+    match attester {
+        Attester::Canister => NONCES.with_borrow(|nonces| nonces.get(&chain_id)),
+        Attester::Recipe(recipe_id) => {
+            RECIPE_NONCES.with_borrow(|nonces| nonces.get(&(chain_id, *recipe_id)))
+        }
+    }
+}
+
+fn set_state(chain_id: u32, attester: &Attester, state: NonceState) {
+    match attester {
+        Attester::Canister => NONCES.with_borrow_mut(|nonces| {
+            nonces.insert(chain_id, state);
+        }),
+        Attester::Recipe(recipe_id) => RECIPE_NONCES.with_borrow_mut(|nonces| {
+            nonces.insert((chain_id, *recipe_id), state);
+        }),
+    }
+}
+
+/// Marks the nonces of all chains and attesters as out of sync. Transactions that were
+/// being prepared when the canister stopped were never sent, so the next nonce is read
+/// from the chain.
+pub fn invalidate_nonces() {
+    NONCES.with_borrow_mut(|nonces| {
+        let chain_ids: Vec<u32> = nonces.iter().map(|(chain_id, _)| chain_id).collect();
+        for chain_id in chain_ids {
+            if let Some(mut state) = nonces.get(&chain_id) {
+                state.needs_sync = true;
+                nonces.insert(chain_id, state);
+            }
+        }
+    });
+    RECIPE_NONCES.with_borrow_mut(|nonces| {
+        let keys: Vec<_> = nonces.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            if let Some(mut state) = nonces.get(&key) {
+                state.needs_sync = true;
+                nonces.insert(key, state);
+            }
+        }
+    });
+}
+
+/// Forgets the nonces of all chains and attesters, used when the signing key is rotated.
+/// The addresses of the new key start from the nonce read from the chain.
+pub fn reset_nonces() {
+    NONCES.with_borrow_mut(|nonces| {
+        let chain_ids: Vec<u32> = nonces.iter().map(|(chain_id, _)| chain_id).collect();
+        for chain_id in chain_ids {
+            nonces.remove(&chain_id);
+        }
+    });
+    RECIPE_NONCES.with_borrow_mut(|nonces| {
+        let keys: Vec<_> = nonces.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            nonces.remove(&key);
+        }
+    });
+}
+
+/// Marks the nonce of a signer on a chain as out of sync, for instance after the node
+/// rejected a transaction because of its nonce. Only the nonces of the active key are
+/// tracked, transactions still draining on a rotated key don't affect them.
+pub fn invalidate_nonce(chain_id: u32, signer: &Signer) {
+    if !signer.is_active() {
+        return;
+    }
+    let mut state = get_state(chain_id, &signer.attester).unwrap_or_default();
+    state.needs_sync = true;
+    set_state(chain_id, &signer.attester, state);
+}
+
+fn needs_sync(chain_id: u32, attester: &Attester) -> bool {
+    get_state(chain_id, attester)
+        .map(|state| state.needs_sync)
+        .unwrap_or(true)
+}
+
+fn key_rotated() -> EvmRpcError {
+    EvmRpcError::Unexpected("Signing key was rotated".to_string())
+}
+
+async fn sync_nonce(chain_config: &ChainConfig, signer: &Signer) -> Result<(), EvmRpcError> {
+    let pending_count = eth_get_transaction_count(BlockTag::Pending, chain_config, signer).await?;
+    let pending_count = u64::try_from(&pending_count.0)
+        .map_err(|_| EvmRpcError::Unexpected("Transaction count overflow".to_string()))?;
+
+    // The count belongs to the address of a key that is no longer active
+    if !signer.is_active() {
+        return Err(key_rotated());
+    }
+
+    let attester = &signer.attester;
+    let mut state = get_state(chain_config.chain_id, attester).unwrap_or_default();
+    if state.needs_sync {
+        // Reservations made before the state was invalidated can't be trusted, the
+        // chain is the source of truth
+        state.next_nonce = pending_count;
+    } else {
+        // Another task synced while the count was being fetched and might already
+        // have handed out nonces above the count
+        state.next_nonce = state.next_nonce.max(pending_count);
+    }
+    state.pending.retain(|nonce| *nonce < state.next_nonce);
+    state.needs_sync = false;
+    set_state(chain_config.chain_id, attester, state);
+
+    logger::debug(
+        format!(
+            "Nonce synced for chain {}, attester {:?}, pending count: {}",
+            chain_config.chain_id, attester, pending_count
+        )
+        .as_str(),
+    );
+
+    Ok(())
+}
+
+/// Reserves the next nonce for a transaction from the signer's address. The nonce is
+/// handed out without awaiting, so concurrent tasks never get the same nonce. Nonces are
+/// only handed out for the active key.
+pub async fn reserve_nonce(
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> Result<u64, EvmRpcError> {
+    if needs_sync(chain_config.chain_id, &signer.attester) {
+        sync_nonce(chain_config, signer).await?;
+    }
+
+    if !signer.is_active() {
+        return Err(key_rotated());
+    }
+
+    let attester = &signer.attester;
+    let mut state = get_state(chain_config.chain_id, attester).unwrap_or_default();
+    let nonce = state.next_nonce;
+    state.next_nonce += 1;
+    state.pending.push(nonce);
+    set_state(chain_config.chain_id, attester, state);
+
+    Ok(nonce)
+}
+
+/// Releases a nonce once a transaction using it has been mined.
+pub fn release_nonce(chain_id: u32, signer: &Signer, nonce: u64) {
+    if !signer.is_active() {
+        return;
+    }
+    if let Some(mut state) = get_state(chain_id, &signer.attester) {
+        state.pending.retain(|pending| *pending != nonce);
+        set_state(chain_id, &signer.attester, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(next_nonce: u64) -> NonceState {
+        NonceState {
+            next_nonce,
+            pending: vec![],
+            needs_sync: false,
+        }
+    }
+
+    #[test]
+    fn attesters_have_separate_nonces() {
+        let recipe_a = Attester::Recipe([1; 12]);
+        let recipe_b = Attester::Recipe([2; 12]);
+        set_state(1, &Attester::Canister, state(5));
+        set_state(1, &recipe_a, state(2));
+        set_state(1, &recipe_b, state(7));
+        set_state(2, &recipe_a, state(9));
+
+        let next_nonce = |chain_id, attester| {
+            get_state(chain_id, attester).map(|state: NonceState| state.next_nonce)
+        };
+        assert_eq!(next_nonce(1, &Attester::Canister), Some(5));
+        assert_eq!(next_nonce(1, &recipe_a), Some(2));
+        assert_eq!(next_nonce(1, &recipe_b), Some(7));
+        assert_eq!(next_nonce(2, &recipe_a), Some(9));
+        assert_eq!(next_nonce(2, &Attester::Canister), None);
+
+        invalidate_nonces();
+        assert!(needs_sync(1, &Attester::Canister));
+        assert!(needs_sync(1, &recipe_a));
+        assert!(needs_sync(2, &recipe_a));
+
+        reset_nonces();
+        assert_eq!(next_nonce(1, &Attester::Canister), None);
+        assert_eq!(next_nonce(1, &recipe_b), None);
+    }
+}
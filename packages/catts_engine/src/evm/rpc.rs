@@ -1,15 +1,10 @@
 use crate::{
-    chain_config::ChainConfig,
+    chain_config::{ChainConfig, TransactionType},
     declarations::evm_rpc::{
-        evm_rpc, Block, BlockTag, FeeHistory, FeeHistoryArgs, FeeHistoryResult,
-        GetBlockByNumberResult, GetLogsArgs, GetLogsResult, GetTransactionCountArgs,
-        GetTransactionCountResult, GetTransactionReceiptResult, LogEntry, MultiFeeHistoryResult,
-        MultiGetBlockByNumberResult, MultiGetLogsResult, MultiGetTransactionCountResult,
-        MultiGetTransactionReceiptResult, MultiSendRawTransactionResult, RequestResult, RpcConfig,
-        RpcError, SendRawTransactionResult, SendRawTransactionStatus, TransactionReceipt,
+        Block, BlockTag, FeeHistory, FeeHistoryArgs, GetLogsArgs, GetTransactionCountArgs,
+        LogEntry, SendRawTransactionStatus, TransactionReceipt,
     },
     evm::util::{ecdsa_key_id, nat_to_u256, nat_to_u64},
-    ETH_DEFAULT_CALL_CYCLES,
 };
 use candid::Nat;
 use ethers_core::{
@@ -18,75 +13,104 @@ use ethers_core::{
         Contract, Token,
     },
     k256::{self, elliptic_curve::sec1::ToEncodedPoint, PublicKey},
+    types::{
+        transaction::{
+            eip1559::Eip1559TransactionRequest,
+            eip2930::{AccessList, AccessListWithGasUsed, Eip2930TransactionRequest},
+        },
+        Signature, TransactionRequest,
+    },
     utils::{hex, keccak256},
 };
 use ic_cdk::api::{
-    call::{call_with_payment128, CallResult, RejectionCode},
+    call::RejectionCode,
     management_canister::ecdsa::{
         ecdsa_public_key, sign_with_ecdsa, EcdsaPublicKeyArgument, SignWithEcdsaArgument,
     },
 };
 use serde_bytes::ByteBuf;
 use serde_json::json;
-use std::{cell::RefCell, str::FromStr};
+use std::{cell::RefCell, collections::HashMap, str::FromStr};
 use thiserror::Error;
 
 use super::{
-    types::{JsonRpcErrorResponse, JsonRpcResponse, SignRequest},
+    client::{evm_client, EvmClient},
+    json_rpc_client::transaction_hash,
+    nonce::{invalidate_nonce, reserve_nonce},
+    types::{Attester, JsonRpcErrorResponse, JsonRpcResponse, SignRequest, Signer},
     util::get_abi_function_by_name,
 };
 
-async fn next_id(chain_config: &ChainConfig) -> Nat {
-    let res: CallResult<(MultiGetTransactionCountResult,)> = call_with_payment128(
-        crate::declarations::evm_rpc::evm_rpc.0,
-        "eth_getTransactionCount",
-        (
-            chain_config.rpc_services.clone(),
-            None::<RpcConfig>,
-            GetTransactionCountArgs {
-                address: get_self_eth_address().await,
-                block: BlockTag::Latest,
-            },
-        ),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await;
-    match res {
-        Ok((MultiGetTransactionCountResult::Consistent(GetTransactionCountResult::Ok(id)),)) => id,
-        Ok((inconsistent,)) => ic_cdk::trap(&format!("Inconsistent: {inconsistent:?}")),
-        Err(err) => ic_cdk::trap(&format!("{:?}", err)),
-    }
-}
-
 #[derive(Error, Debug)]
 pub enum EthTransactionError {
     #[error("Unable to encode args")]
     ArgsEncoding,
 
+    #[error("Unable to decode output")]
+    OutputDecoding,
+
     #[error("Call error")]
     CallError((RejectionCode, String)),
 
     #[error("No transaction Id returned")]
     NoTransactionId,
 
-    #[error("MultiSendRawTransaction: {0:?}")]
-    MultiSendRawTransaction(MultiSendRawTransactionResult),
-
-    #[error("Rpc error: {0:?}")]
-    RpcError(RpcError),
-
     #[error("Json error: {0}")]
     JsonError(#[from] serde_json::Error),
 
     #[error("JsonRpc error: {0:?}")]
     JsonRpcError(JsonRpcErrorResponse),
 
-    #[error("Inconsistent response")]
-    InconsistentResponse,
+    #[error("Nonce too low")]
+    NonceTooLow,
+
+    #[error("Nonce too high")]
+    NonceTooHigh,
+
+    #[error("Insufficient funds")]
+    InsufficientFunds,
+
+    #[error("Client error: {0}")]
+    Client(EvmRpcError),
+
+    #[error("Unable to reserve nonce: {0}")]
+    Nonce(#[from] EvmRpcError),
+}
+
+impl EthTransactionError {
+    /// Nonce errors clear up once the nonce has been synced with the chain, the
+    /// transaction can be sent again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            EthTransactionError::NonceTooLow
+                | EthTransactionError::NonceTooHigh
+                | EthTransactionError::Nonce(_)
+        )
+    }
 }
 
+/// A transaction that has been signed and sent, along with the request it was signed from.
+/// The request can be used to resend the transaction with the same nonce.
+#[derive(Debug)]
+pub struct SentTransaction {
+    pub hash: String,
+    pub request: SignRequest,
+}
+
+/// A transaction that has been signed with a reserved nonce but not sent yet. The hash is
+/// known before sending, a failed send can still have reached the chain.
+#[derive(Debug)]
+pub struct SignedTransaction {
+    pub hash: String,
+    pub signed_data: String,
+    pub request: SignRequest,
+}
+
+/// Reserves the next nonce of the signer and signs a contract call, send it with
+/// `eth_send_signed_transaction`.
 #[allow(clippy::too_many_arguments)]
-pub async fn eth_transaction(
+pub async fn eth_sign_contract_transaction(
     contract_address: String,
     abi_contract: &Contract,
     function_name: &str,
@@ -95,45 +119,108 @@ pub async fn eth_transaction(
     max_fee_per_gas: Nat,
     max_priority_fee_per_gas: Nat,
     chain_config: &ChainConfig,
-) -> Result<String, EthTransactionError> {
+    signer: &Signer,
+) -> Result<SignedTransaction, EthTransactionError> {
     let abi_function = get_abi_function_by_name(abi_contract, function_name);
     let data = abi_function
         .encode_input(args)
         .map_err(|_| EthTransactionError::ArgsEncoding)?;
 
-    let signed_data = sign_transaction(SignRequest {
+    let request = SignRequest {
         chain_id: chain_config.chain_id.into(),
         to: contract_address,
         gas,
         max_fee_per_gas,
         max_priority_fee_per_gas,
         value: 0_u8.into(),
-        nonce: next_id(chain_config).await,
+        nonce: reserve_nonce(chain_config, signer).await?.into(),
         data: Some(data.into()),
+    };
+
+    let signed_data = eth_sign_transaction(request.clone(), chain_config, signer).await;
+    let hash = match transaction_hash(&signed_data) {
+        Ok(hash) => hash,
+        Err(err) => {
+            invalidate_nonce(chain_config.chain_id, signer);
+            return Err(EthTransactionError::Client(err));
+        }
+    };
+
+    Ok(SignedTransaction {
+        hash,
+        signed_data,
+        request,
     })
-    .await;
-
-    let (res,): (MultiSendRawTransactionResult,) = call_with_payment128(
-        crate::declarations::evm_rpc::evm_rpc.0,
-        "eth_sendRawTransaction",
-        (
-            chain_config.rpc_services.clone(),
-            None::<RpcConfig>,
-            signed_data.clone(),
-        ),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await
-    .map_err(EthTransactionError::CallError)?;
-
-    match res {
-        MultiSendRawTransactionResult::Consistent(SendRawTransactionResult::Ok(
-            SendRawTransactionStatus::Ok(txid),
-        )) => match txid {
-            Some(txid) => Ok(txid),
-            None => Err(EthTransactionError::NoTransactionId),
-        },
-        other => Err(EthTransactionError::MultiSendRawTransaction(other)),
+}
+
+/// Sends a transaction signed with `eth_sign_contract_transaction`.
+pub async fn eth_send_signed_transaction(
+    signed_transaction: SignedTransaction,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> Result<SentTransaction, EthTransactionError> {
+    let SignedTransaction {
+        signed_data,
+        request,
+        ..
+    } = signed_transaction;
+
+    match send_raw_transaction(evm_client(chain_config).as_ref(), signed_data).await {
+        Ok(hash) => Ok(SentTransaction { hash, request }),
+        Err(err) => {
+            // The reserved nonce is left unused or the transaction might have reached some
+            // of the nodes, the next nonce has to be read from the chain
+            invalidate_nonce(chain_config.chain_id, signer);
+            Err(err)
+        }
+    }
+}
+
+/// Signs a transaction with the signer's key, in the transaction type of the chain.
+async fn eth_sign_transaction(
+    request: SignRequest,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> String {
+    match chain_config.transaction_type() {
+        TransactionType::Eip1559 => sign_eip1559_transaction(request, signer).await,
+        TransactionType::Legacy => sign_legacy_transaction(request, signer).await,
+        TransactionType::Eip2930 => {
+            // A transaction without access list is still valid, only more expensive
+            let access_list = eth_create_access_list(&request, chain_config, signer)
+                .await
+                .unwrap_or_default();
+            sign_eip2930_transaction(request, access_list, signer).await
+        }
+    }
+}
+
+/// Signs a transaction with the signer's key and sends it, returns the transaction hash.
+pub async fn eth_send_transaction(
+    request: SignRequest,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> Result<String, EthTransactionError> {
+    let signed_data = eth_sign_transaction(request, chain_config, signer).await;
+    send_raw_transaction(evm_client(chain_config).as_ref(), signed_data).await
+}
+
+/// Sends the signed transaction, rejected transactions are mapped to their errors.
+async fn send_raw_transaction(
+    client: &dyn EvmClient,
+    signed_data: String,
+) -> Result<String, EthTransactionError> {
+    let status = client
+        .send_raw_transaction(signed_data)
+        .await
+        .map_err(EthTransactionError::Client)?;
+
+    match status {
+        SendRawTransactionStatus::Ok(Some(txid)) => Ok(txid),
+        SendRawTransactionStatus::Ok(None) => Err(EthTransactionError::NoTransactionId),
+        SendRawTransactionStatus::NonceTooLow => Err(EthTransactionError::NonceTooLow),
+        SendRawTransactionStatus::NonceTooHigh => Err(EthTransactionError::NonceTooHigh),
+        SendRawTransactionStatus::InsufficientFunds => Err(EthTransactionError::InsufficientFunds),
     }
 }
 
@@ -160,90 +247,173 @@ pub async fn eth_estimate_gas(
     })
     .to_string();
 
-    let (result,): (RequestResult,) = call_with_payment128(
-        crate::declarations::evm_rpc::evm_rpc.0,
-        "request",
-        (
-            chain_config.default_rpc_service.clone(),
-            json_rpc_payload,
-            2048_u64,
-        ),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await
-    .map_err(EthTransactionError::CallError)?;
-
-    match result {
-        RequestResult::Ok(s) => serde_json::from_str::<JsonRpcResponse>(&s)
-            .map_err(EthTransactionError::JsonError)
-            .and_then(|r| match r {
-                JsonRpcResponse::Success(s) => Ok(s.result),
-                JsonRpcResponse::Error(e) => Err(EthTransactionError::JsonRpcError(e)),
+    json_rpc_request(json_rpc_payload, chain_config).await
+}
+
+/// Calls a read only contract function and returns the decoded output.
+pub async fn eth_call(
+    contract_address: &str,
+    abi_contract: &Contract,
+    function_name: &str,
+    args: &[Token],
+    chain_config: &ChainConfig,
+) -> Result<Vec<Token>, EthTransactionError> {
+    let abi_function = get_abi_function_by_name(abi_contract, function_name);
+    let data = abi_function
+        .encode_input(args)
+        .map_err(|_| EthTransactionError::ArgsEncoding)?;
+
+    let json_rpc_payload = json!({
+        "id": 1,
+        "jsonrpc": "2.0",
+        "method": "eth_call",
+        "params": [{
+            "to": contract_address,
+            "data": format!("0x{}", hex::encode(data)),
+        }, "latest"],
+    })
+    .to_string();
+
+    let result = json_rpc_request(json_rpc_payload, chain_config).await?;
+    let output = hex::decode(result).map_err(|_| EthTransactionError::OutputDecoding)?;
+
+    abi_function
+        .decode_output(&output)
+        .map_err(|_| EthTransactionError::OutputDecoding)
+}
+
+/// Returns the storage slots the transaction accesses, used for EIP-2930 transactions.
+async fn eth_create_access_list(
+    request: &SignRequest,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> Result<AccessList, EthTransactionError> {
+    let from = get_eth_address(signer)
+        .await
+        .map_err(EthTransactionError::CallError)?;
+
+    let json_rpc_payload = json!({
+        "id": 1,
+        "jsonrpc": "2.0",
+        "method": "eth_createAccessList",
+        "params": [{
+            "from": from,
+            "to": request.to,
+            "gas": format!("0x{:x}", nat_to_u256(&request.gas)),
+            "data": request.data.clone().unwrap_or_default(),
+        }, "latest"],
+    })
+    .to_string();
+
+    let result = json_rpc_request(json_rpc_payload, chain_config).await?;
+    let result: AccessListWithGasUsed = serde_json::from_str(&result)?;
+
+    Ok(result.access_list)
+}
+
+/// Returns the gas price, used to price transactions on chains without EIP-1559.
+pub async fn eth_gas_price(chain_config: &ChainConfig) -> Result<Nat, EthTransactionError> {
+    let json_rpc_payload = json!({
+        "id": 1,
+        "jsonrpc": "2.0",
+        "method": "eth_gasPrice",
+        "params": [],
+    })
+    .to_string();
+
+    let result = json_rpc_request(json_rpc_payload, chain_config).await?;
+    let gas_price = result
+        .strip_prefix("0x")
+        .and_then(|gas_price| u128::from_str_radix(gas_price, 16).ok())
+        .ok_or(EthTransactionError::OutputDecoding)?;
+
+    Ok(Nat::from(gas_price))
+}
+
+async fn json_rpc_request(
+    json_rpc_payload: String,
+    chain_config: &ChainConfig,
+) -> Result<String, EthTransactionError> {
+    let response = evm_client(chain_config)
+        .request(json_rpc_payload)
+        .await
+        .map_err(EthTransactionError::Client)?;
+
+    serde_json::from_str::<JsonRpcResponse>(&response)
+        .map_err(EthTransactionError::JsonError)
+        .and_then(|r| match r {
+            JsonRpcResponse::Success(s) => Ok(match s.result {
+                serde_json::Value::String(result) => result,
+                result => result.to_string(),
             }),
-        RequestResult::Err(e) => Err(EthTransactionError::RpcError(e)),
-    }
+            JsonRpcResponse::Error(e) => Err(EthTransactionError::JsonRpcError(e)),
+        })
 }
 
 pub async fn eth_get_transaction_receipt(
     hash: &str,
     chain_config: &ChainConfig,
 ) -> Result<TransactionReceipt, String> {
-    let (res,): (MultiGetTransactionReceiptResult,) = call_with_payment128(
-        crate::declarations::evm_rpc::evm_rpc.0,
-        "eth_getTransactionReceipt",
-        (chain_config.rpc_services.clone(), None::<RpcConfig>, hash),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await
-    .unwrap();
-
-    match res {
-        MultiGetTransactionReceiptResult::Consistent(GetTransactionReceiptResult::Ok(receipt)) => {
-            match receipt {
-                Some(receipt) => Ok(receipt),
-                None => Err("Receipt not found".to_string()),
-            }
-        }
-        other => Err(format!("{:?}", other)),
-    }
+    transaction_receipt(evm_client(chain_config).as_ref(), hash).await
+}
+
+/// Returns `None` while the transaction has not been included in a block. Unlike
+/// `eth_get_transaction_receipt`, RPC errors are kept apart from missing receipts.
+pub async fn eth_find_transaction_receipt(
+    hash: &str,
+    chain_config: &ChainConfig,
+) -> Result<Option<TransactionReceipt>, EvmRpcError> {
+    evm_client(chain_config)
+        .get_transaction_receipt(hash.to_string())
+        .await
+}
+
+async fn transaction_receipt(
+    client: &dyn EvmClient,
+    hash: &str,
+) -> Result<TransactionReceipt, String> {
+    let receipt = client
+        .get_transaction_receipt(hash.to_string())
+        .await
+        .map_err(|err| err.to_string())?;
+
+    receipt.ok_or_else(|| "Receipt not found".to_string())
 }
 
 pub async fn get_run_payment_logs(
     block_number: u128,
     chain_config: &ChainConfig,
 ) -> Result<Vec<LogEntry>, EthTransactionError> {
-    let (res,): (MultiGetLogsResult,) = call_with_payment128(
-        evm_rpc.0,
-        "eth_getLogs",
-        (
-            chain_config.rpc_services.clone(),
-            1,
-            GetLogsArgs {
-                addresses: vec![chain_config.payment_contract.clone()],
-                fromBlock: Some(BlockTag::Number(block_number.into())),
-                toBlock: Some(BlockTag::Number(block_number.into())),
-                topics: None,
-            },
-        ),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await
-    .map_err(EthTransactionError::CallError)?;
+    evm_client(chain_config)
+        .get_logs(GetLogsArgs {
+            addresses: vec![chain_config.payment_contract.clone()],
+            fromBlock: Some(BlockTag::Number(block_number.into())),
+            toBlock: Some(BlockTag::Number(block_number.into())),
+            topics: None,
+        })
+        .await
+        .map_err(EthTransactionError::Client)
+}
 
-    match res {
-        MultiGetLogsResult::Consistent(log_result) => match log_result {
-            GetLogsResult::Ok(entries) => Ok(entries),
-            GetLogsResult::Err(err) => Err(EthTransactionError::RpcError(err)),
-        },
-        MultiGetLogsResult::Inconsistent(_) => Err(EthTransactionError::InconsistentResponse),
+fn transaction_request(req: &SignRequest) -> TransactionRequest {
+    TransactionRequest {
+        from: None,
+        to: Some(
+            Address::from_str(&req.to)
+                .expect("failed to parse the destination address")
+                .into(),
+        ),
+        gas: Some(nat_to_u256(&req.gas)),
+        gas_price: Some(nat_to_u256(&req.max_fee_per_gas)),
+        value: Some(nat_to_u256(&req.value)),
+        data: req.data.clone(),
+        nonce: Some(nat_to_u256(&req.nonce)),
+        chain_id: Some(nat_to_u64(&req.chain_id)),
     }
 }
 
 /// Computes a signature for an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction.
-async fn sign_transaction(req: SignRequest) -> String {
-    use ethers_core::types::transaction::eip1559::Eip1559TransactionRequest;
-    use ethers_core::types::Signature;
-
+async fn sign_eip1559_transaction(req: SignRequest, signer: &Signer) -> String {
     const EIP1559_TX_ID: u8 = 2;
 
     let tx = Eip1559TransactionRequest {
@@ -268,7 +438,7 @@ async fn sign_transaction(req: SignRequest) -> String {
 
     let txhash = keccak256(&unsigned_tx_bytes);
 
-    let (pubkey, signature) = pubkey_and_signature(txhash.to_vec()).await;
+    let (pubkey, signature) = pubkey_and_signature(txhash.to_vec(), signer).await;
 
     let signature = Signature {
         v: y_parity(&txhash, &signature, &pubkey),
@@ -282,6 +452,70 @@ async fn sign_transaction(req: SignRequest) -> String {
     format!("0x{}", hex::encode(&signed_tx_bytes))
 }
 
+/// Computes a signature for a legacy transaction, with [EIP-155](https://eips.ethereum.org/EIPS/eip-155)
+/// replay protection. `max_fee_per_gas` is used as the gas price.
+async fn sign_legacy_transaction(req: SignRequest, signer: &Signer) -> String {
+    let tx = transaction_request(&req);
+
+    // Includes the chain id, as required by EIP-155
+    let txhash = keccak256(tx.rlp());
+
+    let (pubkey, signature) = pubkey_and_signature(txhash.to_vec(), signer).await;
+
+    let signature = Signature {
+        v: y_parity(&txhash, &signature, &pubkey) + 35 + 2 * nat_to_u64(&req.chain_id).as_u64(),
+        r: U256::from_big_endian(&signature[0..32]),
+        s: U256::from_big_endian(&signature[32..64]),
+    };
+
+    format!("0x{}", hex::encode(tx.rlp_signed(&signature)))
+}
+
+/// Computes a signature for an [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930) transaction.
+/// `max_fee_per_gas` is used as the gas price.
+async fn sign_eip2930_transaction(
+    req: SignRequest,
+    access_list: AccessList,
+    signer: &Signer,
+) -> String {
+    const EIP2930_TX_ID: u8 = 1;
+
+    let tx = Eip2930TransactionRequest::new(transaction_request(&req), access_list);
+
+    let mut unsigned_tx_bytes = tx.rlp().to_vec();
+    unsigned_tx_bytes.insert(0, EIP2930_TX_ID);
+
+    let txhash = keccak256(&unsigned_tx_bytes);
+
+    let (pubkey, signature) = pubkey_and_signature(txhash.to_vec(), signer).await;
+
+    let signature = Signature {
+        v: y_parity(&txhash, &signature, &pubkey),
+        r: U256::from_big_endian(&signature[0..32]),
+        s: U256::from_big_endian(&signature[32..64]),
+    };
+
+    let mut signed_tx_bytes = tx.rlp_signed(&signature).to_vec();
+    signed_tx_bytes.insert(0, EIP2930_TX_ID);
+
+    format!("0x{}", hex::encode(&signed_tx_bytes))
+}
+
+/// Signs a message hash, for instance an EIP-712 digest, with the signer's key. `v` is
+/// 27 or 28, as expected by Ethereum signature verifiers.
+pub async fn sign_message_hash(
+    message_hash: [u8; 32],
+    signer: &Signer,
+) -> ethers_core::types::Signature {
+    let (pubkey, signature) = pubkey_and_signature(message_hash.to_vec(), signer).await;
+
+    ethers_core::types::Signature {
+        v: 27 + y_parity(&message_hash, &signature, &pubkey),
+        r: U256::from_big_endian(&signature[0..32]),
+        s: U256::from_big_endian(&signature[32..64]),
+    }
+}
+
 /// Computes the parity bit allowing to recover the public key from the signature.
 fn y_parity(prehash: &[u8], sig: &[u8], pubkey: &[u8]) -> u64 {
     use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
@@ -304,19 +538,19 @@ fn y_parity(prehash: &[u8], sig: &[u8], pubkey: &[u8]) -> u64 {
     )
 }
 
-/// Returns the public key and a message signature for the specified principal.
-async fn pubkey_and_signature(message_hash: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+/// Returns the public key and a message signature for the specified signer.
+async fn pubkey_and_signature(message_hash: Vec<u8>, signer: &Signer) -> (Vec<u8>, Vec<u8>) {
     // Fetch the pubkey and the signature concurrently to reduce latency.
     let (pubkey, response) = futures::join!(
         ecdsa_public_key(EcdsaPublicKeyArgument {
             canister_id: None,
-            derivation_path: vec![],
-            key_id: ecdsa_key_id()
+            derivation_path: signer.attester.derivation_path(),
+            key_id: ecdsa_key_id(&signer.key_id)
         }),
         sign_with_ecdsa(SignWithEcdsaArgument {
             message_hash,
-            derivation_path: vec![],
-            key_id: ecdsa_key_id(),
+            derivation_path: signer.attester.derivation_path(),
+            key_id: ecdsa_key_id(&signer.key_id),
         })
     );
     (
@@ -326,35 +560,80 @@ async fn pubkey_and_signature(message_hash: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
 }
 
 thread_local! {
-    static SELF_ETH_ADDRESS: RefCell<Option<String>> =
-        const { RefCell::new(None) };
+    static ETH_ADDRESSES: RefCell<HashMap<Signer, String>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the Ethereum address of the signer. Addresses are derived once and then
+/// cached, they never change for a given key.
+pub async fn get_eth_address(signer: &Signer) -> Result<String, (RejectionCode, String)> {
+    if let Some(address) = ETH_ADDRESSES.with_borrow(|addresses| addresses.get(signer).cloned()) {
+        return Ok(address);
+    }
+
+    let (pubkey,) = ecdsa_public_key(EcdsaPublicKeyArgument {
+        canister_id: None,
+        derivation_path: signer.attester.derivation_path(),
+        key_id: ecdsa_key_id(&signer.key_id),
+    })
+    .await?;
+
+    let key = PublicKey::from_sec1_bytes(&pubkey.public_key)
+        .expect("failed to parse the public key as SEC1");
+    let point = key.to_encoded_point(false);
+    // we re-encode the key to the decompressed representation.
+    let point_bytes = point.as_bytes();
+    assert_eq!(point_bytes[0], 0x04);
+
+    let hash = keccak256(&point_bytes[1..]);
+
+    let address = ethers_core::utils::to_checksum(&Address::from_slice(&hash[12..32]), None);
+    ETH_ADDRESSES.with_borrow_mut(|addresses| addresses.insert(signer.clone(), address.clone()));
+
+    Ok(address)
 }
 
 pub async fn get_self_eth_address() -> String {
-    if SELF_ETH_ADDRESS.with(|maybe_address| maybe_address.borrow().is_none()) {
-        let (pubkey,) = ecdsa_public_key(EcdsaPublicKeyArgument {
-            canister_id: None,
-            derivation_path: vec![],
-            key_id: ecdsa_key_id(),
-        })
+    get_eth_address(&Signer::active(Attester::Canister))
         .await
-        .unwrap();
+        .unwrap()
+}
 
-        let key = PublicKey::from_sec1_bytes(&pubkey.public_key)
-            .expect("failed to parse the public key as SEC1");
-        let point = key.to_encoded_point(false);
-        // we re-encode the key to the decompressed representation.
-        let point_bytes = point.as_bytes();
-        assert_eq!(point_bytes[0], 0x04);
+/// Returns the balance of an address in wei, used to check that attesters are funded.
+pub async fn eth_get_balance(
+    address: &str,
+    chain_config: &ChainConfig,
+) -> Result<Nat, EthTransactionError> {
+    let json_rpc_payload = json!({
+        "id": 1,
+        "jsonrpc": "2.0",
+        "method": "eth_getBalance",
+        "params": [address, "latest"],
+    })
+    .to_string();
 
-        let hash = keccak256(&point_bytes[1..]);
+    let result = json_rpc_request(json_rpc_payload, chain_config).await?;
+    let balance = result
+        .strip_prefix("0x")
+        .and_then(|balance| u128::from_str_radix(balance, 16).ok())
+        .ok_or(EthTransactionError::OutputDecoding)?;
 
-        let self_address =
-            ethers_core::utils::to_checksum(&Address::from_slice(&hash[12..32]), None);
-        SELF_ETH_ADDRESS.with(|maybe_address| *maybe_address.borrow_mut() = Some(self_address));
-    }
+    Ok(Nat::from(balance))
+}
 
-    SELF_ETH_ADDRESS.with(|maybe_address| maybe_address.borrow().clone().unwrap())
+/// Returns the code deployed at an address, `0x` for externally owned accounts.
+pub async fn eth_get_code(
+    address: &str,
+    chain_config: &ChainConfig,
+) -> Result<String, EthTransactionError> {
+    let json_rpc_payload = json!({
+        "id": 1,
+        "jsonrpc": "2.0",
+        "method": "eth_getCode",
+        "params": [address, "latest"],
+    })
+    .to_string();
+
+    json_rpc_request(json_rpc_payload, chain_config).await
 }
 
 #[derive(Error, Debug)]
@@ -369,62 +648,15 @@ pub enum EvmRpcError {
     Unexpected(String),
 }
 
-/// Returns the latest block with one caveat: if multiple Rpc services are specified and
-/// they return inconsistent responses, the function will return the earliest block in the
-/// - the "smallest common" latest block.
+/// Returns the block, see the client implementations for how responses from multiple RPC
+/// services are reconciled.
 pub async fn eth_get_block_by_number(
     block_tag: BlockTag,
     chain_config: &ChainConfig,
 ) -> Result<Block, EvmRpcError> {
-    let call_result: CallResult<(MultiGetBlockByNumberResult,)> = call_with_payment128(
-        evm_rpc.0,
-        "eth_getBlockByNumber",
-        (
-            chain_config.rpc_services.clone(),
-            None::<RpcConfig>,
-            block_tag,
-            false,
-        ),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await;
-
-    let block: Block = match call_result {
-        Ok((MultiGetBlockByNumberResult::Consistent(block),)) => match block {
-            GetBlockByNumberResult::Ok(block) => block,
-            GetBlockByNumberResult::Err(e) => {
-                return Err(EvmRpcError::Rpc(format!("{:?}", e)));
-            }
-        },
-        Ok((MultiGetBlockByNumberResult::Inconsistent(res),)) => {
-            let mut maybe_earliest_block: Option<Block> = None;
-            for r in res {
-                if let (_, GetBlockByNumberResult::Ok(b)) = r {
-                    match maybe_earliest_block {
-                        Some(ref earliest_block) => {
-                            if b.number < earliest_block.number {
-                                maybe_earliest_block = Some(b);
-                            }
-                        }
-                        None => {
-                            maybe_earliest_block = Some(b);
-                        }
-                    }
-                }
-            }
-            match maybe_earliest_block {
-                Some(earliest_block) => earliest_block,
-                None => {
-                    return Err(EvmRpcError::Unexpected("No block found".to_string()));
-                }
-            }
-        }
-        Err(e) => {
-            return Err(EvmRpcError::Ic(e.1));
-        }
-    };
-
-    Ok(block)
+    evm_client(chain_config)
+        .get_block_by_number(block_tag)
+        .await
 }
 
 pub async fn eth_fee_history(
@@ -439,39 +671,200 @@ pub async fn eth_fee_history(
         rewardPercentiles: reward_percentiles.map(ByteBuf::from),
     };
 
-    let call_result: CallResult<(MultiFeeHistoryResult,)> = call_with_payment128(
-        evm_rpc.0,
-        "eth_feeHistory",
-        (
-            chain_config.rpc_services.clone(),
-            None::<RpcConfig>,
-            fee_history_args,
-        ),
-        ETH_DEFAULT_CALL_CYCLES,
-    )
-    .await;
-
-    let fee_history: FeeHistory = match call_result {
-        Ok((res,)) => match res {
-            MultiFeeHistoryResult::Consistent(fee_history) => match fee_history {
-                FeeHistoryResult::Ok(maybe_fee_history) => match maybe_fee_history {
-                    Some(fee_history) => fee_history,
-                    None => {
-                        return Err(EvmRpcError::Unexpected("No fee history found".to_string()));
-                    }
-                },
-                FeeHistoryResult::Err(e) => {
-                    return Err(EvmRpcError::Rpc(format!("{:?}", e)));
-                }
-            },
-            MultiFeeHistoryResult::Inconsistent(_) => {
-                return Err(EvmRpcError::Inconsistent);
+    evm_client(chain_config).fee_history(fee_history_args).await
+}
+
+/// Returns the number of transactions sent from the signer address. Use `BlockTag::Latest`
+/// for confirmed transactions only and `BlockTag::Pending` to include the mempool.
+pub async fn eth_get_transaction_count(
+    block_tag: BlockTag,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> Result<Nat, EvmRpcError> {
+    let address = get_eth_address(signer)
+        .await
+        .map_err(|err| EvmRpcError::Ic(err.1))?;
+
+    eth_get_address_transaction_count(address, block_tag, chain_config).await
+}
+
+/// Returns the number of transactions sent from an address, the next nonce of the address.
+pub async fn eth_get_address_transaction_count(
+    address: String,
+    block_tag: BlockTag,
+    chain_config: &ChainConfig,
+) -> Result<Nat, EvmRpcError> {
+    evm_client(chain_config)
+        .get_transaction_count(GetTransactionCountArgs {
+            address,
+            block: block_tag,
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm::client::EvmClientResult;
+    use futures::executor::block_on;
+    use std::sync::Mutex;
+
+    const HASH: &str = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+
+    /// Answers sends and receipt lookups with scripted results and records what was sent.
+    struct MockClient {
+        status: fn() -> Result<SendRawTransactionStatus, EvmRpcError>,
+        receipt: fn() -> Result<Option<TransactionReceipt>, EvmRpcError>,
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl MockClient {
+        fn new(
+            status: fn() -> Result<SendRawTransactionStatus, EvmRpcError>,
+            receipt: fn() -> Result<Option<TransactionReceipt>, EvmRpcError>,
+        ) -> Self {
+            Self {
+                status,
+                receipt,
+                sent: Mutex::new(vec![]),
             }
-        },
-        Err(e) => {
-            return Err(EvmRpcError::Ic(e.1));
         }
-    };
+    }
+
+    /// Fails calls the tests don't script, so that an unexpected call shows up as an error.
+    fn not_expected<T: Send + 'static>(method: &str) -> EvmClientResult<'static, T> {
+        let message = format!("{} not expected", method);
+        Box::pin(async move { Err(EvmRpcError::Unexpected(message)) })
+    }
+
+    impl EvmClient for MockClient {
+        fn request(&self, _json_rpc_payload: String) -> EvmClientResult<'_, String> {
+            not_expected("request")
+        }
+
+        fn send_raw_transaction(
+            &self,
+            signed_transaction: String,
+        ) -> EvmClientResult<'_, SendRawTransactionStatus> {
+            self.sent.lock().unwrap().push(signed_transaction);
+            Box::pin(async move { (self.status)() })
+        }
+
+        fn get_transaction_receipt(
+            &self,
+            _hash: String,
+        ) -> EvmClientResult<'_, Option<TransactionReceipt>> {
+            Box::pin(async move { (self.receipt)() })
+        }
+
+        fn get_logs(&self, _args: GetLogsArgs) -> EvmClientResult<'_, Vec<LogEntry>> {
+            not_expected("get_logs")
+        }
+
+        fn get_block_by_number(&self, _block_tag: BlockTag) -> EvmClientResult<'_, Block> {
+            not_expected("get_block_by_number")
+        }
+
+        fn fee_history(&self, _args: FeeHistoryArgs) -> EvmClientResult<'_, FeeHistory> {
+            not_expected("fee_history")
+        }
+
+        fn get_transaction_count(
+            &self,
+            _args: GetTransactionCountArgs,
+        ) -> EvmClientResult<'_, Nat> {
+            not_expected("get_transaction_count")
+        }
+    }
+
+    fn receipt() -> TransactionReceipt {
+        TransactionReceipt {
+            to: String::new(),
+            status: Nat::from(1_u8),
+            transactionHash: HASH.to_string(),
+            blockNumber: Nat::from(16_u8),
+            from: String::new(),
+            logs: vec![],
+            blockHash: String::new(),
+            r#type: "0x2".to_string(),
+            transactionIndex: Nat::from(0_u8),
+            effectiveGasPrice: Nat::from(0_u8),
+            logsBloom: String::new(),
+            contractAddress: None,
+            gasUsed: Nat::from(21_000_u32),
+        }
+    }
+
+    fn send(client: &MockClient) -> Result<String, EthTransactionError> {
+        block_on(send_raw_transaction(client, "0x02f8".to_string()))
+    }
+
+    #[test]
+    fn send_returns_transaction_hash() {
+        let client = MockClient::new(
+            || Ok(SendRawTransactionStatus::Ok(Some(HASH.to_string()))),
+            || Ok(None),
+        );
+        assert_eq!(send(&client).unwrap(), HASH);
+        assert_eq!(*client.sent.lock().unwrap(), vec!["0x02f8".to_string()]);
+
+        let client = MockClient::new(|| Ok(SendRawTransactionStatus::Ok(None)), || Ok(None));
+        assert!(matches!(
+            send(&client),
+            Err(EthTransactionError::NoTransactionId)
+        ));
+    }
+
+    #[test]
+    fn send_maps_rejections() {
+        let client = MockClient::new(|| Ok(SendRawTransactionStatus::NonceTooLow), || Ok(None));
+        let err = send(&client).unwrap_err();
+        assert!(matches!(err, EthTransactionError::NonceTooLow));
+        assert!(err.is_retryable());
+
+        let client = MockClient::new(|| Ok(SendRawTransactionStatus::NonceTooHigh), || Ok(None));
+        assert!(send(&client).unwrap_err().is_retryable());
+
+        let client = MockClient::new(
+            || Ok(SendRawTransactionStatus::InsufficientFunds),
+            || Ok(None),
+        );
+        let err = send(&client).unwrap_err();
+        assert!(matches!(err, EthTransactionError::InsufficientFunds));
+        assert!(!err.is_retryable());
+
+        let client = MockClient::new(|| Err(EvmRpcError::Inconsistent), || Ok(None));
+        let err = send(&client).unwrap_err();
+        assert!(matches!(
+            err,
+            EthTransactionError::Client(EvmRpcError::Inconsistent)
+        ));
+        assert!(!err.is_retryable());
+    }
 
-    Ok(fee_history)
+    #[test]
+    fn receipt_lookup() {
+        let client = MockClient::new(
+            || Ok(SendRawTransactionStatus::NonceTooLow),
+            || Ok(Some(receipt())),
+        );
+        let found = block_on(transaction_receipt(&client, HASH)).unwrap();
+        assert_eq!(found.transactionHash, HASH);
+
+        // Pending transactions have no receipt yet
+        let client = MockClient::new(|| Ok(SendRawTransactionStatus::NonceTooLow), || Ok(None));
+        assert_eq!(
+            block_on(transaction_receipt(&client, HASH)).unwrap_err(),
+            "Receipt not found"
+        );
+
+        let client = MockClient::new(
+            || Ok(SendRawTransactionStatus::NonceTooLow),
+            || Err(EvmRpcError::Rpc("header not found".to_string())),
+        );
+        assert_eq!(
+            block_on(transaction_receipt(&client, HASH)).unwrap_err(),
+            "Rpc error: header not found"
+        );
+    }
 }
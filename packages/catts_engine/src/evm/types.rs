@@ -1,6 +1,12 @@
-use candid::Nat;
+use crate::{
+    recipe::{Recipe, RecipeId},
+    signing_key,
+};
+use candid::{CandidType, Decode, Encode, Nat};
 use ethers_core::types::Bytes;
+use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct EthCallParams {
@@ -13,7 +19,7 @@ struct EthCallParams {
 pub struct JsonRpcSuccessResponse {
     pub jsonrpc: String,
     pub id: u64,
-    pub result: String,
+    pub result: serde_json::Value,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,7 +44,7 @@ pub enum JsonRpcResponse {
     Error(JsonRpcErrorResponse),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SignRequest {
     pub chain_id: Nat,
     pub to: String,
@@ -49,3 +55,75 @@ pub struct SignRequest {
     pub nonce: Nat,
     pub data: Option<Bytes>,
 }
+
+/// The key transactions and signatures are made with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Attester {
+    /// The canister's own key, derived with an empty derivation path
+    Canister,
+    /// A key derived from the recipe id, for recipes with a dedicated attester
+    Recipe(RecipeId),
+}
+
+impl Attester {
+    pub fn for_recipe(recipe: &Recipe) -> Self {
+        if recipe.has_dedicated_attester() {
+            Attester::Recipe(recipe.id)
+        } else {
+            Attester::Canister
+        }
+    }
+
+    pub fn derivation_path(&self) -> Vec<Vec<u8>> {
+        match self {
+            Attester::Canister => vec![],
+            Attester::Recipe(recipe_id) => vec![b"recipe".to_vec(), recipe_id.to_vec()],
+        }
+    }
+}
+
+/// An attester under a specific threshold ECDSA key. Rotating the key changes the address
+/// of every attester, transactions sent before the rotation keep being tracked and resent
+/// with the key they were signed with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Signer {
+    pub key_id: String,
+    pub attester: Attester,
+}
+
+impl Signer {
+    /// The attester under the active key, used for all new transactions and signatures.
+    pub fn active(attester: Attester) -> Self {
+        Self {
+            key_id: signing_key::active_key_id(),
+            attester,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.key_id == signing_key::active_key_id()
+    }
+}
+
+/// Nonce bookkeeping for one attester address on one chain.
+#[derive(CandidType, Clone, Debug, Default, Deserialize)]
+pub struct NonceState {
+    /// The next nonce to hand out
+    pub next_nonce: u64,
+    /// Nonces handed out to transactions that are not yet confirmed
+    pub pending: Vec<u64>,
+    /// Set when the local state can't be trusted, the next nonce is then read from the chain
+    pub needs_sync: bool,
+}
+
+impl Storable for NonceState {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -5,8 +5,6 @@ use ethers_core::{
 };
 use ic_cdk::api::management_canister::ecdsa::EcdsaKeyId;
 
-use crate::CANISTER_SETTINGS;
-
 pub fn nat_to_u256(n: &Nat) -> U256 {
     let be_bytes = n.0.to_bytes_be();
     U256::from_big_endian(&be_bytes)
@@ -17,6 +15,10 @@ pub fn nat_to_u64(n: &Nat) -> U64 {
     U64::from_big_endian(&be_bytes)
 }
 
+pub fn nat_to_u128(n: &Nat) -> Option<u128> {
+    u128::try_from(&n.0).ok()
+}
+
 pub fn get_abi_function_by_name(abi: &Contract, function_name: &str) -> Function {
     match abi.functions_by_name(function_name).map(|v| &v[..]) {
         Ok([f]) => f.clone(),
@@ -36,9 +38,9 @@ pub fn get_abi_function_by_name(abi: &Contract, function_name: &str) -> Function
     }
 }
 
-pub fn ecdsa_key_id() -> EcdsaKeyId {
+pub fn ecdsa_key_id(name: &str) -> EcdsaKeyId {
     EcdsaKeyId {
         curve: ic_cdk::api::management_canister::ecdsa::EcdsaCurve::Secp256k1,
-        name: CANISTER_SETTINGS.with(|settings| settings.borrow().ecdsa_key_id.clone()),
+        name: name.to_string(),
     }
 }
@@ -105,7 +105,7 @@ impl HttpError {
         )
     }
 
-    pub fn _service_unavailable<M: Display>(message: M) -> Self {
+    pub fn service_unavailable<M: Display>(message: M) -> Self {
         Self::new(
             HttpStatusCode::ServiceUnavailable as u16,
             "Service unavailable".to_string(),
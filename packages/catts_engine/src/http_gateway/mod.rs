@@ -0,0 +1,6 @@
+pub mod rpc;
+pub mod types;
+pub mod util;
+
+pub use types::*;
+pub use util::*;
@@ -0,0 +1,116 @@
+use crate::{
+    certification,
+    eth_address::EthAddress,
+    http_gateway::{
+        list_to_json_body, recipe_badge, run_badge, to_json_body, HttpGatewayRequest,
+        HttpGatewayResponse,
+    },
+    recipe::{self, RecipeError},
+    run::{self, vec_to_run_id},
+};
+use ethers_core::utils::hex;
+use ic_cdk::query;
+use serde_bytes::ByteBuf;
+use serde_json::json;
+
+const CONTENT_TYPE_JSON: &str = "application/json";
+const CONTENT_TYPE_MARKDOWN: &str = "text/markdown; charset=utf-8";
+const CONTENT_TYPE_SVG: &str = "image/svg+xml";
+
+fn response(status_code: u16, content_type: &str, body: Vec<u8>) -> HttpGatewayResponse {
+    HttpGatewayResponse {
+        status_code,
+        headers: vec![
+            ("Content-Type".to_string(), content_type.to_string()),
+            ("Access-Control-Allow-Origin".to_string(), "*".to_string()),
+        ],
+        body: ByteBuf::from(body),
+    }
+}
+
+fn ok(path: &str, content_type: &str, body: Vec<u8>) -> HttpGatewayResponse {
+    let mut response = response(200, content_type, body);
+    if let Some(header) = certification::http_certificate_header(path, &response.body) {
+        response.headers.push(header);
+    }
+    response
+}
+
+fn error(status_code: u16, message: &str) -> HttpGatewayResponse {
+    let body = json!({ "code": status_code, "message": message });
+    response(
+        status_code,
+        CONTENT_TYPE_JSON,
+        body.to_string().into_bytes(),
+    )
+}
+
+fn recipe_error(err: RecipeError) -> HttpGatewayResponse {
+    match err {
+        RecipeError::NotFound => error(404, "Recipe not found"),
+        _ => error(500, &err.to_string()),
+    }
+}
+
+fn route(path: &str) -> HttpGatewayResponse {
+    let segments: Vec<&str> = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    match segments.as_slice() {
+        ["recipes"] => ok(path, CONTENT_TYPE_JSON, list_to_json_body(&recipe::list())),
+        ["recipes", name] => match recipe::get_by_name(&name.to_string()) {
+            Ok(recipe) => ok(path, CONTENT_TYPE_JSON, to_json_body(&recipe)),
+            Err(err) => recipe_error(err),
+        },
+        ["recipes", name, "readme"] => match recipe::read_readme(name) {
+            Ok(readme) => ok(path, CONTENT_TYPE_MARKDOWN, readme.into_bytes()),
+            Err(err) => recipe_error(err),
+        },
+        ["recipes", name, "badge.svg"] => match recipe::get_by_name(&name.to_string()) {
+            Ok(recipe) => ok(path, CONTENT_TYPE_SVG, recipe_badge(&recipe)),
+            Err(err) => recipe_error(err),
+        },
+        ["runs", id] | ["runs", id, "badge.svg"] => {
+            let run_id = match hex::decode(id).map_err(|err| err.to_string()) {
+                Ok(bytes) => match vec_to_run_id(bytes) {
+                    Ok(run_id) => run_id,
+                    Err(err) => return error(400, &err),
+                },
+                Err(err) => return error(400, &err),
+            };
+            let run = match run::get(&run_id) {
+                Ok(run) => run,
+                Err(_) => return error(404, "Run not found"),
+            };
+            if segments.len() == 3 {
+                ok(path, CONTENT_TYPE_SVG, run_badge(&run))
+            } else {
+                ok(path, CONTENT_TYPE_JSON, to_json_body(&run))
+            }
+        }
+        ["users", address, "runs"] => match EthAddress::new(address) {
+            Ok(address) => {
+                let runs = run::list_by_creator(&address);
+                ok(path, CONTENT_TYPE_JSON, list_to_json_body(&runs))
+            }
+            Err(err) => error(400, &err.to_string()),
+        },
+        _ => error(404, "Not found"),
+    }
+}
+
+/// Serves recipes, runs and status badges over HTTP. Recipe and run responses are
+/// certified, so that they can be served from the certified gateway domain. Lists and
+/// readmes are not certified and need to be requested from the raw domain.
+#[query]
+fn http_request(request: HttpGatewayRequest) -> HttpGatewayResponse {
+    if request.method != "GET" {
+        return error(405, "Method not allowed");
+    }
+
+    let path = request.url.split('?').next().unwrap_or_default();
+    route(path)
+}
@@ -0,0 +1,22 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+
+pub type HeaderField = (String, String);
+
+/// A request forwarded by the HTTP gateway, see the `http_request` method of the HTTP
+/// gateway protocol.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpGatewayRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<HeaderField>,
+    pub body: ByteBuf,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct HttpGatewayResponse {
+    pub status_code: u16,
+    pub headers: Vec<HeaderField>,
+    pub body: ByteBuf,
+}
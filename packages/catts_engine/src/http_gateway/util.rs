@@ -0,0 +1,93 @@
+use crate::{
+    json::{bytes_to_hex_string, ToJsonValue},
+    recipe::{Recipe, RecipePublishState},
+    run::{Run, RunStatus},
+};
+use serde_json::json;
+
+const BADGE_COLOR_SUCCESS: &str = "#4c1";
+const BADGE_COLOR_ERROR: &str = "#e05d44";
+const BADGE_COLOR_INACTIVE: &str = "#9f9f9f";
+const BADGE_COLOR_PENDING: &str = "#007ec6";
+
+pub fn recipe_path(recipe_name: &str) -> String {
+    format!("/recipes/{}", recipe_name)
+}
+
+pub fn recipe_badge_path(recipe_name: &str) -> String {
+    format!("/recipes/{}/badge.svg", recipe_name)
+}
+
+/// Run ids are hex encoded with a `0x` prefix, the same format used in JSON responses.
+pub fn run_path(run: &Run) -> String {
+    format!("/runs/{}", bytes_to_hex_string(&run.id))
+}
+
+pub fn run_badge_path(run: &Run) -> String {
+    format!("/runs/{}/badge.svg", bytes_to_hex_string(&run.id))
+}
+
+pub fn to_json_body<T: ToJsonValue>(value: T) -> Vec<u8> {
+    value.to_json_value().to_string().into_bytes()
+}
+
+pub fn list_to_json_body<T: ToJsonValue>(values: &[T]) -> Vec<u8> {
+    let values: Vec<_> = values.iter().map(|value| value.to_json_value()).collect();
+    json!(values).to_string().into_bytes()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Approximate width of a text in the 11px Verdana used by shields.io style badges.
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+/// Renders a flat, two part status badge.
+pub fn render_badge(label: &str, message: &str, color: &str) -> Vec<u8> {
+    let label_width = text_width(label);
+    let message_width = text_width(message);
+    let width = label_width + message_width;
+    let label = escape_xml(label);
+    let message = escape_xml(message);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="14">{message}</text></g></svg>"##,
+        label_x = label_width / 2,
+        message_x = label_width + message_width / 2,
+    )
+    .into_bytes()
+}
+
+pub fn recipe_badge(recipe: &Recipe) -> Vec<u8> {
+    let color = match recipe.publish_state {
+        RecipePublishState::Published => BADGE_COLOR_SUCCESS,
+        RecipePublishState::Draft => BADGE_COLOR_PENDING,
+        RecipePublishState::Unpublished => BADGE_COLOR_INACTIVE,
+    };
+    let message = recipe.publish_state.to_string().to_lowercase();
+    render_badge(&recipe.name, &message, color)
+}
+
+pub fn run_badge(run: &Run) -> Vec<u8> {
+    let (message, color) = if run.is_cancelled {
+        ("cancelled", BADGE_COLOR_INACTIVE)
+    } else if run.error.is_some() {
+        ("failed", BADGE_COLOR_ERROR)
+    } else {
+        match run.status() {
+            RunStatus::PaymentPending => ("payment pending", BADGE_COLOR_PENDING),
+            RunStatus::PaymentRegistered => ("payment registered", BADGE_COLOR_PENDING),
+            RunStatus::PaymentVerified => ("payment verified", BADGE_COLOR_PENDING),
+            RunStatus::AttestationCreated => ("attesting", BADGE_COLOR_PENDING),
+            RunStatus::AttestationUidConfirmed => ("attested", BADGE_COLOR_SUCCESS),
+        }
+    };
+    render_badge("catts run", message, color)
+}
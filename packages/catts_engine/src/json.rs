@@ -5,6 +5,12 @@ pub trait ToJsonValue {
     fn to_json_value(&self) -> Value;
 }
 
+impl ToJsonValue for Value {
+    fn to_json_value(&self) -> Value {
+        self.clone()
+    }
+}
+
 pub fn bytes_to_hex_string(bytes: &[u8]) -> String {
     let mut hex_string = String::from("0x");
     for byte in bytes {
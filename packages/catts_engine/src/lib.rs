@@ -1,3 +1,4 @@
+mod certification;
 mod chain_config;
 mod change_log;
 mod controllers;
@@ -12,21 +13,32 @@ mod eth_address;
 mod evm;
 mod graphql;
 mod http_error;
+mod http_gateway;
 mod json;
 mod logger;
+mod private_data;
 mod recipe;
+mod role;
 mod run;
+mod signing_key;
 mod siwe;
 mod tasks;
 mod time;
+mod url;
 mod user;
+mod webhook;
 
-use candid::CandidType;
+use candid::{CandidType, Nat, Principal};
+use certification::CertifiedResponse;
 use chain_config::{init_chain_configs, ChainConfig};
-use change_log::{ChangeLogItem, ChangeLogResponse};
+use change_log::{
+    ChangeLogFilter, ChangeLogItem, ChangeLogResponse, ChangeLogSnapshot,
+    ChangeLogSnapshotEntitiesResponse, ChangeLogTypeName, EntityVersion,
+};
 use eth_address::EthAddressBytes;
 use ethers_core::abi::Contract;
 use http_error::HttpError;
+use http_gateway::{HttpGatewayRequest, HttpGatewayResponse};
 use ic_cdk::{
     api::management_canister::http_request::{HttpResponse, TransformArgs},
     export_candid, init, post_upgrade, trap,
@@ -34,25 +46,26 @@ use ic_cdk::{
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
     storable::Blob,
-    DefaultMemoryImpl, Log, StableBTreeMap,
+    DefaultMemoryImpl, Log, StableBTreeMap, StableCell,
 };
 use lazy_static::lazy_static;
 use logger::LogItem;
+use private_data::PrivateData;
 use recipe::{Recipe, RecipeDetailsInput, RecipeId};
-use run::{Run, RunId};
+use role::{Role, RoleAssignment};
+use run::{Run, RunId, RunMode, RunRecipientInput};
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
+use signing_key::SigningKeys;
 use std::{cell::RefCell, sync::Arc, time::Duration};
-use tasks::{execute_tasks, Timestamp};
-use user::User;
+use tasks::{execute_tasks, ScheduledTask, Timestamp};
+use user::{User, UserProfileInput};
+use webhook::{Webhook, WebhookId, WebhookInput};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 
 const ETH_DEFAULT_CALL_CYCLES: u128 = 30_000_000_000;
 
-const ETH_PAYMENT_EVENT_SIGNATURE: &str =
-    "0x7c8809bb951e482559074456e6716ca166b1b6992b1205cfaae883fae81cf86a";
-
 const TIMER_INTERVAL_EXECUTE_TASKS: u64 = 15; // 15 seconds
 
 const THEGRAPH_QUERY_PROXY_URL: &str =
@@ -68,6 +81,18 @@ const TASKS_MEMORY_ID: MemoryId = MemoryId::new(6);
 const CHAIN_CONFIGS_MEMORY_ID: MemoryId = MemoryId::new(7);
 const CHANGE_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(8);
 const CHANGE_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(9);
+const ATTESTATION_QUEUE_MEMORY_ID: MemoryId = MemoryId::new(10);
+const NONCES_MEMORY_ID: MemoryId = MemoryId::new(11);
+const WEBHOOKS_MEMORY_ID: MemoryId = MemoryId::new(12);
+const PRIVATE_DATA_MEMORY_ID: MemoryId = MemoryId::new(13);
+const CHANGE_LOG_MEMORY_ID: MemoryId = MemoryId::new(14);
+const CHANGE_LOG_SNAPSHOTS_MEMORY_ID: MemoryId = MemoryId::new(15);
+const CHANGE_LOG_SNAPSHOT_ENTITIES_MEMORY_ID: MemoryId = MemoryId::new(16);
+const CHANGE_LOG_ENTITY_INDEX_MEMORY_ID: MemoryId = MemoryId::new(17);
+const ROLES_MEMORY_ID: MemoryId = MemoryId::new(18);
+const RECIPE_NONCES_MEMORY_ID: MemoryId = MemoryId::new(19);
+const SIGNING_KEYS_MEMORY_ID: MemoryId = MemoryId::new(20);
+const CHAIN_CONFIGS_INITIALISED_MEMORY_ID: MemoryId = MemoryId::new(21);
 
 #[derive(Serialize, Deserialize, CandidType)]
 struct CanisterSettingsInput {
@@ -100,8 +125,31 @@ thread_local! {
         )
     );
 
+    // Set once the default chain configs have been added
+    static CHAIN_CONFIGS_INITIALISED: RefCell<StableCell<bool, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHAIN_CONFIGS_INITIALISED_MEMORY_ID)),
+            false,
+        ).expect("Failed to init chain configs flag")
+    );
+
     static CANISTER_SETTINGS: RefCell<CanisterSettings> = RefCell::new(CanisterSettings::default());
 
+    // Threshold ECDSA keys, the key from the canister settings applies until the first rotation
+    static SIGNING_KEYS: RefCell<StableCell<SigningKeys, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(SIGNING_KEYS_MEMORY_ID)),
+            SigningKeys::default(),
+        ).expect("Failed to init signing keys")
+    );
+
+    // ROLES
+    static ROLES: RefCell<StableBTreeMap<Blob<29>, role::RoleAssignment, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ROLES_MEMORY_ID)),
+        )
+    );
+
     // USER
     static USERS: RefCell<StableBTreeMap<Blob<29>, User, Memory>> = RefCell::new(
         StableBTreeMap::init(
@@ -135,6 +183,41 @@ thread_local! {
         )
     );
 
+    // Runs with verified payments, waiting to be included in an attestation batch
+    static ATTESTATION_QUEUE: RefCell<StableBTreeMap<RunId, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ATTESTATION_QUEUE_MEMORY_ID)),
+        )
+    );
+
+    // WEBHOOKS
+    static WEBHOOKS: RefCell<StableBTreeMap<WebhookId, Webhook, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(WEBHOOKS_MEMORY_ID)),
+        )
+    );
+
+    // EVM
+    static NONCES: RefCell<StableBTreeMap<u32, evm::types::NonceState, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(NONCES_MEMORY_ID)),
+        )
+    );
+
+    // Nonces of the dedicated attesters of recipes, keyed by chain id and recipe id
+    static RECIPE_NONCES: RefCell<StableBTreeMap<(u32, RecipeId), evm::types::NonceState, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(RECIPE_NONCES_MEMORY_ID)),
+        )
+    );
+
+    // Merkle trees of private data runs, only accessible to the run creator
+    static PRIVATE_DATA: RefCell<StableBTreeMap<RunId, private_data::PrivateData, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PRIVATE_DATA_MEMORY_ID)),
+        )
+    );
+
     // TASKS
     static TASKS: RefCell<StableBTreeMap<Timestamp, tasks::Task, Memory>> = RefCell::new(
         StableBTreeMap::init(
@@ -143,13 +226,42 @@ thread_local! {
     );
 
     // CHANGE LOG
-    static CHANGE_LOG: RefCell<Log<ChangeLogItem, Memory, Memory>> = RefCell::new(
+    // Append only log used before compaction was introduced, migrated to CHANGE_LOG on upgrade
+    static CHANGE_LOG_LEGACY: RefCell<Log<ChangeLogItem, Memory, Memory>> = RefCell::new(
         Log::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_INDEX_MEMORY_ID)),
             MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_DATA_MEMORY_ID)),
         ).expect("Failed to initialize change log.")
     );
 
+    // Keyed by change log index, entries older than the retained snapshots are removed
+    static CHANGE_LOG: RefCell<StableBTreeMap<u64, ChangeLogItem, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_MEMORY_ID)),
+        )
+    );
+
+    // Change log indices per entity, used to rebuild entity history. Rebuilt from the change
+    // log when empty, see `change_log::init_entity_index`
+    static CHANGE_LOG_ENTITY_INDEX: RefCell<StableBTreeMap<(change_log::ChangeLogEntityKey, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_ENTITY_INDEX_MEMORY_ID)),
+        )
+    );
+
+    static CHANGE_LOG_SNAPSHOTS: RefCell<StableBTreeMap<u64, change_log::ChangeLogSnapshot, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_SNAPSHOTS_MEMORY_ID)),
+        )
+    );
+
+    // Keyed by snapshot index and entity sequence number
+    static CHANGE_LOG_SNAPSHOT_ENTITIES: RefCell<StableBTreeMap<(u64, u64), change_log::ChangeLogSnapshotEntity, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CHANGE_LOG_SNAPSHOT_ENTITIES_MEMORY_ID)),
+        )
+    );
+
 
 }
 
@@ -169,6 +281,8 @@ fn save_canister_settings(settings: CanisterSettingsInput) {
         trap("The field evm_rpc_canister is required");
     }
 
+    signing_key::check_canister_settings(&settings.ecdsa_key_id);
+
     CANISTER_SETTINGS.with_borrow_mut(|canister_settings| {
         *canister_settings = CanisterSettings {
             ecdsa_key_id: settings.ecdsa_key_id.clone(),
@@ -187,8 +301,15 @@ fn start_task_timer() {
 fn init_and_upgrade(settings: CanisterSettingsInput) {
     init_wasi();
     save_canister_settings(settings);
+    signing_key::schedule_init_active_key();
     start_task_timer();
     init_chain_configs();
+    evm::nonce::invalidate_nonces();
+    change_log::migrate_legacy_change_log();
+    change_log::init_entity_index();
+    user::migrate_users();
+    change_log::tasks::create_snapshot::schedule_create_snapshot();
+    certification::init_certified_data();
 }
 
 #[init]
@@ -0,0 +1,204 @@
+use crate::eas::{schema_item_to_token, SchemaItem};
+use anyhow::{bail, Result};
+use candid::{CandidType, Decode, Encode};
+use ethers_core::{
+    abi::{encode, Token},
+    utils::{hex, keccak256},
+};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::borrow::Cow;
+
+/// Recipes using private data must use the EAS private data schema, only the Merkle root
+/// of the attestation data is attested.
+pub const PRIVATE_DATA_SCHEMA: &str = "bytes32 privateData";
+
+/// One schema field of the private data, along with the proof needed to disclose it.
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct PrivateDataLeaf {
+    pub name: String,
+    pub value_type: String,
+    /// The field value as returned by the recipe processor, JSON encoded
+    pub value: String,
+    /// The ABI encoded field value, hex encoded
+    pub encoded_value: String,
+    pub proof: Vec<String>,
+}
+
+/// The Merkle tree of the attestation data of a private data run. Leaves and tree follow the
+/// layout of the OpenZeppelin `StandardMerkleTree` used by eas-sdk, so that proofs can be
+/// verified with the standard tooling.
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug)]
+pub struct PrivateData {
+    pub root: String,
+    pub tree: Vec<String>,
+    pub leaves: Vec<PrivateDataLeaf>,
+}
+
+impl Storable for PrivateData {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+fn hex_value(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Leaves are hashed twice, `keccak256(keccak256(abi.encode(type, name, value)))`.
+fn hash_leaf(value_type: &str, name: &str, encoded_value: &[u8]) -> [u8; 32] {
+    let encoded = encode(&[
+        Token::String(value_type.to_string()),
+        Token::String(name.to_string()),
+        Token::Bytes(encoded_value.to_vec()),
+    ]);
+    keccak256(keccak256(encoded))
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak256([&a[..], &b[..]].concat())
+    } else {
+        keccak256([&b[..], &a[..]].concat())
+    }
+}
+
+/// Builds a Merkle tree over the schema fields of the attestation data.
+pub fn create_private_data(attestation_data: &str) -> Result<PrivateData> {
+    let schema_items: Vec<SchemaItem> = serde_json::from_str(attestation_data)?;
+    if schema_items.is_empty() {
+        bail!("Attestation data contains no fields");
+    }
+
+    let encoded_values: Vec<Vec<u8>> = schema_items
+        .iter()
+        .map(|item| encode(&[schema_item_to_token(item)]))
+        .collect();
+
+    // Leaves are sorted by hash and stored at the end of the tree, in reverse order
+    let mut leaf_hashes: Vec<(usize, [u8; 32])> = schema_items
+        .iter()
+        .zip(encoded_values.iter())
+        .map(|(item, encoded_value)| hash_leaf(&item.type_field, &item.name, encoded_value))
+        .enumerate()
+        .collect();
+    leaf_hashes.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let leaf_count = leaf_hashes.len();
+    let mut tree = vec![[0u8; 32]; 2 * leaf_count - 1];
+    let mut tree_indices = vec![0; leaf_count];
+    for (i, (item_index, hash)) in leaf_hashes.iter().enumerate() {
+        let tree_index = tree.len() - 1 - i;
+        tree[tree_index] = *hash;
+        tree_indices[*item_index] = tree_index;
+    }
+    for i in (0..leaf_count - 1).rev() {
+        tree[i] = hash_pair(&tree[2 * i + 1], &tree[2 * i + 2]);
+    }
+
+    let leaves = schema_items
+        .iter()
+        .zip(encoded_values.iter())
+        .zip(tree_indices.iter())
+        .map(|((item, encoded_value), tree_index)| {
+            let mut proof = Vec::new();
+            let mut index = *tree_index;
+            while index > 0 {
+                let sibling_index = if index % 2 == 1 { index + 1 } else { index - 1 };
+                proof.push(hex_value(&tree[sibling_index]));
+                index = (index - 1) / 2;
+            }
+
+            PrivateDataLeaf {
+                name: item.name.clone(),
+                value_type: item.type_field.clone(),
+                value: serde_json::to_string(&item.value).unwrap_or_default(),
+                encoded_value: hex_value(encoded_value),
+                proof,
+            }
+        })
+        .collect();
+
+    Ok(PrivateData {
+        root: hex_value(&tree[0]),
+        tree: tree.iter().map(|node| hex_value(node)).collect(),
+        leaves,
+    })
+}
+
+/// Returns the attestation data that commits to the private data, the Merkle root.
+pub fn get_commitment_attestation_data(private_data: &PrivateData) -> String {
+    json!([{
+        "name": "privateData",
+        "type": "bytes32",
+        "value": private_data.root,
+    }])
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reference root and proofs computed with the OpenZeppelin `StandardMerkleTree`, leaf
+    // encoding `["string", "string", "bytes"]`
+    #[test]
+    fn merkle_root_and_proofs() {
+        let private_data = create_private_data(
+            r#"[
+                {"name":"score","type":"uint256","value":42},
+                {"name":"passed","type":"bool","value":true},
+                {"name":"name","type":"string","value":"alice"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            private_data.root,
+            "0x837f9cb7e7e3d2badee46e85fd045e158fc86d4f08366da27b12a91f7555306d"
+        );
+        assert_eq!(private_data.tree.len(), 5);
+        assert_eq!(private_data.tree[0], private_data.root);
+
+        let proofs: Vec<Vec<String>> = private_data
+            .leaves
+            .iter()
+            .map(|leaf| leaf.proof.clone())
+            .collect();
+        assert_eq!(
+            proofs,
+            vec![
+                vec!["0xe38cf57a91406beab7161b08e2cb96c6dc230360cb46264c3ae10aceda5a2ada"],
+                vec![
+                    "0x9887475dea15573100a20b6a0e72f5918ca0e31baf4f7d2adcf67e300c6b87e4",
+                    "0xbfa38eef37a83b0dccf2fffaf17a430dbf7170190adcdc5070df15f66b01152d"
+                ],
+                vec![
+                    "0x6bff8d15d277d6f875f68f16ccf5c226c1dc3fb7d7359bcbb892387733d0da25",
+                    "0xbfa38eef37a83b0dccf2fffaf17a430dbf7170190adcdc5070df15f66b01152d"
+                ],
+            ]
+        );
+
+        let leaf = &private_data.leaves[1];
+        assert_eq!(leaf.name, "passed");
+        assert_eq!(leaf.value_type, "bool");
+        assert_eq!(leaf.value, "true");
+        assert_eq!(
+            leaf.encoded_value,
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn empty_attestation_data() {
+        assert!(create_private_data("[]").is_err());
+    }
+}
@@ -1,8 +1,11 @@
+pub mod recipe_attester_balance;
 pub mod recipe_create;
 pub mod recipe_delete;
 pub mod recipe_get_by_id;
+pub mod recipe_get_by_id_certified;
 pub mod recipe_get_by_name;
 pub mod recipe_get_readme_by_id;
 pub mod recipe_get_readme_by_name;
 pub mod recipe_list;
 pub mod recipe_publish;
+pub mod recipe_unpublish;
@@ -0,0 +1,38 @@
+use candid::Nat;
+use ic_cdk::update;
+
+use crate::{
+    chain_config,
+    evm::rpc::eth_get_balance,
+    http_error::HttpError,
+    recipe::{self, RecipeId},
+    role::{self, Role},
+    user::auth_guard,
+};
+
+/// Returns the balance of the recipe's dedicated attester on a chain, in wei. Only the
+/// recipe creator and auditors can check it.
+#[update]
+async fn recipe_attester_balance(recipe_id: RecipeId, chain_id: u32) -> Result<Nat, HttpError> {
+    // Auditors don't need to sign in with an Ethereum address
+    let address = match role::has_role(ic_cdk::caller(), Role::Auditor) {
+        true => None,
+        false => Some(auth_guard()?),
+    };
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    if address.is_some_and(|address| address.to_string() != recipe.creator) {
+        return Err(HttpError::unauthorized(
+            "You are not the author of this recipe.",
+        ));
+    }
+
+    let attester = recipe
+        .attester
+        .ok_or_else(|| HttpError::bad_request("Recipe has no dedicated attester."))?;
+    let chain_config = chain_config::get(chain_id).map_err(HttpError::not_found)?;
+
+    eth_get_balance(&attester, &chain_config)
+        .await
+        .map_err(HttpError::internal_server_error)
+}
@@ -1,15 +1,44 @@
 use ic_cdk::update;
 
 use crate::{
+    evm::{
+        rpc::get_eth_address,
+        types::{Attester, Signer},
+    },
     http_error::HttpError,
     recipe::{self, Recipe, RecipeDetailsInput},
+    signing_key,
     user::auth_guard,
 };
 
 #[update]
-pub fn recipe_create(details: RecipeDetailsInput, readme: String) -> Result<Recipe, HttpError> {
+pub async fn recipe_create(
+    details: RecipeDetailsInput,
+    readme: String,
+) -> Result<Recipe, HttpError> {
     let address = auth_guard()?;
-    let recipe = Recipe::new(&details, &address).map_err(HttpError::bad_request)?;
+    let mut recipe = Recipe::new(&details, &address).map_err(HttpError::bad_request)?;
+
+    if recipe.has_dedicated_attester() {
+        let signer = Signer::active(Attester::for_recipe(&recipe));
+        let attester = get_eth_address(&signer)
+            .await
+            .map_err(|_| HttpError::internal_server_error("Couldn't derive attester address."))?;
+        recipe.attester = Some(attester);
+
+        // The attester can be funded under the new key before the rotation completes
+        if let Some(pending_key_id) = signing_key::pending_key_id() {
+            let pending_attester = signing_key::derive_recipe_attester(&pending_key_id, recipe.id)
+                .await
+                .map_err(HttpError::internal_server_error)?;
+            recipe.pending_attester = Some(pending_attester);
+        }
+
+        if !signer.is_active() {
+            return Err(HttpError::conflict("Signing key was rotated, try again."));
+        }
+    }
+
     let recipe = recipe::save(recipe).map_err(HttpError::conflict)?;
     recipe::write_readme(&recipe.name, &readme)
         .map_err(|_| HttpError::internal_server_error("Couldn't save README file."))?;
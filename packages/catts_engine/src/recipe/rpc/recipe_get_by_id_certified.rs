@@ -0,0 +1,12 @@
+use crate::{
+    certification::{self, CertifiedResponse},
+    http_error::HttpError,
+    recipe::{self, RecipeId},
+};
+use ic_cdk::query;
+
+#[query]
+fn recipe_get_by_id_certified(id: RecipeId) -> Result<CertifiedResponse, HttpError> {
+    let recipe = recipe::get_by_id(&id).map_err(HttpError::not_found)?;
+    certification::certified_recipe(&recipe)
+}
@@ -0,0 +1,26 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    recipe::{self, Recipe, RecipeId},
+    role::{has_role, Role},
+    user::auth_guard,
+};
+
+/// Unpublishes a recipe. Creators can unpublish their own recipes, moderators any recipe.
+#[update]
+fn recipe_unpublish(recipe_id: RecipeId) -> Result<Recipe, HttpError> {
+    let address = match has_role(ic_cdk::caller(), Role::Moderator) {
+        true => None,
+        false => Some(auth_guard()?),
+    };
+    let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+
+    if address.is_some_and(|address| address.to_string() != recipe.creator) {
+        return Err(HttpError::unauthorized(
+            "You are not the author of this recipe.",
+        ));
+    }
+
+    recipe::unpublish(&recipe_id).map_err(HttpError::bad_request)
+}
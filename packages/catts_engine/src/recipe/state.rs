@@ -1,6 +1,7 @@
 use std::{fs, path::Path};
 
 use crate::{
+    certification,
     change_log::{self, ChangeLogTypeName},
     RECIPES, RECIPE_NAME_INDEX,
 };
@@ -46,14 +47,15 @@ pub fn save(recipe: Recipe) -> Result<Recipe, RecipeError> {
     RECIPES.with_borrow_mut(|recipes| {
         recipes.insert(recipe.id, recipe.clone());
     });
+    certification::certify_recipe(&recipe);
 
     match maybe_saved_recipe {
         Some(saved_recipe) => {
-            change_log::update(ChangeLogTypeName::Recipe, recipe.id, saved_recipe, &recipe)
+            change_log::update(ChangeLogTypeName::Recipe, &recipe.id, saved_recipe, &recipe)
                 .unwrap();
         }
         None => {
-            change_log::create(ChangeLogTypeName::Recipe, recipe.id, &recipe).unwrap();
+            change_log::create(ChangeLogTypeName::Recipe, &recipe.id, &recipe).unwrap();
         }
     }
 
@@ -64,12 +66,67 @@ pub fn save(recipe: Recipe) -> Result<Recipe, RecipeError> {
     Ok(recipe)
 }
 
+/// Saves changes the canister makes to a recipe regardless of its publish state, such as
+/// moving its attester to a new signing key.
+pub fn update(recipe: Recipe) -> Result<Recipe, RecipeError> {
+    let saved_recipe = get_by_id(&recipe.id)?;
+
+    RECIPES.with_borrow_mut(|recipes| {
+        recipes.insert(recipe.id, recipe.clone());
+    });
+    certification::certify_recipe(&recipe);
+    change_log::update(
+        ChangeLogTypeName::Recipe,
+        &recipe.id,
+        &saved_recipe,
+        &recipe,
+    )
+    .unwrap();
+
+    Ok(recipe)
+}
+
+pub fn set_pending_attester(recipe_id: &RecipeId, pending_attester: Option<String>) {
+    if let Ok(mut recipe) = get_by_id(recipe_id) {
+        recipe.pending_attester = pending_attester;
+        update(recipe).unwrap();
+    }
+}
+
+/// Moves the dedicated attesters of all recipes to the addresses derived from the newly
+/// activated signing key. The previous addresses are kept.
+pub fn activate_pending_attesters() {
+    for mut recipe in list() {
+        if let Some(pending_attester) = recipe.pending_attester.take() {
+            if let Some(attester) = recipe.attester.take() {
+                recipe
+                    .previous_attesters
+                    .get_or_insert_with(Vec::new)
+                    .push(attester);
+            }
+            recipe.attester = Some(pending_attester);
+            update(recipe).unwrap();
+        }
+    }
+}
+
 pub fn publish(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
     let mut recipe = get_by_id(recipe_id)?;
     recipe.publish_state = RecipePublishState::Published;
     save(recipe)
 }
 
+/// Takes a published recipe offline, no new runs can be created for it. Existing runs are
+/// kept.
+pub fn unpublish(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
+    let mut recipe = get_by_id(recipe_id)?;
+    if recipe.publish_state != RecipePublishState::Published {
+        return Err(RecipeError::NotPublished);
+    }
+    recipe.publish_state = RecipePublishState::Unpublished;
+    update(recipe)
+}
+
 pub fn delete(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
     let recipe = get_by_id(recipe_id)?;
     RECIPES.with_borrow_mut(|recipes| {
@@ -78,7 +135,8 @@ pub fn delete(recipe_id: &RecipeId) -> Result<Recipe, RecipeError> {
     RECIPE_NAME_INDEX.with_borrow_mut(|index| {
         index.remove(&recipe.name);
     });
-    change_log::delete(ChangeLogTypeName::Recipe, recipe.id).unwrap();
+    certification::uncertify_recipe(&recipe);
+    change_log::delete(ChangeLogTypeName::Recipe, &recipe.id, &recipe).unwrap();
     Ok(recipe)
 }
 
@@ -1,6 +1,7 @@
 use crate::{
     eth_address::EthAddress,
     json::{bytes_to_hex_string_value, ToJsonValue},
+    private_data::PRIVATE_DATA_SCHEMA,
     time::time,
 };
 use candid::{CandidType, Decode, Encode};
@@ -22,6 +23,8 @@ pub type RecipeId = [u8; 12];
 pub enum RecipeError {
     #[error("Only drafts can be updated")]
     NotDraft,
+    #[error("Only published recipes can be unpublished")]
+    NotPublished,
     #[error("Name already in use")]
     NameInUse,
     #[error("Recipe not found")]
@@ -61,6 +64,7 @@ pub struct RecipeQuery {
 }
 
 #[derive(Serialize, Deserialize, CandidType, Clone, Validate)]
+#[validate(schema(function = "validate_private_data_schema"))]
 pub struct Recipe {
     pub id: RecipeId,
 
@@ -96,6 +100,32 @@ pub struct Recipe {
     pub resolver: String,
 
     pub revokable: bool,
+
+    /// Only the Merkle root of the attestation data is attested, the data itself stays
+    /// in the canister
+    pub private_data: Option<bool>,
+
+    /// The queries run for every address linked to the run creator, the processor receives
+    /// the results grouped per address
+    pub aggregate_linked_addresses: Option<bool>,
+
+    /// Attestations are made by an address derived from the recipe id instead of the
+    /// canister address, so that verifiers can trust this recipe specifically
+    pub dedicated_attester: Option<bool>,
+
+    /// The address of the dedicated attester, it has to be funded on every chain the
+    /// recipe is used on. On-chain runs are refused while its balance doesn't cover the
+    /// estimated attestation transaction, see `recipe_attester_balance`
+    pub attester: Option<String>,
+
+    /// The address of the dedicated attester under the pending signing key, while a key
+    /// rotation is in progress
+    pub pending_attester: Option<String>,
+
+    /// Addresses of the dedicated attester under previous signing keys, attestations made
+    /// by them remain valid
+    pub previous_attesters: Option<Vec<String>>,
+
     pub publish_state: RecipePublishState,
 }
 
@@ -120,6 +150,16 @@ fn validate_recipe_name(name: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Private data recipes attest a Merkle root, they must use the EAS private data schema.
+fn validate_private_data_schema(recipe: &Recipe) -> Result<(), ValidationError> {
+    if recipe.uses_private_data() && recipe.schema != PRIVATE_DATA_SCHEMA {
+        return Err(ValidationError::new(
+            "Private data recipes must use the schema 'bytes32 privateData'",
+        ));
+    }
+    Ok(())
+}
+
 fn validate_keywords(keywords: &[String]) -> Result<(), ValidationError> {
     if keywords.is_empty() {
         return Err(ValidationError::new("Keywords must not be empty"));
@@ -176,6 +216,27 @@ impl ToJsonValue for Recipe {
         obj.insert("schema".to_string(), json!(self.schema));
         obj.insert("resolver".to_string(), json!(self.resolver));
         obj.insert("revokable".to_string(), json!(self.revokable));
+        if let Some(private_data) = self.private_data {
+            obj.insert("private_data".to_string(), json!(private_data));
+        }
+        if let Some(aggregate_linked_addresses) = self.aggregate_linked_addresses {
+            obj.insert(
+                "aggregate_linked_addresses".to_string(),
+                json!(aggregate_linked_addresses),
+            );
+        }
+        if let Some(dedicated_attester) = self.dedicated_attester {
+            obj.insert("dedicated_attester".to_string(), json!(dedicated_attester));
+        }
+        if let Some(ref attester) = self.attester {
+            obj.insert("attester".to_string(), json!(attester));
+        }
+        if let Some(ref pending_attester) = self.pending_attester {
+            obj.insert("pending_attester".to_string(), json!(pending_attester));
+        }
+        if let Some(ref previous_attesters) = self.previous_attesters {
+            obj.insert("previous_attesters".to_string(), json!(previous_attesters));
+        }
         obj.insert(
             "publish_state".to_string(),
             json!(format!("{}", self.publish_state)),
@@ -209,6 +270,12 @@ impl Recipe {
             schema: details.schema.clone(),
             resolver: details.resolver.clone(),
             revokable: details.revokable,
+            private_data: details.private_data,
+            aggregate_linked_addresses: details.aggregate_linked_addresses,
+            dedicated_attester: details.dedicated_attester,
+            attester: None,
+            pending_attester: None,
+            previous_attesters: None,
             publish_state: RecipePublishState::Draft,
         };
 
@@ -216,6 +283,18 @@ impl Recipe {
 
         Ok(recipe)
     }
+
+    pub fn uses_private_data(&self) -> bool {
+        self.private_data.unwrap_or(false)
+    }
+
+    pub fn aggregates_linked_addresses(&self) -> bool {
+        self.aggregate_linked_addresses.unwrap_or(false)
+    }
+
+    pub fn has_dedicated_attester(&self) -> bool {
+        self.dedicated_attester.unwrap_or(false)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, CandidType)]
@@ -229,4 +308,7 @@ pub struct RecipeDetailsInput {
     pub schema: String,
     pub resolver: String,
     pub revokable: bool,
+    pub private_data: Option<bool>,
+    pub aggregate_linked_addresses: Option<bool>,
+    pub dedicated_attester: Option<bool>,
 }
@@ -0,0 +1,3 @@
+pub mod role_grant;
+pub mod role_list;
+pub mod role_revoke;
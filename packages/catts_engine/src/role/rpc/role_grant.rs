@@ -0,0 +1,14 @@
+use crate::{
+    http_error::HttpError,
+    role::{self, controller_guard, Role, RoleAssignment},
+};
+use candid::Principal;
+use ic_cdk::update;
+
+/// Grants a role to a principal. Only controllers can manage roles.
+#[update]
+fn role_grant(principal: Principal, role: Role) -> Result<RoleAssignment, HttpError> {
+    controller_guard()?;
+
+    role::grant(principal, role).map_err(HttpError::bad_request)
+}
@@ -0,0 +1,12 @@
+use crate::{
+    http_error::HttpError,
+    role::{self, require_role, Role, RoleAssignment},
+};
+use ic_cdk::query;
+
+#[query]
+fn role_list() -> Result<Vec<RoleAssignment>, HttpError> {
+    require_role(Role::Auditor)?;
+
+    Ok(role::list())
+}
@@ -0,0 +1,17 @@
+use crate::{
+    http_error::HttpError,
+    role::{self, controller_guard, Role, RoleAssignment, RoleError},
+};
+use candid::Principal;
+use ic_cdk::update;
+
+/// Revokes a role from a principal. Only controllers can manage roles.
+#[update]
+fn role_revoke(principal: Principal, role: Role) -> Result<RoleAssignment, HttpError> {
+    controller_guard()?;
+
+    role::revoke(principal, role).map_err(|err| match err {
+        RoleError::NotGranted => HttpError::not_found(err),
+        _ => HttpError::bad_request(err),
+    })
+}
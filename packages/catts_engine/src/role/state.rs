@@ -0,0 +1,62 @@
+use super::{Role, RoleAssignment, RoleError};
+use crate::ROLES;
+use candid::Principal;
+use ic_stable_structures::storable::Blob;
+
+fn principal_key(principal: Principal) -> Result<Blob<29>, RoleError> {
+    Blob::try_from(principal.as_slice()).map_err(|_| RoleError::InvalidPrincipal)
+}
+
+pub fn get(principal: Principal) -> Vec<Role> {
+    principal_key(principal)
+        .ok()
+        .and_then(|key| ROLES.with_borrow(|roles| roles.get(&key)))
+        .map(|assignment| assignment.roles)
+        .unwrap_or_default()
+}
+
+pub fn grant(principal: Principal, role: Role) -> Result<RoleAssignment, RoleError> {
+    let key = principal_key(principal)?;
+    let mut roles = get(principal);
+    if !roles.contains(&role) {
+        roles.push(role);
+    }
+
+    let assignment = RoleAssignment { principal, roles };
+    ROLES.with_borrow_mut(|r| r.insert(key, assignment.clone()));
+
+    Ok(assignment)
+}
+
+pub fn revoke(principal: Principal, role: Role) -> Result<RoleAssignment, RoleError> {
+    let key = principal_key(principal)?;
+    let mut roles = get(principal);
+    if !roles.contains(&role) {
+        return Err(RoleError::NotGranted);
+    }
+    roles.retain(|r| *r != role);
+
+    let assignment = RoleAssignment { principal, roles };
+    ROLES.with_borrow_mut(|r| {
+        if assignment.roles.is_empty() {
+            r.remove(&key);
+        } else {
+            r.insert(key, assignment.clone());
+        }
+    });
+
+    Ok(assignment)
+}
+
+pub fn list() -> Vec<RoleAssignment> {
+    ROLES.with_borrow(|roles| roles.iter().map(|(_, assignment)| assignment).collect())
+}
+
+/// Controllers implicitly have all roles, admins pass every role check.
+pub fn has_role(principal: Principal, role: Role) -> bool {
+    if ic_cdk::api::is_controller(&principal) {
+        return true;
+    }
+    let roles = get(principal);
+    roles.contains(&Role::Admin) || roles.contains(&role)
+}
@@ -0,0 +1,43 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+use std::borrow::Cow;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RoleError {
+    #[error("Invalid principal")]
+    InvalidPrincipal,
+    #[error("Principal does not have the role")]
+    NotGranted,
+}
+
+/// Roles are granted by canister controllers. Controllers and admins pass every role check.
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Admin,
+    /// Runs and schedules maintenance: retries, snapshots, tasks, chain configs
+    Operator,
+    /// Moderates user generated content such as recipes
+    Moderator,
+    /// Read only access to logs and internal state
+    Auditor,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone)]
+pub struct RoleAssignment {
+    pub principal: Principal,
+    pub roles: Vec<Role>,
+}
+
+impl Storable for RoleAssignment {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,25 @@
+use super::{has_role, Role};
+use crate::http_error::HttpError;
+use candid::Principal;
+
+pub fn require_role(role: Role) -> Result<(), HttpError> {
+    let caller = ic_cdk::caller();
+    if has_role(caller, role) {
+        return Ok(());
+    }
+    if caller == Principal::anonymous() {
+        return Err(HttpError::unauthorized(
+            "Anonymous caller is not allowed to call this method.",
+        ));
+    }
+    Err(HttpError::forbidden(format!("{:?} role required", role)))
+}
+
+pub fn controller_guard() -> Result<(), HttpError> {
+    if ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Ok(());
+    }
+    Err(HttpError::forbidden(
+        "Only controllers can call this method",
+    ))
+}
@@ -1,4 +1,7 @@
 pub mod run_cancel;
 pub mod run_create;
 pub mod run_get;
+pub mod run_get_certified;
+pub mod run_get_private_data;
 pub mod run_register_payment;
+pub mod run_retry;
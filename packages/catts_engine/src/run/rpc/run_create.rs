@@ -1,17 +1,29 @@
 use crate::{
-    chain_config::{self},
+    chain_config::{self, ChainConfigError},
+    eth_address::EthAddress,
+    evm::rpc::eth_get_balance,
     http_error::HttpError,
     logger,
     recipe::{self, RecipeId, RecipePublishState},
     run::{
-        self, estimate_gas_usage, get_min_user_fee_for_chain, util::estimate_transaction_fees, Run,
+        self, estimate_gas_usage, get_min_user_fee_for_chain, get_offchain_user_fee_for_chain,
+        util::estimate_transaction_fees, verify_recipient_proof, Run, RunMode, RunRecipientInput,
     },
-    user::auth_guard,
+    user::{self, auth_guard},
 };
 use ic_cdk::{api::canister_balance, update};
 
+/// Creates a run. Runs are attested on-chain unless the off-chain mode is requested.
+///
+/// Attestations go to the caller's primary address, unless an alternative `recipient` is
+/// given along with a proof that the caller controls it.
 #[update]
-async fn run_create(recipe_id: RecipeId, chain_id: u32) -> Result<Run, HttpError> {
+async fn run_create(
+    recipe_id: RecipeId,
+    chain_id: u32,
+    mode: Option<RunMode>,
+    recipient: Option<RunRecipientInput>,
+) -> Result<Run, HttpError> {
     let cycles_before = canister_balance();
     let address = auth_guard()?;
     let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
@@ -20,11 +32,38 @@ async fn run_create(recipe_id: RecipeId, chain_id: u32) -> Result<Run, HttpError
         return Err(HttpError::bad_request("Recipe is not published"));
     }
 
-    chain_config::get(chain_id).map_err(|_| {
-        HttpError::internal_server_error(format!("Chain {} is not supported", chain_id).as_str())
+    let chain_config = chain_config::get_enabled(chain_id).map_err(|err| match err {
+        ChainConfigError::Disabled => {
+            HttpError::bad_request(format!("Chain {} is disabled", chain_id))
+        }
+        _ => HttpError::internal_server_error(format!("Chain {} is not supported", chain_id)),
     })?;
 
-    let mut run = Run::new(&recipe_id, chain_id, &address).map_err(HttpError::bad_request)?;
+    let mode = mode.unwrap_or(RunMode::Onchain);
+    let mut run = Run::new(&recipe_id, chain_id, &address, mode).map_err(HttpError::bad_request)?;
+
+    run.recipient = match recipient {
+        Some(recipient) => {
+            let recipient_address = EthAddress::new(&recipient.address)
+                .map_err(|e| HttpError::bad_request(e.to_string()))?;
+            verify_recipient_proof(&address, &recipient_address, &recipient.signature, chain_id)
+                .await
+                .map_err(HttpError::unauthorized)?;
+            Some(recipient_address.to_string())
+        }
+        None => {
+            let user = user::get_by_principal(ic_cdk::caller()).map_err(HttpError::not_found)?;
+            Some(user.primary_address())
+        }
+    };
+
+    // Off-chain runs are signed by the canister, no gas to estimate
+    if mode == RunMode::Offchain {
+        let user_fee =
+            get_offchain_user_fee_for_chain(chain_id).map_err(HttpError::internal_server_error)?;
+        run.user_fee = Some(user_fee);
+        return Ok(run::create(run));
+    }
 
     let fee_estimates = estimate_transaction_fees(&run)
         .await
@@ -34,11 +73,25 @@ async fn run_create(recipe_id: RecipeId, chain_id: u32) -> Result<Run, HttpError
         .await
         .map_err(HttpError::internal_server_error)?;
 
-    let user_fee = gas.clone()
+    let transaction_cost = gas.clone()
         * (fee_estimates.base_fee_per_gas.clone() + fee_estimates.max_priority_fee_per_gas.clone());
     let min_user_fee =
         get_min_user_fee_for_chain(chain_id).map_err(HttpError::internal_server_error)?;
-    let user_fee = user_fee.max(min_user_fee);
+    let user_fee = transaction_cost.clone().max(min_user_fee);
+
+    // Dedicated attesters are funded by the recipe creator, refuse runs the attester can't
+    // pay the gas for rather than failing them after the user has paid
+    if let Some(attester) = recipe.attester.as_ref() {
+        let balance = eth_get_balance(attester, &chain_config)
+            .await
+            .map_err(HttpError::internal_server_error)?;
+        if balance < transaction_cost {
+            return Err(HttpError::service_unavailable(format!(
+                "Recipe attester {} is not funded on chain {}",
+                attester, chain_id
+            )));
+        }
+    }
 
     ic_cdk::println!(
         "base_fee_per_gas: {}, max_priority_fee_per_gas: {}, gas: {}",
@@ -0,0 +1,12 @@
+use crate::{
+    certification::{self, CertifiedResponse},
+    http_error::HttpError,
+    run::{self, RunId},
+};
+use ic_cdk::query;
+
+#[query]
+fn run_get_certified(run_id: RunId) -> Result<CertifiedResponse, HttpError> {
+    let run = run::get(&run_id).map_err(HttpError::not_found)?;
+    certification::certified_run(&run)
+}
@@ -0,0 +1,23 @@
+use crate::{
+    http_error::HttpError,
+    private_data::PrivateData,
+    run::{self, RunId},
+    user::auth_guard,
+};
+use ic_cdk::query;
+
+/// Returns the Merkle tree and proofs of a private data run. Only the run creator can
+/// access them, fields can then be disclosed one by one using their proofs.
+#[query]
+fn run_get_private_data(run_id: RunId) -> Result<PrivateData, HttpError> {
+    let address = auth_guard()?;
+    let run = run::get(&run_id).map_err(HttpError::not_found)?;
+
+    if run.creator != address.to_string() {
+        return Err(HttpError::forbidden(
+            "Only the run creator can access its private data",
+        ));
+    }
+
+    run::get_private_data(&run_id).ok_or(HttpError::not_found("Run has no private data"))
+}
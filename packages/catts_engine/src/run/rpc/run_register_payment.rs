@@ -3,14 +3,14 @@ use ic_cdk::{api::canister_balance, update};
 use crate::{
     http_error::HttpError,
     logger::info,
-    run::{self, tasks::register_payment::ProcessRunPaymentArgs, Run, RunId},
-    tasks::{add_task, Task, TaskType},
+    run::{
+        self,
+        tasks::register_payment::{schedule_register_payment, ProcessRunPaymentArgs},
+        Run, RunId,
+    },
     user::auth_guard,
 };
 
-const PROCESS_RUN_PAYMENT_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
-const PROCESS_RUN_PAYMENT_MAX_RETRIES: u32 = 3;
-
 #[update]
 async fn run_register_payment(
     run_id: RunId,
@@ -29,23 +29,11 @@ async fn run_register_payment(
     let run = run::register_payment(&run_id, &transaction_hash, block_to_process)
         .map_err(HttpError::bad_request)?;
 
-    let args: Vec<u8> = bincode::serialize(&ProcessRunPaymentArgs {
+    schedule_register_payment(&ProcessRunPaymentArgs {
         block_to_process,
         from_address: address.as_byte_array(),
         run_id,
-    })
-    .unwrap();
-
-    add_task(
-        0, // Run ASAP
-        Task {
-            task_type: TaskType::ProcessRunPayment,
-            args,
-            max_retries: PROCESS_RUN_PAYMENT_MAX_RETRIES,
-            execute_count: 0,
-            retry_interval: PROCESS_RUN_PAYMENT_RETRY_INTERVAL,
-        },
-    );
+    });
 
     let cycles_after = canister_balance();
     info(
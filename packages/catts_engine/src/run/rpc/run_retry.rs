@@ -0,0 +1,86 @@
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    role::{self, Role},
+    run::{
+        self,
+        tasks::{
+            get_attestation_uid::schedule_get_attestation_uid,
+            register_payment::{schedule_register_payment, ProcessRunPaymentArgs},
+            util::schedule_attestation,
+        },
+        Run, RunError, RunId, RunStatus, SentAttestation,
+    },
+    user::auth_guard,
+};
+use ic_cdk::update;
+
+/// Resumes a failed run from its last successful step. A run that has an attestation
+/// transaction is never attested again, only the attestation UID is fetched. Transactions
+/// that failed to send are looked up on the chain before a run is attested again.
+#[update]
+async fn run_retry(run_id: RunId) -> Result<Run, HttpError> {
+    let run = run::get(&run_id).map_err(HttpError::not_found)?;
+
+    // Only creator or operators can retry the run
+    if !role::has_role(ic_cdk::caller(), Role::Operator) {
+        let address = auth_guard()?;
+        if run.creator != address.to_string() {
+            return Err(HttpError::forbidden(
+                "Only creator or operators can retry the run",
+            ));
+        }
+    }
+
+    // A run waiting for attestation might have a transaction that failed to send but still
+    // reached the chain, it is only attested again once that transaction can't be mined
+    if !run.is_cancelled && run.error.is_some() && run.status() == RunStatus::PaymentVerified {
+        match run::check_sent_attestation(&run)
+            .await
+            .map_err(HttpError::service_unavailable)?
+        {
+            SentAttestation::Mined(hash) => {
+                let mut run = run::get(&run_id).map_err(HttpError::not_found)?;
+                run.attestation_transaction_hash = Some(hash);
+                run::update(run).map_err(HttpError::internal_server_error)?;
+            }
+            SentAttestation::NotAttested => {}
+            SentAttestation::Pending => {
+                return Err(HttpError::conflict(
+                    "The attestation transaction might still be pending, retry later",
+                ));
+            }
+        }
+    }
+
+    let run = run::reset_for_retry(&run_id).map_err(|err| match err {
+        RunError::CantBeRetried(msg) => HttpError::bad_request(msg),
+        err => HttpError::internal_server_error(err),
+    })?;
+
+    match run.status() {
+        RunStatus::PaymentRegistered => {
+            let block_to_process = run
+                .payment_block_number
+                .as_ref()
+                .and_then(|block_number| u128::try_from(&block_number.0).ok())
+                .ok_or(HttpError::internal_server_error(
+                    "Run has no valid payment block number",
+                ))?;
+            schedule_register_payment(&ProcessRunPaymentArgs {
+                block_to_process,
+                from_address: EthAddress::from(run.creator.as_str()).as_byte_array(),
+                run_id,
+            });
+        }
+        RunStatus::PaymentVerified => {
+            schedule_attestation(&run);
+        }
+        RunStatus::AttestationCreated => {
+            schedule_get_attestation_uid(&run_id, 0);
+        }
+        _ => {}
+    }
+
+    Ok(run)
+}
@@ -1,7 +1,8 @@
-use super::types::{Run, RunError, RunId};
+use super::types::{Run, RunError, RunId, RunStatus};
 use crate::change_log::ChangeLogTypeName;
 use crate::eth_address::EthAddress;
-use crate::{change_log, RUNS};
+use crate::private_data::PrivateData;
+use crate::{certification, change_log, webhook, ATTESTATION_QUEUE, PRIVATE_DATA, RUNS};
 use blake2::digest::{Update, VariableOutput};
 use blake2::Blake2bVar;
 use candid::Nat;
@@ -19,7 +20,8 @@ pub fn create(run: Run) -> Run {
     RUNS.with_borrow_mut(|runs| {
         runs.insert(run.id, run.clone());
     });
-    change_log::create(ChangeLogTypeName::Run, run.id, &run).unwrap();
+    certification::certify_run(&run);
+    change_log::create(ChangeLogTypeName::Run, &run.id, &run).unwrap();
     run
 }
 
@@ -28,7 +30,9 @@ pub fn update(run: Run) -> Result<Run, RunError> {
     RUNS.with_borrow_mut(|runs| {
         runs.insert(run.id, run.clone());
     });
-    change_log::update(ChangeLogTypeName::Run, run.id, &old_run, &run).unwrap();
+    certification::certify_run(&run);
+    change_log::update(ChangeLogTypeName::Run, &run.id, &old_run, &run).unwrap();
+    webhook::notify_run_update(&old_run, &run);
     Ok(run)
 }
 
@@ -45,10 +49,58 @@ pub fn cancel(run_id: &RunId) -> Result<Run, RunError> {
     update(run)
 }
 
+/// Clears the error of a failed run so that it can be resumed from its last successful step.
+pub fn reset_for_retry(run_id: &RunId) -> Result<Run, RunError> {
+    let mut run = get(run_id)?;
+
+    if run.is_cancelled {
+        return Err(RunError::CantBeRetried("Run is cancelled".to_string()));
+    }
+
+    // Failed tasks always save an error on the run, a run without an error is still being
+    // processed
+    if run.error.is_none() {
+        return Err(RunError::CantBeRetried(
+            "Only failed runs can be retried".to_string(),
+        ));
+    }
+
+    match run.status() {
+        RunStatus::PaymentPending => {
+            return Err(RunError::CantBeRetried("Run is not paid".to_string()));
+        }
+        RunStatus::AttestationUidConfirmed => {
+            return Err(RunError::CantBeRetried(
+                "Run is already completed".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    if is_queued_for_attestation(run_id) {
+        return Err(RunError::CantBeRetried(
+            "Run is already queued for attestation".to_string(),
+        ));
+    }
+
+    run.error = None;
+
+    update(run)
+}
+
 pub fn get(run_id: &RunId) -> Result<Run, RunError> {
     RUNS.with_borrow(|runs| runs.get(run_id).ok_or(RunError::NotFound))
 }
 
+pub fn list_by_creator(creator: &EthAddress) -> Vec<Run> {
+    RUNS.with_borrow(|runs| {
+        runs.iter()
+            .filter(|(_, run)| run.creator == creator.as_str())
+            .map(|(_, run)| run)
+            .collect()
+    })
+}
+
 pub fn register_payment(
     run_id: &RunId,
     transaction_hash: &str,
@@ -65,3 +117,59 @@ pub fn register_payment(
 
     update(run)
 }
+
+pub fn queue_for_attestation(run: &Run) {
+    ATTESTATION_QUEUE.with_borrow_mut(|queue| {
+        queue.insert(run.id, run.chain_id);
+    });
+}
+
+/// Removes and returns up to `max` runs that are queued for attestation on the given chain.
+pub fn dequeue_for_attestation(chain_id: u32, max: usize) -> Vec<RunId> {
+    ATTESTATION_QUEUE.with_borrow_mut(|queue| {
+        let run_ids: Vec<RunId> = queue
+            .iter()
+            .filter(|(_, queued_chain_id)| *queued_chain_id == chain_id)
+            .map(|(run_id, _)| run_id)
+            .take(max)
+            .collect();
+
+        for run_id in run_ids.iter() {
+            queue.remove(run_id);
+        }
+
+        run_ids
+    })
+}
+
+pub fn is_queued_for_attestation(run_id: &RunId) -> bool {
+    ATTESTATION_QUEUE.with_borrow(|queue| queue.contains_key(run_id))
+}
+
+pub fn has_queued_attestations(chain_id: u32) -> bool {
+    ATTESTATION_QUEUE.with_borrow(|queue| {
+        queue
+            .iter()
+            .any(|(_, queued_chain_id)| queued_chain_id == chain_id)
+    })
+}
+
+/// Returns true if the chain has runs that are not final, including runs that still wait
+/// for their payment.
+pub fn has_open_runs(chain_id: u32) -> bool {
+    RUNS.with_borrow(|runs| {
+        runs.iter()
+            .any(|(_, run)| run.chain_id == chain_id && !run.is_final())
+    })
+}
+
+/// Private data is stored outside of the run, runs and their change log are public.
+pub fn save_private_data(run_id: &RunId, private_data: PrivateData) {
+    PRIVATE_DATA.with_borrow_mut(|data| {
+        data.insert(*run_id, private_data);
+    });
+}
+
+pub fn get_private_data(run_id: &RunId) -> Option<PrivateData> {
+    PRIVATE_DATA.with_borrow(|data| data.get(run_id))
+}
@@ -0,0 +1,213 @@
+use crate::{
+    chain_config,
+    eas::{create_attest_request, fee_compatible_batches, sign_batch_attestation},
+    evm::{
+        rpc::{eth_send_signed_transaction, get_eth_address, EthTransactionError},
+        types::{Attester, Signer},
+        util::nat_to_u128,
+    },
+    recipe::{self},
+    run::{self, Run, RunId, RunStatus},
+    tasks::{add_task, is_task_scheduled, Task, TaskError, TaskExecutor, TaskType},
+};
+use anyhow::{bail, Result};
+use ethers_core::abi::Token;
+use futures::Future;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+use super::{
+    track_attestation_transaction::track_attestation_transaction, util::save_error_and_cancel,
+};
+
+const CREATE_ATTESTATION_BATCH_WINDOW: u64 = 30_000_000_000; // 30 seconds
+const CREATE_ATTESTATION_BATCH_MAX_SIZE: usize = 20;
+const CREATE_ATTESTATION_BATCH_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const CREATE_ATTESTATION_BATCH_MAX_RETRIES: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CreateAttestationBatchArgs {
+    pub chain_id: u32,
+}
+
+/// Schedules an attestation batch for the chain unless one is already scheduled. Runs
+/// that are queued within the batch window are attested in the same transaction.
+pub fn schedule_attestation_batch(chain_id: u32) {
+    let args = bincode::serialize(&CreateAttestationBatchArgs { chain_id }).unwrap();
+
+    if is_task_scheduled(&TaskType::CreateAttestationBatch, &args) {
+        return;
+    }
+
+    add_task(
+        ic_cdk::api::time() + CREATE_ATTESTATION_BATCH_WINDOW,
+        Task {
+            task_type: TaskType::CreateAttestationBatch,
+            args,
+            max_retries: CREATE_ATTESTATION_BATCH_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: CREATE_ATTESTATION_BATCH_RETRY_INTERVAL,
+        },
+    );
+}
+
+/// Runs the recipe queries and the processor for a run and returns the attest request,
+/// along with the attester that has to send it.
+async fn prepare_attest_request(run_id: &RunId) -> Result<(Run, Attester, Token)> {
+    let run = run::get(run_id)?;
+    let recipe = recipe::get_by_id(&run.recipe_id)?;
+
+    if run.is_cancelled {
+        bail!("Run is cancelled");
+    }
+
+    match run.status() {
+        RunStatus::PaymentPending => bail!("Run not yet paid"),
+        RunStatus::PaymentRegistered => bail!("Run payment not yet verified"),
+        RunStatus::PaymentVerified => {}
+        _ => bail!("Run already attested"),
+    }
+
+    let recipient = run.recipient();
+    let attestation_data = run::get_attestation_data(&recipe, &run).await?;
+    let attestation_data = run::commit_private_data(&run.id, &recipe, attestation_data)?;
+
+    let attest_request = create_attest_request(&recipe, &attestation_data, &recipient)?;
+
+    Ok((run, Attester::for_recipe(&recipe), attest_request))
+}
+
+/// Nonce errors clear up once the nonce is synced, the runs are attested in a later batch.
+/// Other errors fail the runs.
+fn fail_attestation_batch(chain_id: u32, runs: &[Run], err: anyhow::Error) -> TaskError {
+    if err
+        .downcast_ref::<EthTransactionError>()
+        .is_some_and(|err| err.is_retryable())
+    {
+        for run in runs.iter() {
+            run::queue_for_attestation(run);
+        }
+        schedule_attestation_batch(chain_id);
+        return TaskError::Cancel(format!("Attestation batch postponed: {}", err));
+    }
+
+    let error = format!("Error creating attestation: {}", err);
+    for run in runs.iter() {
+        save_error_and_cancel(&run.id, error.clone());
+    }
+    TaskError::Cancel(error)
+}
+
+/// Sends one attestation transaction for runs that share an attester and starts tracking it.
+async fn send_attestation_batch(
+    chain_id: u32,
+    attester: Attester,
+    mut runs: Vec<Run>,
+    attest_requests: Vec<Token>,
+) -> Result<(), TaskError> {
+    let signer = Signer::active(attester);
+    let chain_config = chain_config::get(chain_id)
+        .map_err(|err| fail_attestation_batch(chain_id, &runs, err.into()))?;
+    let signed_transaction =
+        match sign_batch_attestation(&runs, attest_requests, chain_id, &signer).await {
+            Ok(signed_transaction) => signed_transaction,
+            Err(err) => return Err(fail_attestation_batch(chain_id, &runs, err)),
+        };
+
+    // The address is cached, it was derived when the transaction was signed
+    let signer_address = get_eth_address(&signer).await.ok();
+
+    // Recorded before sending, a send that fails might still have reached the chain.
+    // run_retry checks the hash and nonce before attesting the run again.
+    let nonce = nat_to_u128(&signed_transaction.request.nonce).and_then(|n| u64::try_from(n).ok());
+    for (batch_index, run) in runs.iter_mut().enumerate() {
+        run.attestation_transaction_hashes
+            .get_or_insert_with(Vec::new)
+            .push(signed_transaction.hash.clone());
+        run.attestation_batch_index = Some(batch_index as u32);
+        run.attestation_nonce = nonce;
+        if let Some(ref signer_address) = signer_address {
+            run.add_attester_address(signer_address.clone());
+        }
+        run::update(run.clone()).unwrap();
+    }
+
+    let sent_transaction =
+        match eth_send_signed_transaction(signed_transaction, &chain_config, &signer).await {
+            Ok(sent_transaction) => sent_transaction,
+            Err(err) => return Err(fail_attestation_batch(chain_id, &runs, err.into())),
+        };
+
+    track_attestation_transaction(chain_id, &runs, &sent_transaction);
+
+    for run in runs.into_iter() {
+        // Runs might have been updated while the transaction was sent
+        let Ok(mut run) = run::get(&run.id) else {
+            continue;
+        };
+        run.attestation_transaction_hash = Some(sent_transaction.hash.clone());
+        let hashes = run
+            .attestation_transaction_hashes
+            .get_or_insert_with(Vec::new);
+        if !hashes.contains(&sent_transaction.hash) {
+            hashes.push(sent_transaction.hash.clone());
+        }
+        run::update(run).unwrap();
+    }
+
+    Ok(())
+}
+
+pub struct CreateAttestationBatchExecutor {}
+
+impl TaskExecutor for CreateAttestationBatchExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let args: CreateAttestationBatchArgs = bincode::deserialize(&task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let run_ids =
+                run::dequeue_for_attestation(args.chain_id, CREATE_ATTESTATION_BATCH_MAX_SIZE);
+
+            // Runs that didn't fit in this batch are picked up by the next one
+            if run::has_queued_attestations(args.chain_id) {
+                schedule_attestation_batch(args.chain_id);
+            }
+
+            // Recipes with a dedicated attester send their attestations from their own
+            // address, runs are batched per attester
+            let mut attester_runs: Vec<(Attester, Vec<(Run, Token)>)> = Vec::new();
+
+            for run_id in run_ids {
+                match prepare_attest_request(&run_id).await {
+                    Ok((run, attester, attest_request)) => {
+                        match attester_runs
+                            .iter_mut()
+                            .find(|(batch_attester, _)| *batch_attester == attester)
+                        {
+                            Some((_, runs)) => runs.push((run, attest_request)),
+                            None => attester_runs.push((attester, vec![(run, attest_request)])),
+                        }
+                    }
+                    Err(err) => {
+                        save_error_and_cancel(&run_id, err.to_string());
+                    }
+                }
+            }
+
+            let mut result = Ok(());
+            for (attester, runs) in attester_runs {
+                for batch in fee_compatible_batches(runs) {
+                    let (runs, attest_requests) = batch.into_iter().unzip();
+                    if let Err(err) =
+                        send_attestation_batch(args.chain_id, attester, runs, attest_requests).await
+                    {
+                        result = Err(err);
+                    }
+                }
+            }
+
+            result
+        })
+    }
+}
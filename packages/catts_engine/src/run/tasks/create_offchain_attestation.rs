@@ -0,0 +1,91 @@
+use crate::{
+    chain_config::{self},
+    eas::create_offchain_attestation,
+    logger, recipe,
+    run::{self, RunId, RunStatus},
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+};
+use futures::Future;
+use std::pin::Pin;
+
+use super::util::save_error_and_cancel;
+
+const CREATE_OFFCHAIN_ATTESTATION_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const CREATE_OFFCHAIN_ATTESTATION_MAX_RETRIES: u32 = 3;
+
+pub fn schedule_create_offchain_attestation(run_id: &RunId) {
+    add_task(
+        ic_cdk::api::time(),
+        Task {
+            task_type: TaskType::CreateOffchainAttestation,
+            args: run_id.to_vec(),
+            max_retries: CREATE_OFFCHAIN_ATTESTATION_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: CREATE_OFFCHAIN_ATTESTATION_RETRY_INTERVAL,
+        },
+    );
+}
+
+pub struct CreateOffchainAttestationExecutor {}
+
+impl TaskExecutor for CreateOffchainAttestationExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let run_id = run::vec_to_run_id(task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let run = run::get(&run_id)
+                .map_err(|_| save_error_and_cancel(&run_id, "Run not found".to_string()))?;
+
+            if run.status() != RunStatus::PaymentVerified {
+                return Err(save_error_and_cancel(
+                    &run_id,
+                    "Run payment not verified or run already attested".to_string(),
+                ));
+            }
+
+            let recipe = recipe::get_by_id(&run.recipe_id)
+                .map_err(|_| save_error_and_cancel(&run_id, "Recipe not found".to_string()))?;
+
+            let chain_config = chain_config::get(run.chain_id).map_err(|_| {
+                save_error_and_cancel(&run_id, "Chain config not found".to_string())
+            })?;
+
+            let recipient = run.recipient();
+
+            let attestation_data = run::get_attestation_data(&recipe, &run)
+                .await
+                .map_err(|err| TaskError::Retry(err.to_string()))?;
+
+            let attestation_data = run::commit_private_data(&run_id, &recipe, attestation_data)
+                .map_err(|err| save_error_and_cancel(&run_id, err.to_string()))?;
+
+            let attestation =
+                create_offchain_attestation(&recipe, &attestation_data, &recipient, &chain_config)
+                    .await
+                    .map_err(|err| {
+                        TaskError::Retry(format!("Error creating off-chain attestation: {}", err))
+                    })?;
+
+            // The run might have changed while the attestation was being created
+            let mut run = run::get(&run_id).unwrap();
+            run.attestation_uid = Some(attestation.uid);
+            run.offchain_attestation = Some(attestation.package);
+            run.add_attester_address(attestation.signer);
+            run::update(run).unwrap();
+
+            logger::info("Off-chain attestation created");
+
+            Ok(())
+        })
+    }
+
+    fn on_max_retries_reached(&self, task: &Task, reason: &str) {
+        if let Ok(run_id) = run::vec_to_run_id(task.args.clone()) {
+            save_error_and_cancel(
+                &run_id,
+                format!("Off-chain attestation could not be created: {}", reason),
+            );
+        }
+    }
+}
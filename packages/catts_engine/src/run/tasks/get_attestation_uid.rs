@@ -2,15 +2,75 @@ use std::pin::Pin;
 
 use crate::{
     chain_config::{self},
-    evm::rpc::eth_get_transaction_receipt,
-    logger,
-    run::{self},
-    tasks::{Task, TaskError, TaskExecutor},
+    eas::get_schema_uid,
+    eth_address::EthAddress,
+    evm::{
+        events::{decode_attested_events, AttestedEvent, EventDecodingError},
+        rpc::{eth_get_transaction_receipt, get_eth_address},
+        types::{Attester, Signer},
+    },
+    logger, recipe,
+    run::{self, Run, RunId},
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
 };
+use anyhow::{anyhow, bail, Result};
+use ethers_core::utils::hex;
 use futures::Future;
 
 use super::util::save_error_and_cancel;
 
+const GET_ATTESTATION_UID_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
+const GET_ATTESTATION_UID_MAX_RETRIES: u32 = 10;
+
+pub fn schedule_get_attestation_uid(run_id: &RunId, run_time: u64) {
+    add_task(
+        run_time,
+        Task {
+            task_type: TaskType::GetAttestationUid,
+            args: run_id.to_vec(),
+            max_retries: GET_ATTESTATION_UID_MAX_RETRIES,
+            execute_count: 0,
+            retry_interval: GET_ATTESTATION_UID_RETRY_INTERVAL,
+        },
+    );
+}
+
+/// Checks that the attestation was made by one of the addresses the run was attested from,
+/// for the run recipient, using the schema of the run's recipe.
+async fn verify_attested_event(event: &AttestedEvent, run: &Run) -> Result<()> {
+    let recipient = run.recipient();
+    if event.recipient.0 != recipient.as_byte_array() {
+        bail!("Attestation recipient does not match the run recipient");
+    }
+
+    let recipe = recipe::get_by_id(&run.recipe_id)?;
+
+    // Runs attested before attester addresses were recorded used the active key
+    let attesters = match run.attester_addresses {
+        Some(ref addresses) => addresses.clone(),
+        None => vec![
+            get_eth_address(&Signer::active(Attester::for_recipe(&recipe)))
+                .await
+                .map_err(|err| anyhow!("Couldn't derive attester address: {}", err.1))?,
+        ],
+    };
+    let is_attester = attesters.iter().any(|attester| {
+        EthAddress::new(attester)
+            .map(|attester| event.attester.0 == attester.as_byte_array())
+            .unwrap_or(false)
+    });
+    if !is_attester {
+        bail!("Attestation was not made by the recipe attester");
+    }
+
+    let schema_uid = get_schema_uid(&recipe.schema, &recipe.resolver, recipe.revokable)?;
+    if event.schema != schema_uid {
+        bail!("Attestation schema does not match the recipe schema");
+    }
+
+    Ok(())
+}
+
 pub struct GetAttestationUidExecutor {}
 
 impl TaskExecutor for GetAttestationUidExecutor {
@@ -23,7 +83,7 @@ impl TaskExecutor for GetAttestationUidExecutor {
                 .map_err(|_| save_error_and_cancel(&run_id, "Run not found".to_string()))?;
 
             if run.attestation_uid.is_some() {
-                save_error_and_cancel(&run_id, "Run already attested".to_string());
+                return Err(TaskError::Cancel("Run already attested".to_string()));
             }
 
             let chain_config = chain_config::get(run.chain_id).map_err(|_| {
@@ -40,28 +100,85 @@ impl TaskExecutor for GetAttestationUidExecutor {
                 }
             };
 
-            let receipt =
-                match eth_get_transaction_receipt(&attestation_transaction_hash, &chain_config)
-                    .await
-                {
-                    Ok(receipt) => receipt,
-                    Err(err) => {
-                        return Err(TaskError::Retry(err.to_string()));
-                    }
-                };
-
-            if receipt.logs.is_empty() {
-                return Err(TaskError::Retry(
-                    "No logs in transaction receipt".to_string(),
-                ));
+            // The transaction might have been resent with higher fees, any of the attempts
+            // can be the one that got mined
+            let transaction_hashes = match run.attestation_transaction_hashes {
+                Some(ref hashes) if !hashes.is_empty() => hashes.clone(),
+                _ => vec![attestation_transaction_hash],
+            };
+
+            let mut receipt = None;
+            for hash in transaction_hashes.iter().rev() {
+                if let Ok(r) = eth_get_transaction_receipt(hash, &chain_config).await {
+                    receipt = Some(r);
+                    break;
+                }
             }
 
+            let receipt = match receipt {
+                Some(receipt) => receipt,
+                None => {
+                    return Err(TaskError::Retry(
+                        "Transaction receipt not found".to_string(),
+                    ));
+                }
+            };
+
+            run.attestation_transaction_hash = Some(receipt.transactionHash.clone());
+
+            let attested_events = match decode_attested_events(&receipt, &chain_config) {
+                Ok(events) => events,
+                Err(EventDecodingError::TransactionReverted) => {
+                    // The run was not attested, without a transaction hash run_retry
+                    // attests it again
+                    run.attestation_transaction_hash = None;
+                    run.attestation_batch_index = None;
+                    run::update(run).unwrap();
+                    return Err(save_error_and_cancel(
+                        &run_id,
+                        "Attestation transaction reverted".to_string(),
+                    ));
+                }
+                Err(err) => {
+                    return Err(save_error_and_cancel(
+                        &run_id,
+                        format!("Invalid Attested event: {}", err),
+                    ));
+                }
+            };
+
+            // A batch transaction emits one Attested event per run, in batch order
+            let batch_index = run.attestation_batch_index.unwrap_or(0) as usize;
+            let attested_event = match attested_events.into_iter().nth(batch_index) {
+                Some(event) => event,
+                None => {
+                    return Err(save_error_and_cancel(
+                        &run_id,
+                        "No Attested event for run in transaction receipt".to_string(),
+                    ));
+                }
+            };
+
+            verify_attested_event(&attested_event, &run)
+                .await
+                .map_err(|err| save_error_and_cancel(&run_id, err.to_string()))?;
+
+            let uid = format!("0x{}", hex::encode(&attested_event.uid));
+
             logger::debug("Attestation uid found");
-            let uid = receipt.logs[0].data.clone();
             run.attestation_uid = Some(uid);
             run::update(run).unwrap();
 
             Ok(())
         })
     }
+
+    fn on_max_retries_reached(&self, task: &Task, reason: &str) {
+        if let Ok(run_id) = run::vec_to_run_id(task.args.clone()) {
+            save_error_and_cancel(
+                &run_id,
+                format!("Attestation UID could not be found: {}", reason),
+            );
+        }
+    }
 }
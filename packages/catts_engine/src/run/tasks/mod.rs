@@ -1,4 +1,7 @@
 pub mod create_attestation;
+pub mod create_attestation_batch;
+pub mod create_offchain_attestation;
 pub mod get_attestation_uid;
 pub mod register_payment;
+pub mod track_attestation_transaction;
 pub mod util;
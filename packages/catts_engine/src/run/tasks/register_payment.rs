@@ -1,23 +1,18 @@
-use crate::chain_config::{self, ChainConfig};
+use crate::chain_config::{self, ChainConfig, DEFAULT_POLL_INTERVAL};
 use crate::declarations::evm_rpc::LogEntry;
-use crate::evm::rpc::get_run_payment_logs;
+use crate::evm::{events::decode_run_payment_event, rpc::get_run_payment_logs};
 use crate::logger::{self};
 use crate::run::{self, Run, RunStatus};
 use crate::tasks::{add_task, Task, TaskError, TaskExecutor, TaskType};
-use crate::{
-    eth_address::{remove_address_padding, EthAddress},
-    ETH_PAYMENT_EVENT_SIGNATURE,
-};
 use anyhow::{anyhow, bail, Result};
-use ethers_core::abi::ParamType;
+use candid::Nat;
 use futures::Future;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 
-use super::util::save_error_and_cancel;
+use super::util::{has_confirmations, save_error_and_cancel, schedule_attestation};
 
-const CREATE_ATTESTATION_RETRY_INTERVAL: u64 = 15_000_000_000; // 15 seconds
-const CREATE_ATTESTATION_MAX_RETRIES: u32 = 3;
+const PROCESS_RUN_PAYMENT_MAX_RETRIES: u32 = 3;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProcessRunPaymentArgs {
@@ -26,6 +21,29 @@ pub struct ProcessRunPaymentArgs {
     pub run_id: [u8; 12],
 }
 
+/// Payments are retried once per block until the payment block has the number of
+/// confirmations the chain requires.
+pub fn schedule_register_payment(args: &ProcessRunPaymentArgs) {
+    let chain_config = run::get(&args.run_id)
+        .ok()
+        .and_then(|run| chain_config::get(run.chain_id).ok());
+    let (confirmations, retry_interval) = match chain_config {
+        Some(chain_config) => (chain_config.confirmations(), chain_config.poll_interval()),
+        None => (0, DEFAULT_POLL_INTERVAL),
+    };
+
+    add_task(
+        0, // Run ASAP
+        Task {
+            task_type: TaskType::ProcessRunPayment,
+            args: bincode::serialize(args).unwrap(),
+            max_retries: PROCESS_RUN_PAYMENT_MAX_RETRIES + confirmations,
+            execute_count: 0,
+            retry_interval,
+        },
+    );
+}
+
 pub struct RegisterPaymentExecutor {}
 
 impl TaskExecutor for RegisterPaymentExecutor {
@@ -39,6 +57,12 @@ impl TaskExecutor for RegisterPaymentExecutor {
             let chain_config = chain_config::get(run.chain_id)
                 .map_err(|e| save_error_and_cancel(&args.run_id, e.to_string()))?;
 
+            if !has_confirmations(&Nat::from(args.block_to_process), &chain_config).await {
+                return Err(TaskError::Retry(
+                    "Payment block does not have enough confirmations".to_string(),
+                ));
+            }
+
             let payment_logs = get_run_payment_logs(args.block_to_process, &chain_config)
                 .await
                 .map_err(|e| TaskError::Retry(e.to_string()))?;
@@ -57,24 +81,28 @@ impl TaskExecutor for RegisterPaymentExecutor {
 
                 logger::info("Payment log entry processed successfully");
 
-                run::update(run).unwrap();
-
-                add_task(
-                    0, // Run ASAP
-                    Task {
-                        task_type: TaskType::CreateAttestation,
-                        args: args.run_id.to_vec(),
-                        max_retries: CREATE_ATTESTATION_MAX_RETRIES,
-                        execute_count: 0,
-                        retry_interval: CREATE_ATTESTATION_RETRY_INTERVAL,
-                    },
-                );
+                let run = run::update(run).unwrap();
+
+                schedule_attestation(&run);
+
                 return Ok(());
             }
 
-            Err(TaskError::Cancel("No valid log entries found".to_string()))
+            Err(save_error_and_cancel(
+                &args.run_id,
+                "No valid log entries found".to_string(),
+            ))
         })
     }
+
+    fn on_max_retries_reached(&self, task: &Task, reason: &str) {
+        if let Ok(args) = bincode::deserialize::<ProcessRunPaymentArgs>(&task.args) {
+            save_error_and_cancel(
+                &args.run_id,
+                format!("Payment could not be verified: {}", reason),
+            );
+        }
+    }
 }
 
 fn process_log_entry(
@@ -82,108 +110,56 @@ fn process_log_entry(
     args: &ProcessRunPaymentArgs,
     chain_config: &ChainConfig,
 ) -> Result<Run> {
-    if entry.address.to_lowercase() != chain_config.payment_contract.to_lowercase() {
-        bail!("Payment log entry address does not match the expected address");
-    }
-
-    if entry.topics.len() < 2 {
-        bail!("Not enough topics in payment log entry");
-    }
+    let event = decode_run_payment_event(entry, chain_config)
+        .map_err(|e| anyhow!("Invalid payment log entry: {}", e))?;
 
-    let event_signature = &entry.topics[0];
-    if !event_signature
-        .to_lowercase()
-        .eq(ETH_PAYMENT_EVENT_SIGNATURE)
-    {
-        bail!("Payment log entry signature does not match the expected signature");
+    if event.payer.0 != args.from_address {
+        bail!("Payment log entry from address does not match the expected address");
     }
 
-    let event_from_address = &entry.topics[1];
-    let event_from_address = remove_address_padding(event_from_address);
-    let event_from_address = match EthAddress::new(&event_from_address) {
-        Ok(address) => address,
+    let event_run_id = match run::vec_to_run_id(event.run_id) {
+        Ok(run_id) => run_id,
         Err(_) => {
-            bail!("Payment log entry from address is not a valid address");
+            bail!("Payment run_id is not a valid run_id");
         }
     };
 
-    let from_address = EthAddress::from(args.from_address);
-
-    if event_from_address.as_byte_array() != from_address.as_byte_array() {
-        bail!("Payment log entry from address does not match the expected address");
+    if event_run_id != args.run_id {
+        bail!("Payment run_id does not match the expected run_id");
     }
 
-    // Hex string to raw bytes
-    if let Ok(data) = ethers_core::utils::hex::decode(entry.data.clone()) {
-        // Raw bytes to two parameters, payment amount and run_id
-        if let Ok(decoded_data) =
-            ethers_core::abi::decode(&[ParamType::Uint(256), ParamType::FixedBytes(12)], &data)
-        {
-            if decoded_data.len() < 2 {
-                bail!("Decoded data has less than 2 elements");
-            }
-
-            let event_run_id = match decoded_data[1].clone().into_fixed_bytes() {
-                Some(bytes) => bytes,
-                None => {
-                    bail!("Payment run_id is the wrong data type");
-                }
-            };
-
-            let event_run_id = match run::vec_to_run_id(event_run_id) {
-                Ok(run_id) => run_id,
-                Err(_) => {
-                    bail!("Payment run_id is not a valid run_id");
-                }
-            };
-
-            if event_run_id != args.run_id {
-                bail!("Payment run_id does not match the expected run_id");
-            }
-
-            let run = match run::get(&event_run_id) {
-                Ok(run) => run,
-                Err(_) => {
-                    bail!("Found payment for non-existent run");
-                }
-            };
-
-            if run.status() == RunStatus::PaymentPending {
-                bail!("No payment transaction is registered for this run");
-            }
-
-            if run.status() > RunStatus::PaymentRegistered {
-                bail!("Run payment is already verified");
-            }
+    let run = match run::get(&event_run_id) {
+        Ok(run) => run,
+        Err(_) => {
+            bail!("Found payment for non-existent run");
+        }
+    };
 
-            let event_amount = match decoded_data[0].clone().into_uint() {
-                Some(amount) => amount,
-                None => {
-                    bail!("Payment amount is the wrong data type");
-                }
-            };
+    if run.status() == RunStatus::PaymentPending {
+        bail!("No payment transaction is registered for this run");
+    }
 
-            let event_amount: u128 = match event_amount.try_into() {
-                Ok(amount) => amount,
-                Err(_) => {
-                    bail!("Payment amount is too large");
-                }
-            };
+    if run.status() > RunStatus::PaymentRegistered {
+        bail!("Run payment is already verified");
+    }
 
-            let user_fee = match run.user_fee.clone() {
-                Some(fee) => fee,
-                None => {
-                    bail!("Run does not have a user fee");
-                }
-            };
+    let event_amount: u128 = match event.amount.try_into() {
+        Ok(amount) => amount,
+        Err(_) => {
+            bail!("Payment amount is too large");
+        }
+    };
 
-            if event_amount >= user_fee {
-                return Ok(run);
-            } else {
-                bail!("Payment did not cover the cost of the run");
-            }
+    let user_fee = match run.user_fee.clone() {
+        Some(fee) => fee,
+        None => {
+            bail!("Run does not have a user fee");
         }
-        bail!("Failed to decode log data");
+    };
+
+    if event_amount >= user_fee {
+        Ok(run)
+    } else {
+        bail!("Payment did not cover the cost of the run");
     }
-    Err(anyhow!("Failed to decode log hex data"))
 }
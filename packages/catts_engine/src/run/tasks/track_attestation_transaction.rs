@@ -0,0 +1,365 @@
+use crate::{
+    chain_config::{self, ChainConfig, DEFAULT_POLL_INTERVAL},
+    declarations::evm_rpc::BlockTag,
+    eas::batch_max_fee_per_gas_cap,
+    evm::{
+        nonce::release_nonce,
+        rpc::{
+            eth_find_transaction_receipt, eth_get_transaction_count, eth_send_transaction,
+            get_eth_address, EvmRpcError, SentTransaction,
+        },
+        types::{Attester, SignRequest, Signer},
+        util::nat_to_u128,
+    },
+    logger, recipe,
+    run::{self, Run, RunId},
+    signing_key,
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+};
+use candid::Nat;
+use futures::Future;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+use super::{
+    get_attestation_uid::schedule_get_attestation_uid,
+    util::{has_confirmations, save_error_and_cancel},
+};
+
+const TRACK_ATTESTATION_TRANSACTION_TIMEOUT: u64 = 1_200_000_000_000; // 20 minutes
+const TRACK_ATTESTATION_TRANSACTION_STUCK_AFTER: u64 = 120_000_000_000; // 2 minutes
+
+// Nodes only accept a replacement transaction if it raises the fees by at least 10%
+const FEE_BUMP_NUMERATOR: u128 = 1125;
+const MIN_FEE_BUMP_NUMERATOR: u128 = 1100;
+const FEE_BUMP_DENOMINATOR: u128 = 1000;
+
+/// Checks that a replacement fee is at least 10% above the previous fee.
+fn is_accepted_fee_bump(previous_fee: u128, fee: u128) -> bool {
+    fee.saturating_mul(FEE_BUMP_DENOMINATOR) >= previous_fee.saturating_mul(MIN_FEE_BUMP_NUMERATOR)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TrackAttestationTransactionArgs {
+    pub chain_id: u32,
+    pub run_ids: Vec<RunId>,
+    pub to: String,
+    pub data: Vec<u8>,
+    pub nonce: u128,
+    pub gas: u128,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    /// The highest max_fee_per_gas all runs in the transaction have paid for
+    pub max_fee_per_gas_cap: u128,
+    /// Every hash sent for this nonce, the latest attempt last
+    pub transaction_hashes: Vec<String>,
+    pub last_sent: u64,
+    pub poll_count: u32,
+}
+
+impl TrackAttestationTransactionArgs {
+    fn sign_request(&self) -> SignRequest {
+        SignRequest {
+            chain_id: self.chain_id.into(),
+            to: self.to.clone(),
+            gas: Nat::from(self.gas),
+            max_fee_per_gas: Nat::from(self.max_fee_per_gas),
+            max_priority_fee_per_gas: Nat::from(self.max_priority_fee_per_gas),
+            value: 0_u8.into(),
+            nonce: Nat::from(self.nonce),
+            data: Some(self.data.clone().into()),
+        }
+    }
+}
+
+enum TransactionState {
+    Pending,
+    Dropped,
+    Replaced,
+}
+
+/// The runs of a transaction all have the same signer. The attester is resolved from the
+/// recipe of the first run and the key from the address the run was attested from, which
+/// is a previous key when the transaction was sent before a key rotation.
+async fn signer(args: &TrackAttestationTransactionArgs) -> Signer {
+    let run = args
+        .run_ids
+        .first()
+        .and_then(|run_id| run::get(run_id).ok());
+    let attester = run
+        .as_ref()
+        .and_then(|run| recipe::get_by_id(&run.recipe_id).ok())
+        .map(|recipe| Attester::for_recipe(&recipe))
+        .unwrap_or(Attester::Canister);
+
+    if let Some(address) = run.as_ref().and_then(|run| run.attester_address()) {
+        for key_id in signing_key::key_ids() {
+            let signer = Signer { key_id, attester };
+            if get_eth_address(&signer).await.ok().as_ref() == Some(address) {
+                return signer;
+            }
+        }
+    }
+
+    Signer::active(attester)
+}
+
+/// Transactions are polled once per block, as configured for the chain.
+fn poll_interval(chain_id: u32) -> u64 {
+    chain_config::get(chain_id)
+        .map(|chain_config| chain_config.poll_interval())
+        .unwrap_or(DEFAULT_POLL_INTERVAL)
+}
+
+fn schedule_tracking(args: &TrackAttestationTransactionArgs) {
+    let interval = poll_interval(args.chain_id);
+    add_task(
+        ic_cdk::api::time() + interval,
+        Task {
+            task_type: TaskType::TrackAttestationTransaction,
+            args: bincode::serialize(args).unwrap(),
+            max_retries: 1,
+            execute_count: 0,
+            retry_interval: interval,
+        },
+    );
+}
+
+/// Starts tracking a submitted attestation transaction. The transaction is resent with
+/// higher fees if it gets stuck or dropped, until it is confirmed or replaced.
+pub fn track_attestation_transaction(
+    chain_id: u32,
+    runs: &[Run],
+    sent_transaction: &SentTransaction,
+) {
+    let request = &sent_transaction.request;
+    let max_fee_per_gas = nat_to_u128(&request.max_fee_per_gas).unwrap_or_default();
+
+    // Fees can be bumped as long as no run pays more than the user fee it was quoted
+    let max_fee_per_gas_cap = batch_max_fee_per_gas_cap(runs, max_fee_per_gas);
+
+    schedule_tracking(&TrackAttestationTransactionArgs {
+        chain_id,
+        run_ids: runs.iter().map(|run| run.id).collect(),
+        to: request.to.clone(),
+        data: request
+            .data
+            .as_ref()
+            .map(|data| data.to_vec())
+            .unwrap_or_default(),
+        nonce: nat_to_u128(&request.nonce).unwrap_or_default(),
+        gas: nat_to_u128(&request.gas).unwrap_or_default(),
+        max_fee_per_gas,
+        max_priority_fee_per_gas: nat_to_u128(&request.max_priority_fee_per_gas)
+            .unwrap_or_default(),
+        max_fee_per_gas_cap,
+        transaction_hashes: vec![sent_transaction.hash.clone()],
+        last_sent: ic_cdk::api::time(),
+        poll_count: 0,
+    });
+}
+
+/// Returns the hash of the mined transaction, if any, and whether it has the number of
+/// confirmations the chain requires. An RPC error is returned as is, it doesn't mean that
+/// none of the transactions were mined.
+async fn find_mined_transaction(
+    args: &TrackAttestationTransactionArgs,
+    chain_config: &ChainConfig,
+) -> Result<Option<(String, bool)>, EvmRpcError> {
+    for hash in args.transaction_hashes.iter().rev() {
+        if let Some(receipt) = eth_find_transaction_receipt(hash, chain_config).await? {
+            let confirmed = has_confirmations(&receipt.blockNumber, chain_config).await;
+            return Ok(Some((hash.clone(), confirmed)));
+        }
+    }
+    Ok(None)
+}
+
+async fn get_transaction_state(
+    args: &TrackAttestationTransactionArgs,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+) -> Result<TransactionState, EvmRpcError> {
+    let nonce = Nat::from(args.nonce);
+
+    // The nonce is used but none of our transactions were mined
+    let confirmed_count = eth_get_transaction_count(BlockTag::Latest, chain_config, signer).await?;
+    if confirmed_count > nonce {
+        return Ok(TransactionState::Replaced);
+    }
+
+    // The nonce is not used by any transaction in the mempool
+    let pending_count = eth_get_transaction_count(BlockTag::Pending, chain_config, signer).await?;
+    if pending_count <= nonce {
+        return Ok(TransactionState::Dropped);
+    }
+
+    Ok(TransactionState::Pending)
+}
+
+/// Resends the transaction with the same nonce. Fees are bumped, capped at what the runs
+/// have paid for.
+async fn resend_transaction(
+    args: &mut TrackAttestationTransactionArgs,
+    chain_config: &ChainConfig,
+    signer: &Signer,
+    require_fee_bump: bool,
+) {
+    let max_fee_per_gas = (args.max_fee_per_gas * FEE_BUMP_NUMERATOR / FEE_BUMP_DENOMINATOR)
+        .min(args.max_fee_per_gas_cap);
+    let max_priority_fee_per_gas = (args.max_priority_fee_per_gas * FEE_BUMP_NUMERATOR
+        / FEE_BUMP_DENOMINATOR)
+        .min(max_fee_per_gas);
+
+    // A replacement capped below the minimum bump would be rejected as underpriced
+    if require_fee_bump
+        && !(is_accepted_fee_bump(args.max_fee_per_gas, max_fee_per_gas)
+            && is_accepted_fee_bump(args.max_priority_fee_per_gas, max_priority_fee_per_gas))
+    {
+        logger::warn("Attestation transaction is stuck, fees can't be bumped further");
+        return;
+    }
+
+    let mut replacement = args.clone();
+    replacement.max_fee_per_gas = max_fee_per_gas;
+    replacement.max_priority_fee_per_gas = max_priority_fee_per_gas;
+
+    match eth_send_transaction(replacement.sign_request(), chain_config, signer).await {
+        Ok(hash) => {
+            logger::info(format!("Attestation transaction resent: {}", hash).as_str());
+            args.max_fee_per_gas = max_fee_per_gas;
+            args.max_priority_fee_per_gas = max_priority_fee_per_gas;
+            args.transaction_hashes.push(hash.clone());
+            args.last_sent = ic_cdk::api::time();
+            for run_id in args.run_ids.iter() {
+                if let Ok(mut run) = run::get(run_id) {
+                    run.attestation_transaction_hash = Some(hash.clone());
+                    run.attestation_transaction_hashes
+                        .get_or_insert_with(Vec::new)
+                        .push(hash.clone());
+                    run::update(run).unwrap();
+                }
+            }
+        }
+        Err(err) => {
+            logger::warn(format!("Failed to resend attestation transaction: {}", err).as_str());
+        }
+    }
+}
+
+fn confirm_transaction(args: &TrackAttestationTransactionArgs, signer: &Signer, hash: &str) {
+    release_nonce(args.chain_id, signer, args.nonce as u64);
+    for run_id in args.run_ids.iter() {
+        if let Ok(mut run) = run::get(run_id) {
+            run.attestation_transaction_hash = Some(hash.to_string());
+            run::update(run).unwrap();
+            schedule_get_attestation_uid(run_id, 0);
+        }
+    }
+}
+
+/// None of the transactions were mined, the runs go back to waiting for attestation so
+/// that they can be retried.
+fn replace_transaction(args: &TrackAttestationTransactionArgs, signer: &Signer) {
+    release_nonce(args.chain_id, signer, args.nonce as u64);
+    for run_id in args.run_ids.iter() {
+        if let Ok(mut run) = run::get(run_id) {
+            run.attestation_transaction_hash = None;
+            run.attestation_batch_index = None;
+            run.error =
+                Some("Attestation transaction was replaced by another transaction".to_string());
+            run::update(run).unwrap();
+        }
+    }
+}
+
+pub struct TrackAttestationTransactionExecutor {}
+
+impl TaskExecutor for TrackAttestationTransactionExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let mut args: TrackAttestationTransactionArgs = bincode::deserialize(&task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            let chain_config = chain_config::get(args.chain_id)
+                .map_err(|err| TaskError::Cancel(err.to_string()))?;
+
+            let signer = signer(&args).await;
+
+            args.poll_count += 1;
+
+            match find_mined_transaction(&args, &chain_config).await {
+                Ok(Some((hash, true))) => {
+                    confirm_transaction(&args, &signer, &hash);
+                    return Ok(());
+                }
+                Ok(Some((_, false))) => {
+                    // Mined, waiting for confirmations
+                    schedule_tracking(&args);
+                    return Ok(());
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    logger::debug(
+                        format!("Could not get attestation transaction receipt: {}", err).as_str(),
+                    );
+                    schedule_tracking(&args);
+                    return Ok(());
+                }
+            }
+
+            let polled_for = args.poll_count as u64 * chain_config.poll_interval();
+            if polled_for >= TRACK_ATTESTATION_TRANSACTION_TIMEOUT {
+                let error = "Attestation transaction was not confirmed in time".to_string();
+                for run_id in args.run_ids.iter() {
+                    save_error_and_cancel(run_id, error.clone());
+                }
+                return Err(TaskError::Cancel(error));
+            }
+
+            match get_transaction_state(&args, &chain_config, &signer).await {
+                Ok(TransactionState::Replaced) => {
+                    // The transaction might have been mined since the receipts were checked
+                    match find_mined_transaction(&args, &chain_config).await {
+                        Ok(Some((hash, true))) => {
+                            confirm_transaction(&args, &signer, &hash);
+                            return Ok(());
+                        }
+                        Ok(Some((_, false))) => {
+                            schedule_tracking(&args);
+                            return Ok(());
+                        }
+                        Ok(None) => {}
+                        // Only replaced once all receipts are known to be missing
+                        Err(_) => {
+                            schedule_tracking(&args);
+                            return Ok(());
+                        }
+                    }
+                    replace_transaction(&args, &signer);
+                    return Err(TaskError::Cancel(
+                        "Attestation transaction was replaced".to_string(),
+                    ));
+                }
+                Ok(TransactionState::Dropped) => {
+                    resend_transaction(&mut args, &chain_config, &signer, false).await;
+                }
+                Ok(TransactionState::Pending) => {
+                    let pending_for = ic_cdk::api::time().saturating_sub(args.last_sent);
+                    if pending_for > TRACK_ATTESTATION_TRANSACTION_STUCK_AFTER {
+                        resend_transaction(&mut args, &chain_config, &signer, true).await;
+                    }
+                }
+                Err(err) => {
+                    logger::debug(
+                        format!("Could not get attestation transaction state: {}", err).as_str(),
+                    );
+                }
+            }
+
+            schedule_tracking(&args);
+
+            Ok(())
+        })
+    }
+}
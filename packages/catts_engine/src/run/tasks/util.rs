@@ -1,4 +1,16 @@
-use crate::{run, tasks::TaskError};
+use crate::{
+    chain_config::ChainConfig,
+    declarations::evm_rpc::BlockTag,
+    evm::rpc::eth_get_block_by_number,
+    run::{self, Run},
+    tasks::TaskError,
+};
+use candid::Nat;
+
+use super::{
+    create_attestation_batch::schedule_attestation_batch,
+    create_offchain_attestation::schedule_create_offchain_attestation,
+};
 
 pub fn save_error_and_cancel(run_id: &[u8; 12], error: String) -> TaskError {
     let mut run = run::get(run_id).unwrap();
@@ -6,3 +18,29 @@ pub fn save_error_and_cancel(run_id: &[u8; 12], error: String) -> TaskError {
     run::update(run).unwrap();
     TaskError::Cancel(error)
 }
+
+/// Schedules the attestation of a run with a verified payment. On-chain runs are queued
+/// for the next attestation batch of their chain.
+pub fn schedule_attestation(run: &Run) {
+    if run.is_offchain() {
+        schedule_create_offchain_attestation(&run.id);
+        return;
+    }
+
+    run::queue_for_attestation(run);
+    schedule_attestation_batch(run.chain_id);
+}
+
+/// Checks that at least the number of confirmations the chain requires have been built on
+/// top of the block. Without required confirmations, no RPC call is made.
+pub async fn has_confirmations(block_number: &Nat, chain_config: &ChainConfig) -> bool {
+    let confirmations = chain_config.confirmations();
+    if confirmations == 0 {
+        return true;
+    }
+
+    match eth_get_block_by_number(BlockTag::Latest, chain_config).await {
+        Ok(latest_block) => latest_block.number >= block_number.clone() + confirmations,
+        Err(_) => false,
+    }
+}
@@ -23,6 +23,8 @@ pub enum RunError {
     CantBeCancelled(String),
     #[error("Already paid")]
     AlreadyPaid,
+    #[error("Can't be retried: {0}")]
+    CantBeRetried(String),
 }
 
 pub type RunId = [u8; 12];
@@ -32,6 +34,8 @@ pub struct Run {
     pub id: RunId,
     pub recipe_id: RecipeId,
     pub creator: String,
+    /// Address that receives the attestation, defaults to `creator`
+    pub recipient: Option<String>,
     pub created: u32,
     pub chain_id: u32,
     pub gas: Option<Nat>,
@@ -42,11 +46,41 @@ pub struct Run {
     pub payment_block_number: Option<Nat>,
     pub payment_log_index: Option<Nat>,
     pub attestation_transaction_hash: Option<String>,
+    /// Every attestation transaction hash, recorded when the transaction is signed and
+    /// before it is sent. A send that failed might still have reached the chain.
+    pub attestation_transaction_hashes: Option<Vec<String>>,
+    pub attestation_batch_index: Option<u32>,
+    /// Nonce of the latest attestation transaction, recorded along with its hash
+    pub attestation_nonce: Option<u64>,
+    /// Every address the run was attested from, the latest last. The address changes when
+    /// the signing key is rotated while the attestation is in flight.
+    pub attester_addresses: Option<Vec<String>>,
     pub attestation_uid: Option<String>,
+    pub mode: Option<RunMode>,
+    /// The signed off-chain attestation package, JSON encoded
+    pub offchain_attestation: Option<String>,
     pub is_cancelled: bool,
     pub error: Option<String>,
 }
 
+/// An alternative attestation recipient and a proof that the run creator controls it. The
+/// proof is a signature of the message returned by `run::recipient_proof_message`, either
+/// an ECDSA signature by the recipient or, for smart contract wallets, a signature the
+/// recipient accepts through EIP-1271 `isValidSignature`.
+#[derive(Deserialize, Debug, CandidType, Clone)]
+pub struct RunRecipientInput {
+    pub address: String,
+    pub signature: String,
+}
+
+/// On-chain runs are attested using a transaction to the EAS contract. Off-chain runs are
+/// only signed by the canister and don't cost any gas.
+#[derive(Serialize, Deserialize, Debug, CandidType, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    Onchain,
+    Offchain,
+}
+
 #[derive(PartialEq, PartialOrd)]
 pub enum RunStatus {
     PaymentPending = 0,
@@ -78,6 +112,9 @@ impl ToJsonValue for Run {
             bytes_to_hex_string_value(&self.recipe_id),
         );
         obj.insert("creator".to_string(), json!(self.creator));
+        if let Some(ref recipient) = self.recipient {
+            obj.insert("recipient".to_string(), json!(recipient));
+        }
         obj.insert("created".to_string(), json!(self.created));
         obj.insert("chain_id".to_string(), json!(self.chain_id));
         if let Some(ref gas) = self.gas {
@@ -122,12 +159,39 @@ impl ToJsonValue for Run {
                 Value::String(attestation_transaction_hash.to_string()),
             );
         }
+        if let Some(ref attestation_transaction_hashes) = self.attestation_transaction_hashes {
+            obj.insert(
+                "attestation_transaction_hashes".to_string(),
+                json!(attestation_transaction_hashes),
+            );
+        }
+        if let Some(attestation_batch_index) = self.attestation_batch_index {
+            obj.insert(
+                "attestation_batch_index".to_string(),
+                json!(attestation_batch_index),
+            );
+        }
+        if let Some(attestation_nonce) = self.attestation_nonce {
+            obj.insert("attestation_nonce".to_string(), json!(attestation_nonce));
+        }
+        if let Some(ref attester_addresses) = self.attester_addresses {
+            obj.insert("attester_addresses".to_string(), json!(attester_addresses));
+        }
         if let Some(ref attestation_uid) = self.attestation_uid {
             obj.insert(
                 "attestation_uid".to_string(),
                 Value::String(attestation_uid.to_string()),
             );
         }
+        if let Some(mode) = self.mode {
+            obj.insert("mode".to_string(), json!(mode));
+        }
+        if let Some(ref offchain_attestation) = self.offchain_attestation {
+            obj.insert(
+                "offchain_attestation".to_string(),
+                Value::String(offchain_attestation.to_string()),
+            );
+        }
         obj.insert("is_cancelled".to_string(), json!(self.is_cancelled));
         if let Some(ref error) = self.error {
             obj.insert("error".to_string(), Value::String(error.to_string()));
@@ -148,6 +212,7 @@ impl Run {
         recipe_id: &[u8; 12],
         chain_id: u32,
         creator: &EthAddress,
+        mode: RunMode,
     ) -> Result<Self, RunError> {
         // A run must be created with a valid recipe
         recipe::get_by_id(recipe_id).map_err(|_| RunError::RecipeNotFound)?;
@@ -159,6 +224,7 @@ impl Run {
             id,
             recipe_id: *recipe_id,
             creator: creator.to_string(),
+            recipient: None,
             created,
             chain_id,
             gas: None,
@@ -169,7 +235,13 @@ impl Run {
             payment_block_number: None,
             payment_log_index: None,
             attestation_transaction_hash: None,
+            attestation_transaction_hashes: None,
+            attestation_batch_index: None,
+            attestation_nonce: None,
+            attester_addresses: None,
             attestation_uid: None,
+            mode: Some(mode),
+            offchain_attestation: None,
             is_cancelled: false,
             error: None,
         };
@@ -177,6 +249,35 @@ impl Run {
         Ok(run)
     }
 
+    pub fn recipient(&self) -> EthAddress {
+        EthAddress::from(self.recipient.as_deref().unwrap_or(&self.creator))
+    }
+
+    /// The address the latest attestation of the run was made from.
+    pub fn attester_address(&self) -> Option<&String> {
+        self.attester_addresses
+            .as_ref()
+            .and_then(|addresses| addresses.last())
+    }
+
+    pub fn add_attester_address(&mut self, address: String) {
+        if self.attester_address() != Some(&address) {
+            self.attester_addresses
+                .get_or_insert_with(Vec::new)
+                .push(address);
+        }
+    }
+
+    pub fn is_offchain(&self) -> bool {
+        self.mode == Some(RunMode::Offchain)
+    }
+
+    /// Cancelled and attested runs are final, other runs can still be paid, processed or
+    /// retried.
+    pub fn is_final(&self) -> bool {
+        self.is_cancelled || self.status() == RunStatus::AttestationUidConfirmed
+    }
+
     pub fn status(&self) -> RunStatus {
         if self.attestation_uid.is_some() {
             return RunStatus::AttestationUidConfirmed;
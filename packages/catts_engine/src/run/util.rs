@@ -1,14 +1,29 @@
-use super::types::Run;
+use super::{
+    state::save_private_data,
+    types::{Run, RunId},
+};
 use crate::{
-    chain_config::{self},
+    chain_config::{self, TransactionType},
     declarations::evm_rpc::BlockTag,
     eas::{self},
     eth_address::EthAddress,
-    evm::rpc::{eth_fee_history, eth_get_block_by_number},
+    evm::rpc::{
+        eth_call, eth_fee_history, eth_find_transaction_receipt, eth_gas_price,
+        eth_get_address_transaction_count, eth_get_block_by_number, eth_get_code,
+    },
+    private_data::{create_private_data, get_commitment_attestation_data},
     recipe::Recipe,
+    user,
 };
 use anyhow::{anyhow, bail, Result};
 use candid::Nat;
+use ethers_core::{
+    abi::{parse_abi, Token},
+    utils::{hash_message, hex},
+};
+
+/// Returned by `isValidSignature` when a contract accepts the signature, see EIP-1271.
+const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
 
 pub fn vec_to_run_id(bytes: Vec<u8>) -> Result<[u8; 12], String> {
     if bytes.len() == 12 {
@@ -35,6 +50,14 @@ fn median_index(length: usize) -> usize {
 pub async fn estimate_transaction_fees(run: &Run) -> Result<FeeEstimates> {
     let chain_config = chain_config::get(run.chain_id)?;
 
+    // Without EIP-1559 the whole gas price is paid, there is no separate tip
+    if chain_config.transaction_type() != TransactionType::Eip1559 {
+        return Ok(FeeEstimates {
+            base_fee_per_gas: eth_gas_price(&chain_config).await?,
+            max_priority_fee_per_gas: Nat::from(0_u8),
+        });
+    }
+
     let latest_block = eth_get_block_by_number(BlockTag::Latest, &chain_config).await?;
 
     let block_count = 9_u8;
@@ -82,17 +105,14 @@ pub async fn estimate_transaction_fees(run: &Run) -> Result<FeeEstimates> {
 }
 
 pub async fn estimate_gas_usage(recipe: &Recipe, run: &Run) -> Result<Nat> {
-    let recipient = EthAddress::from(run.creator.as_str());
-    let mut query_response = Vec::new();
+    let recipient = run.recipient();
 
-    for i in 0..recipe.queries.len() {
-        let response = eas::run_query(&recipient, &recipe.queries[i]).await?;
-        query_response.push(response);
-    }
+    let mut attestation_data = get_attestation_data(recipe, run).await?;
 
-    let aggregated_response = format!("[{}]", query_response.join(","));
-
-    let attestation_data = eas::process_query_result(&recipe.processor, &aggregated_response);
+    if recipe.uses_private_data() {
+        let private_data = create_private_data(&attestation_data)?;
+        attestation_data = get_commitment_attestation_data(&private_data);
+    }
 
     let chain_config = chain_config::get(run.chain_id)?;
 
@@ -107,17 +127,208 @@ pub async fn estimate_gas_usage(recipe: &Recipe, run: &Run) -> Result<Nat> {
     let gas_usage = u64::from_str_radix(gas_usage, 16)
         .map_err(|err| anyhow!(format!("Error decoding gas usage: {}", err)))?;
 
-    // Add 20% to the gas usage to account for any discrepancies
-    let gas_usage = gas_usage + (gas_usage / 5);
+    // Add a margin to the gas usage to account for any discrepancies
+    let gas_usage = gas_usage + (gas_usage * chain_config.fee_margin_percent() as u64 / 100);
 
     Ok(Nat::from(gas_usage))
 }
 
-pub fn get_min_user_fee_for_chain(chain_id: u32) -> Result<Nat> {
-    let fee: u64 = match chain_id {
-        11155111 => 500000000000000, // Sepolia, 0.0005 ETH
-        10 => 50000000000000,        // Optimism, 0.00005 ETH
-        _ => bail!("Chain not supported"),
+async fn run_queries(recipe: &Recipe, address: &EthAddress) -> Result<String> {
+    let mut query_response = Vec::new();
+    for query in recipe.queries.iter() {
+        let response = eas::run_query(address, query)
+            .await
+            .map_err(|err| anyhow!("Error running EAS query: {}", err))?;
+        query_response.push(response);
+    }
+    Ok(format!("[{}]", query_response.join(",")))
+}
+
+/// Runs the recipe queries for the run recipient and processes the results into attestation
+/// data.
+///
+/// Recipes that aggregate linked addresses run the queries for every address of the run
+/// creator instead. The processor then receives the results grouped per address, primary
+/// address first: `[{"address": "0x..", "results": [..]}, ..]`.
+pub async fn get_attestation_data(recipe: &Recipe, run: &Run) -> Result<String> {
+    if recipe.queries.is_empty() {
+        bail!("Recipe contains no queries");
+    }
+
+    let recipient = run.recipient();
+
+    if !recipe.aggregates_linked_addresses() {
+        let aggregated_response = run_queries(recipe, &recipient).await?;
+        return Ok(eas::process_query_result(
+            &recipe.processor,
+            &aggregated_response,
+        ));
+    }
+
+    let creator = EthAddress::from(run.creator.as_str());
+    let mut addresses = match user::get_by_eth_address(&creator) {
+        Ok(user) => user.addresses(),
+        Err(_) => vec![run.creator.clone()],
     };
-    Ok(Nat::from(fee))
+    addresses.retain(|address| address != recipient.as_str());
+    addresses.insert(0, recipient.to_string());
+
+    let mut address_responses = Vec::new();
+    for address in addresses.iter() {
+        let results = run_queries(recipe, &EthAddress::from(address.as_str())).await?;
+        address_responses.push(format!(
+            "{{\"address\":\"{}\",\"results\":{}}}",
+            address, results
+        ));
+    }
+    let aggregated_response = format!("[{}]", address_responses.join(","));
+
+    Ok(eas::process_query_result(
+        &recipe.processor,
+        &aggregated_response,
+    ))
+}
+
+/// For private data recipes, stores the Merkle tree of the attestation data and returns
+/// attestation data that only contains the root. Other recipes attest the data as is.
+pub fn commit_private_data(
+    run_id: &RunId,
+    recipe: &Recipe,
+    attestation_data: String,
+) -> Result<String> {
+    if !recipe.uses_private_data() {
+        return Ok(attestation_data);
+    }
+
+    let private_data = create_private_data(&attestation_data)?;
+    let commitment = get_commitment_attestation_data(&private_data);
+    save_private_data(run_id, private_data);
+
+    Ok(commitment)
+}
+
+/// The message the recipient of a run signs to prove that the run creator controls it.
+pub fn recipient_proof_message(
+    creator: &EthAddress,
+    recipient: &EthAddress,
+    chain_id: u32,
+) -> String {
+    format!(
+        "Receive catts attestations for {} at {} on chain {}",
+        creator, recipient, chain_id
+    )
+}
+
+/// Verifies the recipient proof of a run. 65 byte ECDSA signatures are recovered in the
+/// canister. `isValidSignature` is only called on the recipient, see EIP-1271, when the
+/// signature is in another format or when the recipient is a contract.
+pub async fn verify_recipient_proof(
+    creator: &EthAddress,
+    recipient: &EthAddress,
+    signature: &str,
+    chain_id: u32,
+) -> Result<()> {
+    let message = recipient_proof_message(creator, recipient, chain_id);
+    let signature_bytes =
+        hex::decode(signature).map_err(|_| anyhow!("Signature is not hex encoded"))?;
+
+    let chain_config = chain_config::get(chain_id)?;
+    if signature_bytes.len() == 65 {
+        if user::verify_address_signature(recipient, &message, signature).is_ok() {
+            return Ok(());
+        }
+        let code = eth_get_code(recipient.as_str(), &chain_config)
+            .await
+            .map_err(|err| anyhow!("Couldn't check the recipient: {}", err))?;
+        if code.trim_start_matches("0x").is_empty() {
+            bail!("Signature was not created by the recipient");
+        }
+    }
+
+    let eip1271_contract = parse_abi(&[
+        "function isValidSignature(bytes32 hash, bytes signature) view returns (bytes4)",
+    ])?;
+
+    let tokens = eth_call(
+        recipient.as_str(),
+        &eip1271_contract,
+        "isValidSignature",
+        &[
+            Token::FixedBytes(hash_message(message).as_bytes().to_vec()),
+            Token::Bytes(signature_bytes),
+        ],
+        &chain_config,
+    )
+    .await
+    .map_err(|_| anyhow!("Signature is not valid for the recipient"))?;
+
+    match tokens.first() {
+        Some(Token::FixedBytes(value)) if value.as_slice() == EIP1271_MAGIC_VALUE => Ok(()),
+        _ => bail!("Signature is not valid for the recipient"),
+    }
+}
+
+/// Where the attestation transactions of a run that failed before its attestation was
+/// confirmed stand.
+pub enum SentAttestation {
+    /// One of the transactions was mined, the run was attested by it
+    Mined(String),
+    /// The transactions reverted or their nonce was used by another transaction, the run
+    /// can be attested again
+    NotAttested,
+    /// None of the transactions were mined and their nonce is unused, a transaction might
+    /// still be pending
+    Pending,
+}
+
+/// Checks the attestation transactions recorded on the run, a transaction that failed to
+/// send might still have reached the chain.
+pub async fn check_sent_attestation(run: &Run) -> Result<SentAttestation> {
+    let hashes = run
+        .attestation_transaction_hashes
+        .clone()
+        .unwrap_or_default();
+    if hashes.is_empty() {
+        return Ok(SentAttestation::NotAttested);
+    }
+
+    let chain_config = chain_config::get(run.chain_id)?;
+
+    // Runs attested before nonces were recorded can only be checked by their receipts. The
+    // nonce is checked first, once it is used no transaction can be mined after its receipt
+    // was checked.
+    if let (Some(nonce), Some(address)) = (run.attestation_nonce, run.attester_address()) {
+        let transaction_count =
+            eth_get_address_transaction_count(address.clone(), BlockTag::Latest, &chain_config)
+                .await
+                .map_err(|err| anyhow!("Couldn't get the attester nonce: {}", err))?;
+        if transaction_count <= nonce {
+            return Ok(SentAttestation::Pending);
+        }
+    }
+
+    for hash in hashes.iter().rev() {
+        let receipt = eth_find_transaction_receipt(hash, &chain_config)
+            .await
+            .map_err(|err| anyhow!("Couldn't get the attestation receipt: {}", err))?;
+        if receipt.is_some_and(|receipt| receipt.status == 1_u8) {
+            return Ok(SentAttestation::Mined(hash.clone()));
+        }
+    }
+
+    Ok(SentAttestation::NotAttested)
+}
+
+pub fn get_min_user_fee_for_chain(chain_id: u32) -> Result<Nat> {
+    chain_config::get(chain_id)?
+        .min_user_fee
+        .ok_or(anyhow!("Chain has no minimum fee"))
+}
+
+/// Off-chain attestations don't need a transaction, the fee only covers the cost of running
+/// the recipe and signing.
+pub fn get_offchain_user_fee_for_chain(chain_id: u32) -> Result<Nat> {
+    chain_config::get(chain_id)?
+        .min_offchain_user_fee
+        .ok_or(anyhow!("Chain has no minimum off-chain fee"))
 }
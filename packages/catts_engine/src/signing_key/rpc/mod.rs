@@ -0,0 +1,4 @@
+pub mod signing_key_activate;
+pub mod signing_key_cancel;
+pub mod signing_key_rotate;
+pub mod signing_key_status;
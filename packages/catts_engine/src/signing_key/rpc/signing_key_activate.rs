@@ -0,0 +1,22 @@
+use crate::{
+    http_error::HttpError,
+    role::controller_guard,
+    signing_key::{self, SigningKeyError, SigningKeys},
+};
+use ic_cdk::update;
+
+/// Switches signing to the pending key. Fails as long as the new canister address or a new
+/// dedicated attester address of a published recipe is not funded on every enabled chain.
+/// Only controllers can rotate keys.
+#[update]
+async fn signing_key_activate() -> Result<SigningKeys, HttpError> {
+    controller_guard()?;
+
+    signing_key::activate().await.map_err(|err| match err {
+        SigningKeyError::NoPendingKey => HttpError::not_found(err),
+        SigningKeyError::NotFunded(_) | SigningKeyError::AttestersNotFunded(_) => {
+            HttpError::conflict(err)
+        }
+        _ => HttpError::internal_server_error(err),
+    })
+}
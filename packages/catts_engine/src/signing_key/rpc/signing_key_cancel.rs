@@ -0,0 +1,14 @@
+use crate::{
+    http_error::HttpError,
+    role::controller_guard,
+    signing_key::{self, SigningKeys},
+};
+use ic_cdk::update;
+
+/// Cancels a key rotation in progress. Only controllers can rotate keys.
+#[update]
+fn signing_key_cancel() -> Result<SigningKeys, HttpError> {
+    controller_guard()?;
+
+    signing_key::cancel().map_err(HttpError::not_found)
+}
@@ -0,0 +1,20 @@
+use crate::{
+    http_error::HttpError,
+    role::controller_guard,
+    signing_key::{self, SigningKeyError, SigningKeys},
+};
+use ic_cdk::update;
+
+/// Registers a new threshold ECDSA key, for instance `key_1`. Signing switches over once
+/// the key is activated. Only controllers can rotate keys.
+#[update]
+async fn signing_key_rotate(key_id: String) -> Result<SigningKeys, HttpError> {
+    controller_guard()?;
+
+    signing_key::rotate(key_id).await.map_err(|err| match err {
+        SigningKeyError::RotationInProgress | SigningKeyError::AlreadyActive => {
+            HttpError::conflict(err)
+        }
+        _ => HttpError::bad_request(err),
+    })
+}
@@ -0,0 +1,17 @@
+use crate::{
+    http_error::HttpError,
+    role::{require_role, Role},
+    signing_key::{self, SigningKeys},
+};
+use ic_cdk::update;
+
+/// Returns the active signing key, the pending key of a rotation in progress and the
+/// keys used before, along with their canister attester addresses.
+#[update]
+async fn signing_key_status() -> Result<SigningKeys, HttpError> {
+    require_role(Role::Auditor)?;
+
+    signing_key::init_active_key()
+        .await
+        .map_err(HttpError::internal_server_error)
+}
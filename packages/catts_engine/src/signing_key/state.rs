@@ -0,0 +1,86 @@
+use crate::{time::time, CANISTER_SETTINGS, SIGNING_KEYS};
+
+use super::{SigningKey, SigningKeyError, SigningKeys};
+
+pub fn get() -> SigningKeys {
+    SIGNING_KEYS.with_borrow(|keys| keys.get().clone())
+}
+
+fn set(keys: SigningKeys) {
+    SIGNING_KEYS.with_borrow_mut(|cell| {
+        cell.set(keys).expect("Failed to save signing keys");
+    });
+}
+
+/// The key all new transactions and signatures are made with.
+pub fn active_key_id() -> String {
+    SIGNING_KEYS
+        .with_borrow(|keys| keys.get().active.as_ref().map(|key| key.key_id.clone()))
+        .unwrap_or_else(|| CANISTER_SETTINGS.with_borrow(|settings| settings.ecdsa_key_id.clone()))
+}
+
+/// The active key followed by the previous keys, the most recent first.
+pub fn key_ids() -> Vec<String> {
+    let previous: Vec<String> = get().previous.into_iter().map(|key| key.key_id).collect();
+    std::iter::once(active_key_id()).chain(previous).collect()
+}
+
+pub fn pending_key_id() -> Option<String> {
+    get().pending.map(|key| key.key_id)
+}
+
+pub fn set_settings_key_id(key_id: &str) {
+    let mut keys = get();
+    keys.settings_key_id = Some(key_id.to_string());
+    set(keys);
+}
+
+/// Records the key from the canister settings as the active key, unless a key already is.
+pub fn set_active_if_unset(key: SigningKey) {
+    let mut keys = get();
+    if keys.active.is_none() {
+        keys.active = Some(SigningKey {
+            activated: Some(key.registered),
+            ..key
+        });
+        set(keys);
+    }
+}
+
+pub fn set_pending(key: SigningKey) -> Result<SigningKeys, SigningKeyError> {
+    let mut keys = get();
+    if keys.pending.is_some() {
+        return Err(SigningKeyError::RotationInProgress);
+    }
+    keys.pending = Some(key);
+    set(keys.clone());
+    Ok(keys)
+}
+
+pub fn clear_pending() -> Result<SigningKey, SigningKeyError> {
+    let mut keys = get();
+    let pending = keys.pending.take().ok_or(SigningKeyError::NoPendingKey)?;
+    set(keys);
+    Ok(pending)
+}
+
+/// Makes the pending key the active key. The previously active key is retired, its address
+/// is kept in the history.
+pub fn activate_pending(key_id: &str) -> Result<SigningKeys, SigningKeyError> {
+    let mut keys = get();
+    let mut pending = match keys.pending.take() {
+        Some(pending) if pending.key_id == key_id => pending,
+        _ => return Err(SigningKeyError::NoPendingKey),
+    };
+
+    let now = time();
+    pending.activated = Some(now);
+    if let Some(mut active) = keys.active.take() {
+        active.retired = Some(now);
+        keys.previous.insert(0, active);
+    }
+    keys.active = Some(pending);
+
+    set(keys.clone());
+    Ok(keys)
+}
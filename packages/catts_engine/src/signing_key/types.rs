@@ -0,0 +1,58 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+use std::borrow::Cow;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SigningKeyError {
+    #[error("A key rotation is already in progress")]
+    RotationInProgress,
+    #[error("No key rotation in progress")]
+    NoPendingKey,
+    #[error("The key is already active")]
+    AlreadyActive,
+    #[error("Key unavailable: {0}")]
+    KeyUnavailable(String),
+    #[error("The new address is not funded on chains {0:?}")]
+    NotFunded(Vec<u32>),
+    #[error("The new recipe attester addresses are not funded: {0:?}")]
+    AttestersNotFunded(Vec<String>),
+}
+
+/// A threshold ECDSA key the canister signs with, along with the address of the canister
+/// attester under that key.
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone)]
+pub struct SigningKey {
+    pub key_id: String,
+    pub address: String,
+    pub registered: u32,
+    pub activated: Option<u32>,
+    pub retired: Option<u32>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SigningKeys {
+    /// Unset until the first rotation, the key from the canister settings is used until then
+    pub active: Option<SigningKey>,
+    /// The key signing switches to once its address is funded
+    pub pending: Option<SigningKey>,
+    /// Keys that were active before, the most recent first. Their addresses remain valid
+    /// attesters for the attestations they made.
+    pub previous: Vec<SigningKey>,
+    /// The key from the canister settings, kept until the active key is recorded so that
+    /// the settings key can't change before the key history exists
+    pub settings_key_id: Option<String>,
+}
+
+impl Storable for SigningKeys {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,218 @@
+use candid::Nat;
+use std::time::Duration;
+
+use crate::{
+    chain_config,
+    evm::{
+        nonce::reset_nonces,
+        rpc::{eth_get_balance, get_eth_address},
+        types::{Attester, Signer},
+    },
+    logger,
+    recipe::{self, RecipePublishState},
+    time::time,
+};
+
+use super::{
+    activate_pending, active_key_id, clear_pending, get, set_active_if_unset, set_pending,
+    set_settings_key_id, SigningKey, SigningKeyError, SigningKeys,
+};
+
+/// Derives the canister attester address under a key, which also checks that the key is
+/// available to the canister.
+async fn derive_signing_key(key_id: &str) -> Result<SigningKey, SigningKeyError> {
+    let signer = Signer {
+        key_id: key_id.to_string(),
+        attester: Attester::Canister,
+    };
+    let address = get_eth_address(&signer)
+        .await
+        .map_err(|err| SigningKeyError::KeyUnavailable(err.1))?;
+
+    Ok(SigningKey {
+        key_id: key_id.to_string(),
+        address,
+        registered: time(),
+        activated: None,
+        retired: None,
+    })
+}
+
+/// Records the key from the canister settings as the active key, so that its address is
+/// part of the key history.
+pub async fn init_active_key() -> Result<SigningKeys, SigningKeyError> {
+    if get().active.is_none() {
+        let key = derive_signing_key(&active_key_id()).await?;
+        set_active_if_unset(key);
+    }
+    Ok(get())
+}
+
+/// Records the active key right after install and upgrade. Key rotations and the status
+/// endpoint record it as well, in case the key was unavailable at that time.
+pub fn schedule_init_active_key() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || {
+        ic_cdk::spawn(async {
+            if let Err(err) = init_active_key().await {
+                logger::warn(format!("Couldn't record the active signing key: {}", err).as_str());
+            }
+        });
+    });
+}
+
+/// Derives the address of a recipe's dedicated attester under a key.
+pub async fn derive_recipe_attester(
+    key_id: &str,
+    recipe_id: recipe::RecipeId,
+) -> Result<String, SigningKeyError> {
+    let signer = Signer {
+        key_id: key_id.to_string(),
+        attester: Attester::Recipe(recipe_id),
+    };
+    get_eth_address(&signer)
+        .await
+        .map_err(|err| SigningKeyError::KeyUnavailable(err.1))
+}
+
+/// Registers a pending key. Signing continues with the active key until the pending key
+/// is activated, the new addresses are exposed so that they can be funded beforehand.
+pub async fn rotate(key_id: String) -> Result<SigningKeys, SigningKeyError> {
+    if get().pending.is_some() {
+        return Err(SigningKeyError::RotationInProgress);
+    }
+    if key_id == active_key_id() {
+        return Err(SigningKeyError::AlreadyActive);
+    }
+
+    init_active_key().await?;
+    let key = derive_signing_key(&key_id).await?;
+
+    let mut recipe_attesters = Vec::new();
+    for recipe in recipe::list()
+        .into_iter()
+        .filter(|recipe| recipe.has_dedicated_attester())
+    {
+        let address = derive_recipe_attester(&key_id, recipe.id).await?;
+        recipe_attesters.push((recipe.id, address));
+    }
+
+    let keys = set_pending(key)?;
+    for (recipe_id, address) in recipe_attesters {
+        recipe::set_pending_attester(&recipe_id, Some(address));
+    }
+
+    logger::info(format!("Signing key rotation to {} started", key_id).as_str());
+
+    Ok(keys)
+}
+
+/// Switches signing to the pending key once its canister address, and the dedicated
+/// attester addresses of published recipes, are funded on every enabled chain.
+/// Transactions sent with the previous key keep being tracked and resent with it until
+/// they are mined.
+pub async fn activate() -> Result<SigningKeys, SigningKeyError> {
+    let pending = get().pending.ok_or(SigningKeyError::NoPendingKey)?;
+    let chain_configs: Vec<_> = chain_config::list()
+        .into_iter()
+        .filter(|chain_config| !chain_config.is_disabled())
+        .collect();
+
+    let mut unfunded = Vec::new();
+    for chain_config in chain_configs.iter() {
+        match eth_get_balance(&pending.address, chain_config).await {
+            Ok(balance) if balance > Nat::from(0_u8) => {}
+            _ => unfunded.push(chain_config.chain_id),
+        }
+    }
+    if !unfunded.is_empty() {
+        return Err(SigningKeyError::NotFunded(unfunded));
+    }
+
+    // Recipes created while the rotation was in progress might lack a pending attester
+    for recipe in recipe::list()
+        .into_iter()
+        .filter(|recipe| recipe.has_dedicated_attester() && recipe.pending_attester.is_none())
+    {
+        let address = derive_recipe_attester(&pending.key_id, recipe.id).await?;
+        recipe::set_pending_attester(&recipe.id, Some(address));
+    }
+
+    let mut unfunded_attesters = Vec::new();
+    for recipe in recipe::list().into_iter().filter(|recipe| {
+        recipe.has_dedicated_attester() && recipe.publish_state == RecipePublishState::Published
+    }) {
+        let Some(attester) = recipe.pending_attester else {
+            continue;
+        };
+        for chain_config in chain_configs.iter() {
+            match eth_get_balance(&attester, chain_config).await {
+                Ok(balance) if balance > Nat::from(0_u8) => {}
+                _ => unfunded_attesters.push(format!(
+                    "{} ({}) on chain {}",
+                    attester, recipe.name, chain_config.chain_id
+                )),
+            }
+        }
+    }
+    if !unfunded_attesters.is_empty() {
+        return Err(SigningKeyError::AttestersNotFunded(unfunded_attesters));
+    }
+
+    let keys = activate_pending(&pending.key_id)?;
+    recipe::activate_pending_attesters();
+    reset_nonces();
+
+    logger::info(format!("Signing key {} activated", pending.key_id).as_str());
+
+    Ok(keys)
+}
+
+/// Abandons a rotation, the pending key and addresses are forgotten.
+pub fn cancel() -> Result<SigningKeys, SigningKeyError> {
+    let pending = clear_pending()?;
+    for recipe in recipe::list()
+        .into_iter()
+        .filter(|recipe| recipe.pending_attester.is_some())
+    {
+        recipe::set_pending_attester(&recipe.id, None);
+    }
+
+    logger::info(format!("Signing key rotation to {} cancelled", pending.key_id).as_str());
+
+    Ok(get())
+}
+
+/// The key in the canister settings only applies until the first rotation. Once the
+/// active key is recorded, a different key passed on upgrade is ignored, rotations go
+/// through `signing_key_rotate`. Before that the key history is missing and a different
+/// key would silently replace the addresses in use, the upgrade is refused instead.
+pub fn check_canister_settings(settings_key_id: &str) {
+    let keys = get();
+    match keys.active {
+        Some(active) => {
+            if active.key_id != settings_key_id {
+                logger::warn(
+                    format!(
+                        "Ignoring ecdsa_key_id {} from the canister settings, the active signing key is {}",
+                        settings_key_id, active.key_id
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        None => {
+            if let Some(recorded_key_id) = keys.settings_key_id {
+                if recorded_key_id != settings_key_id {
+                    ic_cdk::trap(
+                        format!(
+                            "ecdsa_key_id can't change from {} to {} before the active signing key is recorded, call signing_key_status first",
+                            recorded_key_id, settings_key_id
+                        )
+                        .as_str(),
+                    );
+                }
+            }
+            set_settings_key_id(settings_key_id);
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use crate::{
+    change_log::tasks::create_snapshot::CreateChangeLogSnapshotExecutor,
     logger,
     run::tasks::{
         create_attestation::CreateAttestationExecutor,
+        create_attestation_batch::CreateAttestationBatchExecutor,
+        create_offchain_attestation::CreateOffchainAttestationExecutor,
         get_attestation_uid::GetAttestationUidExecutor, register_payment::RegisterPaymentExecutor,
+        track_attestation_transaction::TrackAttestationTransactionExecutor,
     },
+    webhook::tasks::deliver_webhook::DeliverWebhookExecutor,
     TASKS,
 };
 use candid::{CandidType, Decode, Encode};
@@ -23,11 +28,16 @@ pub enum TaskError {
     Retry(String),
 }
 
-#[derive(CandidType, Deserialize, Debug, Clone)]
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq)]
 pub enum TaskType {
     ProcessRunPayment,
     CreateAttestation,
     GetAttestationUid,
+    CreateAttestationBatch,
+    TrackAttestationTransaction,
+    DeliverWebhook,
+    CreateOffchainAttestation,
+    CreateChangeLogSnapshot,
 }
 
 #[derive(CandidType, Deserialize, Debug, Clone)]
@@ -53,6 +63,9 @@ impl Storable for Task {
 
 pub trait TaskExecutor {
     fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>>;
+
+    /// Called when a task has failed and there are no retries left.
+    fn on_max_retries_reached(&self, _task: &Task, _reason: &str) {}
 }
 
 fn get_executor_for_task(task: &Task) -> Box<dyn TaskExecutor> {
@@ -60,6 +73,11 @@ fn get_executor_for_task(task: &Task) -> Box<dyn TaskExecutor> {
         TaskType::ProcessRunPayment => Box::new(RegisterPaymentExecutor {}),
         TaskType::CreateAttestation => Box::new(CreateAttestationExecutor {}),
         TaskType::GetAttestationUid => Box::new(GetAttestationUidExecutor {}),
+        TaskType::CreateAttestationBatch => Box::new(CreateAttestationBatchExecutor {}),
+        TaskType::TrackAttestationTransaction => Box::new(TrackAttestationTransactionExecutor {}),
+        TaskType::DeliverWebhook => Box::new(DeliverWebhookExecutor {}),
+        TaskType::CreateOffchainAttestation => Box::new(CreateOffchainAttestationExecutor {}),
+        TaskType::CreateChangeLogSnapshot => Box::new(CreateChangeLogSnapshotExecutor {}),
     }
 }
 
@@ -69,11 +87,47 @@ pub fn add_task(run_time: Timestamp, task: Task) {
         execute_task(task);
         return;
     }
+    insert_task(run_time, task);
+}
+
+/// Tasks are keyed by their run time. Tasks scheduled for the same time are spread out
+/// by a nanosecond each, so they don't overwrite each other.
+fn insert_task(run_time: Timestamp, task: Task) {
     TASKS.with_borrow_mut(|tasks| {
+        let mut run_time = run_time;
+        while tasks.contains_key(&run_time) {
+            run_time += 1;
+        }
         tasks.insert(run_time, task);
     });
 }
 
+/// A task waiting in the queue, as returned to operators inspecting the queue.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct ScheduledTask {
+    pub run_time: Timestamp,
+    pub task: Task,
+}
+
+pub fn list_tasks() -> Vec<ScheduledTask> {
+    TASKS.with_borrow(|tasks| {
+        tasks
+            .iter()
+            .map(|(run_time, task)| ScheduledTask { run_time, task })
+            .collect()
+    })
+}
+
+/// Returns true if a task of the given type and with the given arguments is waiting to be
+/// executed.
+pub fn is_task_scheduled(task_type: &TaskType, args: &[u8]) -> bool {
+    TASKS.with_borrow(|tasks| {
+        tasks
+            .iter()
+            .any(|(_, task)| task.task_type == *task_type && task.args == args)
+    })
+}
+
 pub fn execute_tasks() {
     let mut tasks_to_process: Vec<Task> = Vec::new();
     let current_time = ic_cdk::api::time();
@@ -111,14 +165,13 @@ fn execute_task(mut task: Task) {
                 TaskError::Retry(reason) => {
                     if task.execute_count + 1 < task.max_retries {
                         task.execute_count += 1;
-                        TASKS.with_borrow_mut(|tasks| {
-                            tasks.insert(ic_cdk::api::time() + task.retry_interval, task);
-                        });
+                        insert_task(ic_cdk::api::time() + task.retry_interval, task);
                         logger::debug(format!("Task failed, retrying: {}", reason).as_str());
                     } else {
                         logger::debug(
                             format!("Task failed, max retries reached: {}", reason).as_str(),
                         );
+                        get_executor_for_task(&task).on_max_retries_reached(&task, &reason);
                     }
                 }
                 TaskError::Cancel(reason) => {
@@ -0,0 +1,27 @@
+/// Returns true for https urls and for plain http urls pointing at the local machine, so
+/// that outcalls can be tested against local devnets and servers. The host is compared
+/// exactly, `http://localhost.example.com` is rejected.
+pub fn is_https_or_localhost(url: &str) -> bool {
+    if url.starts_with("https://") {
+        return true;
+    }
+    let Some(rest) = url.strip_prefix("http://") else {
+        return false;
+    };
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    // With credentials the host follows the @, http://localhost:x@example.com goes to
+    // example.com
+    if authority.contains('@') {
+        return false;
+    }
+
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => host,
+        Some(_) => return false,
+        None => authority,
+    };
+
+    host == "127.0.0.1" || host == "localhost"
+}
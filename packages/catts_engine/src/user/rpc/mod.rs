@@ -1,4 +1,10 @@
 pub mod user_create;
+pub mod user_delete;
+pub mod user_export;
 pub mod user_get_by_eth_address;
 pub mod user_get_by_principal;
 pub mod user_get_current;
+pub mod user_link_address;
+pub mod user_set_primary_address;
+pub mod user_unlink_address;
+pub mod user_update_profile;
@@ -0,0 +1,14 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    user::{self, auth_guard, User},
+};
+
+/// Deletes the caller's account. Recipes and runs created by the caller are kept.
+#[update]
+fn user_delete() -> Result<User, HttpError> {
+    auth_guard()?;
+
+    user::delete(ic_cdk::api::caller()).map_err(HttpError::not_found)
+}
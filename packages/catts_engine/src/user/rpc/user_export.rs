@@ -0,0 +1,16 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    user::{self, auth_guard},
+};
+
+/// Returns everything the engine holds about the caller as one JSON document. An update
+/// call, collecting the runs and recipes of the user scans all of them.
+#[update]
+fn user_export() -> Result<String, HttpError> {
+    auth_guard()?;
+
+    let user = user::get_by_principal(ic_cdk::api::caller()).map_err(HttpError::not_found)?;
+    Ok(user::export_user_data(&user).to_string())
+}
@@ -0,0 +1,30 @@
+use ic_cdk::update;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    user::{self, User, UserError},
+};
+
+/// Links `address` to the caller. `signature` is an EIP-191 signature by `address` of the
+/// message "Link address {address} to the catts account of {eth_address} with nonce
+/// {link_nonce}", using the lowercase addresses and the current `link_nonce` of the user.
+#[update]
+fn user_link_address(address: String, signature: String) -> Result<User, HttpError> {
+    user::auth_guard()?;
+
+    let address = EthAddress::new(&address).map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let caller = ic_cdk::api::caller();
+    let user = user::get_by_principal(caller).map_err(HttpError::not_found)?;
+
+    let message = user::link_address_message(&user, &address);
+    user::verify_address_signature(&address, &message, &signature)
+        .map_err(HttpError::unauthorized)?;
+
+    user::link_address(caller, &address).map_err(|err| match err {
+        UserError::AddressInUse => HttpError::conflict(err),
+        UserError::TooManyLinkedAddresses => HttpError::bad_request(err),
+        _ => HttpError::internal_server_error(err),
+    })
+}
@@ -0,0 +1,21 @@
+use ic_cdk::update;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    user::{self, User, UserError},
+};
+
+/// Sets the address that receives the attestations of the caller's runs. Must be the sign
+/// in address or a linked address.
+#[update]
+fn user_set_primary_address(address: String) -> Result<User, HttpError> {
+    user::auth_guard()?;
+
+    let address = EthAddress::new(&address).map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    user::set_primary_address(ic_cdk::api::caller(), &address).map_err(|err| match err {
+        UserError::AddressNotLinked => HttpError::not_found(err),
+        _ => HttpError::internal_server_error(err),
+    })
+}
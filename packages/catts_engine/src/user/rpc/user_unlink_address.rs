@@ -0,0 +1,19 @@
+use ic_cdk::update;
+
+use crate::{
+    eth_address::EthAddress,
+    http_error::HttpError,
+    user::{self, User, UserError},
+};
+
+#[update]
+fn user_unlink_address(address: String) -> Result<User, HttpError> {
+    user::auth_guard()?;
+
+    let address = EthAddress::new(&address).map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    user::unlink_address(ic_cdk::api::caller(), &address).map_err(|err| match err {
+        UserError::AddressNotLinked => HttpError::not_found(err),
+        _ => HttpError::internal_server_error(err),
+    })
+}
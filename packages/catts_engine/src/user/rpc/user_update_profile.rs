@@ -0,0 +1,19 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    user::{self, auth_guard, User, UserProfileInput},
+};
+
+#[update]
+fn user_update_profile(profile: UserProfileInput) -> Result<User, HttpError> {
+    auth_guard()?;
+
+    let caller = ic_cdk::api::caller();
+    let user = user::get_by_principal(caller).map_err(HttpError::not_found)?;
+    let user = user
+        .with_profile(&profile)
+        .map_err(HttpError::bad_request)?;
+
+    user::update(caller, user).map_err(HttpError::internal_server_error)
+}
@@ -1,15 +1,30 @@
-use super::{principal_to_blob, User, UserError};
-use crate::{eth_address::EthAddress, USERS, USER_ETH_ADDRESS_INDEX};
+use super::{principal_to_blob, User, UserError, MAX_LINKED_ADDRESSES};
+use crate::{
+    change_log::{self, ChangeLogTypeName},
+    eth_address::EthAddress,
+    json::ToJsonValue,
+    logger,
+    time::time,
+    webhook, USERS, USER_ETH_ADDRESS_INDEX,
+};
 use candid::Principal;
 use ic_stable_structures::storable::Blob;
+use serde_json::Value;
 
+/// Creates a user for the sign in address. Addresses linked to another user can't be used
+/// to create a new one.
 pub fn create(principal: Principal, eth_address: &EthAddress) -> Result<User, UserError> {
     let principal_bytes = principal_to_blob(principal)?;
 
-    USERS.with_borrow_mut(|users| {
+    let user = USERS.with_borrow_mut(|users| {
         if users.contains_key(&principal_bytes) {
             return Err(UserError::AlreadyExists);
         }
+        if USER_ETH_ADDRESS_INDEX
+            .with_borrow(|index| index.contains_key(&eth_address.as_byte_array()))
+        {
+            return Err(UserError::AddressInUse);
+        }
         let user = User::new(eth_address.as_str());
         users.insert(principal_bytes, user.clone());
 
@@ -18,7 +33,166 @@ pub fn create(principal: Principal, eth_address: &EthAddress) -> Result<User, Us
         });
 
         Ok(user)
-    })
+    })?;
+
+    change_log::create(ChangeLogTypeName::User, &eth_address.as_byte_array(), &user).unwrap();
+
+    Ok(user)
+}
+
+pub fn update(principal: Principal, user: User) -> Result<User, UserError> {
+    let principal_bytes = principal_to_blob(principal)?;
+    let old_user = get_by_principal_bytes(&principal_bytes)?;
+
+    USERS.with_borrow_mut(|users| {
+        users.insert(principal_bytes, user.clone());
+    });
+
+    let eth_address = EthAddress::from(user.eth_address.as_str());
+    change_log::update(
+        ChangeLogTypeName::User,
+        &eth_address.as_byte_array(),
+        &old_user,
+        &user,
+    )
+    .unwrap();
+
+    Ok(user)
+}
+
+/// Links an additional address to the user and burns the link nonce. Addresses can only be
+/// linked to one user, the caller is responsible for verifying ownership of the address.
+pub fn link_address(principal: Principal, address: &EthAddress) -> Result<User, UserError> {
+    let principal_bytes = principal_to_blob(principal)?;
+    let mut user = get_by_principal_bytes(&principal_bytes)?;
+
+    if USER_ETH_ADDRESS_INDEX.with_borrow(|index| index.contains_key(&address.as_byte_array())) {
+        return Err(UserError::AddressInUse);
+    }
+
+    let mut linked_addresses = user.linked_addresses.unwrap_or_default();
+    if linked_addresses.len() >= MAX_LINKED_ADDRESSES {
+        return Err(UserError::TooManyLinkedAddresses);
+    }
+    linked_addresses.push(address.to_string());
+    user.linked_addresses = Some(linked_addresses);
+    user.link_nonce = Some(user.link_nonce() + 1);
+
+    USER_ETH_ADDRESS_INDEX.with_borrow_mut(|index| {
+        index.insert(address.as_byte_array(), principal_bytes);
+    });
+
+    update(principal, user)
+}
+
+/// Unlinks an address from the user. The sign in address cannot be unlinked. If the
+/// address was the primary address, the sign in address becomes primary again.
+pub fn unlink_address(principal: Principal, address: &EthAddress) -> Result<User, UserError> {
+    let mut user = get_by_principal(principal)?;
+
+    let mut linked_addresses = user.linked_addresses.unwrap_or_default();
+    let position = linked_addresses
+        .iter()
+        .position(|linked_address| linked_address == address.as_str())
+        .ok_or(UserError::AddressNotLinked)?;
+    linked_addresses.remove(position);
+    user.linked_addresses = if linked_addresses.is_empty() {
+        None
+    } else {
+        Some(linked_addresses)
+    };
+
+    if user.primary_address.as_deref() == Some(address.as_str()) {
+        user.primary_address = None;
+    }
+
+    USER_ETH_ADDRESS_INDEX.with_borrow_mut(|index| {
+        index.remove(&address.as_byte_array());
+    });
+
+    update(principal, user)
+}
+
+pub fn set_primary_address(principal: Principal, address: &EthAddress) -> Result<User, UserError> {
+    let mut user = get_by_principal(principal)?;
+
+    if !user.owns_address(address.as_str()) {
+        return Err(UserError::AddressNotLinked);
+    }
+
+    user.primary_address = if user.eth_address == address.as_str() {
+        None
+    } else {
+        Some(address.to_string())
+    };
+
+    update(principal, user)
+}
+
+/// Profile fields and addresses, removed from the change log history when a user is deleted.
+const PROFILE_FIELDS: [&str; 6] = [
+    "display_name",
+    "avatar_url",
+    "bio",
+    "links",
+    "linked_addresses",
+    "primary_address",
+];
+
+fn scrub_profile(data: &mut Value) {
+    if let Some(data) = data.as_object_mut() {
+        for field in PROFILE_FIELDS {
+            data.remove(field);
+        }
+    }
+}
+
+/// Deletes the user and the address mappings. The profile and the linked addresses are
+/// scrubbed from the change log entries and snapshots of the user before the delete is
+/// recorded, so that the history no longer exposes them. Recipes and runs of the user are kept, webhooks owned by the user
+/// are removed.
+pub fn delete(principal: Principal) -> Result<User, UserError> {
+    let principal_bytes = principal_to_blob(principal)?;
+    let user = get_by_principal_bytes(&principal_bytes)?;
+
+    USERS.with_borrow_mut(|users| {
+        users.remove(&principal_bytes);
+    });
+    USER_ETH_ADDRESS_INDEX.with_borrow_mut(|index| {
+        for address in user.addresses() {
+            index.remove(&EthAddress::from(address.as_str()).as_byte_array());
+        }
+    });
+
+    for webhook in webhook::list_by_owner(&user.eth_address) {
+        webhook::delete(&webhook.id).ok();
+    }
+
+    let eth_address = EthAddress::from(user.eth_address.as_str());
+    let scrubbed = change_log::scrub_entity(
+        &ChangeLogTypeName::User,
+        eth_address.as_str(),
+        scrub_profile,
+    );
+    if let Err(err) = scrubbed {
+        logger::error(
+            format!(
+                "Couldn't scrub the change log of user {}: {}",
+                eth_address, err
+            )
+            .as_str(),
+        );
+    }
+    let mut old_data = user.to_json_value();
+    scrub_profile(&mut old_data);
+    change_log::delete(
+        ChangeLogTypeName::User,
+        &eth_address.as_byte_array(),
+        old_data,
+    )
+    .unwrap();
+
+    Ok(user)
 }
 
 pub fn get_by_principal_bytes(principal_bytes: &Blob<29>) -> Result<User, UserError> {
@@ -36,3 +210,30 @@ pub fn get_by_eth_address(eth_address: &EthAddress) -> Result<User, UserError> {
         .with_borrow(|index| index.get(&eth_address_bytes).ok_or(UserError::NotFound))?;
     get_by_principal_bytes(&principal_bytes)
 }
+
+/// Users created before profiles were introduced have no creation time and no change log
+/// entries. They get the migration time as creation time and a `Create` entry, so that
+/// change log consumers see all users.
+pub fn migrate_users() {
+    let unmigrated: Vec<(Blob<29>, User)> = USERS.with_borrow(|users| {
+        users
+            .iter()
+            .filter(|(_, user)| user.created.is_none())
+            .collect()
+    });
+    if unmigrated.is_empty() {
+        return;
+    }
+
+    let created = time();
+    for (principal_bytes, mut user) in unmigrated.iter().cloned() {
+        user.created = Some(created);
+        USERS.with_borrow_mut(|users| {
+            users.insert(principal_bytes, user.clone());
+        });
+        let eth_address = EthAddress::from(user.eth_address.as_str());
+        change_log::create(ChangeLogTypeName::User, &eth_address.as_byte_array(), &user).unwrap();
+    }
+
+    logger::info(format!("Migrated {} users", unmigrated.len()).as_str());
+}
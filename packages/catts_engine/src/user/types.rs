@@ -1,7 +1,12 @@
+use crate::{json::ToJsonValue, time::time};
 use candid::{CandidType, Decode, Deserialize, Encode};
 use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::borrow::Cow;
 use thiserror::Error;
+use validator::{Validate, ValidationError};
+use validator_derive::Validate;
 
 #[derive(Error, Debug)]
 pub enum UserError {
@@ -11,18 +16,124 @@ pub enum UserError {
     InvalidPrincipal,
     #[error("User not found")]
     NotFound,
+    #[error("Address is already linked to a user")]
+    AddressInUse,
+    #[error("Address is not linked to the user")]
+    AddressNotLinked,
+    #[error("Too many linked addresses, the limit is {MAX_LINKED_ADDRESSES}")]
+    TooManyLinkedAddresses,
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
 }
 
-#[derive(CandidType, Deserialize, Debug, Clone)]
+pub const MAX_LINKED_ADDRESSES: usize = 10;
+
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone, Validate)]
 pub struct User {
     pub eth_address: String,
+
+    /// Not set for users created before profiles were introduced, until they are migrated
+    pub created: Option<u32>,
+
+    #[validate(length(min = 3, max = 50))]
+    pub display_name: Option<String>,
+
+    // validate_profile_url: https only
+    #[validate(length(max = 512), custom(function = "validate_profile_url"))]
+    pub avatar_url: Option<String>,
+
+    #[validate(length(max = 500))]
+    pub bio: Option<String>,
+
+    // validate_links: at most 5 https urls
+    #[validate(custom(function = "validate_links"))]
+    pub links: Option<Vec<String>>,
+
+    /// Additional addresses the user has proven ownership of
+    pub linked_addresses: Option<Vec<String>>,
+
+    /// Address that receives attestations, defaults to `eth_address`
+    pub primary_address: Option<String>,
+
+    /// Nonce of the next link address message, burned when an address is linked so that a
+    /// signature can't be used again
+    pub link_nonce: Option<u64>,
+}
+
+fn validate_profile_url(url: &str) -> Result<(), ValidationError> {
+    if url.starts_with("https://") {
+        return Ok(());
+    }
+    Err(ValidationError::new("Profile urls must use https"))
+}
+
+fn validate_links(links: &[String]) -> Result<(), ValidationError> {
+    if links.len() > 5 {
+        return Err(ValidationError::new("Too many links, the limit is 5"));
+    }
+    for link in links {
+        if link.len() > 255 {
+            return Err(ValidationError::new("Link is too long"));
+        }
+        validate_profile_url(link)?;
+    }
+    Ok(())
 }
 
 impl User {
     pub fn new(address: &str) -> Self {
         Self {
             eth_address: address.to_string(),
+            created: Some(time()),
+            display_name: None,
+            avatar_url: None,
+            bio: None,
+            links: None,
+            linked_addresses: None,
+            primary_address: None,
+            link_nonce: None,
+        }
+    }
+
+    /// Replaces the profile fields of the user.
+    pub fn with_profile(
+        &self,
+        profile: &UserProfileInput,
+    ) -> Result<Self, validator::ValidationErrors> {
+        let user = Self {
+            display_name: profile.display_name.clone(),
+            avatar_url: profile.avatar_url.clone(),
+            bio: profile.bio.clone(),
+            links: profile.links.clone(),
+            ..self.clone()
+        };
+
+        user.validate()?;
+
+        Ok(user)
+    }
+
+    /// The sign in address followed by the linked addresses.
+    pub fn addresses(&self) -> Vec<String> {
+        let mut addresses = vec![self.eth_address.clone()];
+        if let Some(ref linked_addresses) = self.linked_addresses {
+            addresses.extend(linked_addresses.iter().cloned());
         }
+        addresses
+    }
+
+    pub fn primary_address(&self) -> String {
+        self.primary_address
+            .clone()
+            .unwrap_or_else(|| self.eth_address.clone())
+    }
+
+    pub fn link_nonce(&self) -> u64 {
+        self.link_nonce.unwrap_or(0)
+    }
+
+    pub fn owns_address(&self, address: &str) -> bool {
+        self.addresses().iter().any(|a| a == address)
     }
 }
 
@@ -37,3 +148,48 @@ impl Storable for User {
 
     const BOUND: Bound = Bound::Unbounded;
 }
+
+impl ToJsonValue for User {
+    fn to_json_value(&self) -> Value {
+        let mut obj = serde_json::Map::new();
+
+        obj.insert("eth_address".to_string(), json!(self.eth_address));
+        if let Some(created) = self.created {
+            obj.insert("created".to_string(), json!(created));
+        }
+        if let Some(ref display_name) = self.display_name {
+            obj.insert("display_name".to_string(), json!(display_name));
+        }
+        if let Some(ref avatar_url) = self.avatar_url {
+            obj.insert("avatar_url".to_string(), json!(avatar_url));
+        }
+        if let Some(ref bio) = self.bio {
+            obj.insert("bio".to_string(), json!(bio));
+        }
+        if let Some(ref links) = self.links {
+            obj.insert("links".to_string(), json!(links));
+        }
+        if let Some(ref linked_addresses) = self.linked_addresses {
+            obj.insert("linked_addresses".to_string(), json!(linked_addresses));
+        }
+        if let Some(ref primary_address) = self.primary_address {
+            obj.insert("primary_address".to_string(), json!(primary_address));
+        }
+
+        Value::Object(obj)
+    }
+}
+
+impl ToJsonValue for &User {
+    fn to_json_value(&self) -> Value {
+        (*self).to_json_value()
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct UserProfileInput {
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub links: Option<Vec<String>>,
+}
@@ -1,9 +1,12 @@
 use crate::eth_address::EthAddress;
 use crate::http_error::HttpError;
-use crate::user;
+use crate::json::{bytes_to_hex_string_value, ToJsonValue};
+use crate::{recipe, run, user, webhook};
 use anyhow::{bail, Result};
 use candid::Principal;
+use ethers_core::types::{Address, Signature};
 use ic_stable_structures::storable::Blob;
+use serde_json::{json, Value};
 
 use super::UserError;
 
@@ -27,3 +30,76 @@ pub fn principal_to_blob(principal: Principal) -> Result<Blob<29>, UserError> {
         .try_into()
         .map_err(|_| UserError::InvalidPrincipal)
 }
+
+/// The message a user signs with an address to link it to their account. The nonce of the
+/// user is burned once an address is linked.
+pub fn link_address_message(user: &user::User, address: &EthAddress) -> String {
+    format!(
+        "Link address {} to the catts account of {} with nonce {}",
+        address,
+        user.eth_address,
+        user.link_nonce()
+    )
+}
+
+/// Verifies an EIP-191 `personal_sign` signature of `message` by `address`.
+pub fn verify_address_signature(
+    address: &EthAddress,
+    message: &str,
+    signature: &str,
+) -> Result<(), UserError> {
+    let signature = signature
+        .parse::<Signature>()
+        .map_err(|err| UserError::InvalidSignature(err.to_string()))?;
+    let signer = signature
+        .recover(message)
+        .map_err(|err| UserError::InvalidSignature(err.to_string()))?;
+
+    if signer != Address::from(address.as_byte_array()) {
+        return Err(UserError::InvalidSignature(
+            "Signature was not created by the address".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Everything the engine holds about a user: the user record, the runs and recipes created
+/// by the user, the payments made for the runs and the webhooks owned by the user.
+pub fn export_user_data(user: &user::User) -> Value {
+    let eth_address = EthAddress::from(user.eth_address.as_str());
+
+    let runs = run::list_by_creator(&eth_address);
+    let payments: Vec<Value> = runs
+        .iter()
+        .filter_map(|run| {
+            let transaction_hash = run.payment_transaction_hash.as_ref()?;
+            Some(json!({
+                "run_id": bytes_to_hex_string_value(&run.id),
+                "chain_id": run.chain_id,
+                "transaction_hash": transaction_hash,
+                "user_fee": run.user_fee.as_ref().map(|fee| fee.to_string()),
+                "verified": run.payment_log_index.is_some(),
+            }))
+        })
+        .collect();
+
+    let recipes: Vec<Value> = recipe::list()
+        .iter()
+        .filter(|recipe| recipe.creator == user.eth_address)
+        .map(|recipe| recipe.to_json_value())
+        .collect();
+
+    let webhooks: Vec<Value> = webhook::list_by_owner(&user.eth_address)
+        .iter()
+        .map(|webhook| serde_json::to_value(webhook).unwrap_or(Value::Null))
+        .collect();
+
+    json!({
+        "user": user.to_json_value(),
+        "runs": runs.iter().map(|run| run.to_json_value()).collect::<Vec<_>>(),
+        "recipes": recipes,
+        "payments": payments,
+        "webhooks": webhooks,
+    })
+}
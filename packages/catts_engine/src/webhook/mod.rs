@@ -0,0 +1,9 @@
+pub mod rpc;
+pub mod state;
+pub mod tasks;
+pub mod types;
+pub mod util;
+
+pub use state::*;
+pub use types::*;
+pub use util::*;
@@ -0,0 +1,3 @@
+pub mod webhook_create;
+pub mod webhook_delete;
+pub mod webhook_list;
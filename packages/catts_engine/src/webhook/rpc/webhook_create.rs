@@ -0,0 +1,29 @@
+use ic_cdk::{api::management_canister::main::raw_rand, update};
+
+use crate::{
+    http_error::HttpError,
+    recipe,
+    user::auth_guard,
+    webhook::{self, Webhook, WebhookInput},
+};
+
+#[update]
+async fn webhook_create(input: WebhookInput) -> Result<Webhook, HttpError> {
+    let address = auth_guard()?;
+
+    if let Some(recipe_id) = input.recipe_id {
+        let recipe = recipe::get_by_id(&recipe_id).map_err(HttpError::not_found)?;
+        if address.to_string() != recipe.creator {
+            return Err(HttpError::unauthorized(
+                "You are not the author of this recipe.",
+            ));
+        }
+    }
+
+    let (random_bytes,) = raw_rand()
+        .await
+        .map_err(|(_, message)| HttpError::internal_server_error(message))?;
+
+    let webhook = Webhook::new(&input, &address, &random_bytes).map_err(HttpError::bad_request)?;
+    webhook::create(webhook).map_err(HttpError::bad_request)
+}
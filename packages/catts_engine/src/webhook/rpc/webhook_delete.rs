@@ -0,0 +1,21 @@
+use ic_cdk::update;
+
+use crate::{
+    http_error::HttpError,
+    user::auth_guard,
+    webhook::{self, Webhook, WebhookId},
+};
+
+#[update]
+fn webhook_delete(webhook_id: WebhookId) -> Result<Webhook, HttpError> {
+    let address = auth_guard()?;
+    let webhook = webhook::get_by_id(&webhook_id).map_err(HttpError::not_found)?;
+
+    if address.to_string() != webhook.owner {
+        return Err(HttpError::unauthorized(
+            "You are not the owner of this webhook.",
+        ));
+    }
+
+    webhook::delete(&webhook_id).map_err(HttpError::not_found)
+}
@@ -0,0 +1,14 @@
+use ic_cdk::query;
+
+use crate::{
+    http_error::HttpError,
+    user::auth_guard,
+    webhook::{self, Webhook},
+};
+
+/// Lists the webhooks registered by the caller.
+#[query]
+fn webhook_list() -> Result<Vec<Webhook>, HttpError> {
+    let address = auth_guard()?;
+    Ok(webhook::list_by_owner(address.as_str()))
+}
@@ -0,0 +1,52 @@
+use super::{Webhook, WebhookError, WebhookId};
+use crate::{run::Run, WEBHOOKS};
+
+const MAX_WEBHOOKS_PER_OWNER: usize = 10;
+
+pub fn create(webhook: Webhook) -> Result<Webhook, WebhookError> {
+    if list_by_owner(&webhook.owner).len() >= MAX_WEBHOOKS_PER_OWNER {
+        return Err(WebhookError::LimitReached(MAX_WEBHOOKS_PER_OWNER));
+    }
+
+    WEBHOOKS.with_borrow_mut(|webhooks| {
+        webhooks.insert(webhook.id, webhook.clone());
+    });
+
+    Ok(webhook)
+}
+
+pub fn get_by_id(webhook_id: &WebhookId) -> Result<Webhook, WebhookError> {
+    WEBHOOKS
+        .with_borrow(|webhooks| webhooks.get(webhook_id))
+        .ok_or(WebhookError::NotFound)
+}
+
+pub fn delete(webhook_id: &WebhookId) -> Result<Webhook, WebhookError> {
+    WEBHOOKS
+        .with_borrow_mut(|webhooks| webhooks.remove(webhook_id))
+        .ok_or(WebhookError::NotFound)
+}
+
+pub fn list_by_owner(owner: &str) -> Vec<Webhook> {
+    WEBHOOKS.with_borrow(|webhooks| {
+        webhooks
+            .iter()
+            .filter(|(_, webhook)| webhook.owner.eq_ignore_ascii_case(owner))
+            .map(|(_, webhook)| webhook)
+            .collect()
+    })
+}
+
+/// Returns the webhooks of the run's recipe and the webhooks of the run creator.
+pub fn list_for_run(run: &Run) -> Vec<Webhook> {
+    WEBHOOKS.with_borrow(|webhooks| {
+        webhooks
+            .iter()
+            .filter(|(_, webhook)| match webhook.recipe_id {
+                Some(recipe_id) => recipe_id == run.recipe_id,
+                None => webhook.owner.eq_ignore_ascii_case(&run.creator),
+            })
+            .map(|(_, webhook)| webhook)
+            .collect()
+    })
+}
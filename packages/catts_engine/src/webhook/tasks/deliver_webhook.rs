@@ -0,0 +1,115 @@
+use crate::{
+    logger,
+    tasks::{add_task, Task, TaskError, TaskExecutor, TaskType},
+    time::time,
+    webhook::{self, sign_payload, WebhookId},
+    ETH_DEFAULT_CALL_CYCLES,
+};
+use futures::Future;
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, TransformContext,
+};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+const DELIVER_WEBHOOK_RETRY_INTERVAL: u64 = 30_000_000_000; // 30 seconds, doubled for every attempt
+const DELIVER_WEBHOOK_MAX_ATTEMPTS: u32 = 6;
+const DELIVER_WEBHOOK_MAX_RESPONSE_BYTES: u64 = 2048;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DeliverWebhookArgs {
+    pub webhook_id: WebhookId,
+    pub payload: String,
+    pub attempt: u32,
+}
+
+pub fn schedule_deliver_webhook(webhook_id: &WebhookId, payload: String, attempt: u32) {
+    let delay = match attempt {
+        0 => 0,
+        _ => DELIVER_WEBHOOK_RETRY_INTERVAL << (attempt - 1),
+    };
+
+    add_task(
+        ic_cdk::api::time() + delay,
+        Task {
+            task_type: TaskType::DeliverWebhook,
+            args: bincode::serialize(&DeliverWebhookArgs {
+                webhook_id: *webhook_id,
+                payload,
+                attempt,
+            })
+            .unwrap(),
+            max_retries: 1,
+            execute_count: 0,
+            retry_interval: DELIVER_WEBHOOK_RETRY_INTERVAL,
+        },
+    );
+}
+
+pub struct DeliverWebhookExecutor {}
+
+impl TaskExecutor for DeliverWebhookExecutor {
+    fn execute(&self, task: Task) -> Pin<Box<dyn Future<Output = Result<(), TaskError>> + Send>> {
+        Box::pin(async move {
+            let args: DeliverWebhookArgs = bincode::deserialize(&task.args)
+                .map_err(|_| TaskError::Cancel("Invalid arguments".to_string()))?;
+
+            // The webhook might have been deleted since the delivery was scheduled
+            let webhook = webhook::get_by_id(&args.webhook_id)
+                .map_err(|err| TaskError::Cancel(err.to_string()))?;
+
+            let timestamp = time();
+            let signature = sign_payload(&webhook.secret, timestamp, &args.payload);
+
+            let request = CanisterHttpRequestArgument {
+                url: webhook.url.clone(),
+                method: HttpMethod::POST,
+                headers: vec![
+                    HttpHeader {
+                        name: "Content-Type".to_string(),
+                        value: "application/json".to_string(),
+                    },
+                    HttpHeader {
+                        name: "User-Agent".to_string(),
+                        value: "catts/0.0.1".to_string(),
+                    },
+                    HttpHeader {
+                        name: "X-Catts-Timestamp".to_string(),
+                        value: timestamp.to_string(),
+                    },
+                    HttpHeader {
+                        name: "X-Catts-Signature".to_string(),
+                        value: format!("sha256={}", signature),
+                    },
+                ],
+                body: Some(args.payload.clone().into_bytes()),
+                max_response_bytes: Some(DELIVER_WEBHOOK_MAX_RESPONSE_BYTES),
+                transform: Some(TransformContext::from_name(
+                    "webhook_transform".to_string(),
+                    vec![],
+                )),
+            };
+
+            let error = match http_request(request, ETH_DEFAULT_CALL_CYCLES).await {
+                Ok((response,)) => {
+                    let status = u16::try_from(&response.status.0).unwrap_or_default();
+                    if (200..300).contains(&status) {
+                        return Ok(());
+                    }
+                    format!("Webhook responded with status {}", status)
+                }
+                Err((_, message)) => format!("Webhook request failed: {}", message),
+            };
+
+            if args.attempt + 1 < DELIVER_WEBHOOK_MAX_ATTEMPTS {
+                schedule_deliver_webhook(&args.webhook_id, args.payload, args.attempt + 1);
+            } else {
+                logger::warn(
+                    format!("Webhook delivery to {} failed: {}", webhook.url, error).as_str(),
+                );
+            }
+
+            Err(TaskError::Cancel(error))
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{eth_address::EthAddress, recipe::RecipeId, time::time, url::is_https_or_localhost};
+use candid::{CandidType, Decode, Encode};
+use core::fmt;
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use thiserror::Error;
+use validator::{Validate, ValidationError};
+use validator_derive::Validate;
+
+use super::generate_webhook_id;
+
+pub type WebhookId = [u8; 12];
+
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Webhook not found")]
+    NotFound,
+    #[error("Too many webhooks, the limit is {0}")]
+    LimitReached(usize),
+}
+
+/// Run state transitions that webhooks can subscribe to.
+#[derive(Serialize, Deserialize, CandidType, Clone, Copy, Debug, PartialEq)]
+pub enum WebhookEvent {
+    PaymentVerified,
+    Attested,
+    Failed,
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            WebhookEvent::PaymentVerified => "run.payment_verified",
+            WebhookEvent::Attested => "run.attested",
+            WebhookEvent::Failed => "run.failed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Serialize, Deserialize, CandidType, Clone, Validate)]
+pub struct Webhook {
+    pub id: WebhookId,
+
+    pub owner: String,
+
+    /// Webhooks with a recipe id receive the events of all runs of that recipe, only the
+    /// recipe creator can register them. Webhooks without a recipe id receive the events of
+    /// the owner's own runs.
+    pub recipe_id: Option<RecipeId>,
+
+    // validate_webhook_url: https, or http for local testing
+    #[validate(length(min = 10, max = 512), custom(function = "validate_webhook_url"))]
+    pub url: String,
+
+    #[validate(length(min = 1, max = 3))]
+    pub events: Vec<WebhookEvent>,
+
+    /// Key used to sign the payloads, receivers use it to verify the signature
+    pub secret: String,
+
+    pub created: u32,
+}
+
+/// Payloads are delivered using HTTPS outcalls. Plain http is only accepted for localhost,
+/// so that deliveries can be tested against a local server.
+fn validate_webhook_url(url: &str) -> Result<(), ValidationError> {
+    if is_https_or_localhost(url) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        "Webhook url must use https, http is only allowed for localhost",
+    ))
+}
+
+impl Webhook {
+    pub fn new(
+        input: &WebhookInput,
+        owner: &EthAddress,
+        random_bytes: &[u8],
+    ) -> Result<Self, validator::ValidationErrors> {
+        let webhook = Self {
+            id: generate_webhook_id(owner, random_bytes),
+            owner: owner.to_string(),
+            recipe_id: input.recipe_id,
+            url: input.url.clone(),
+            events: input.events.clone(),
+            secret: ethers_core::utils::hex::encode(random_bytes),
+            created: time(),
+        };
+
+        webhook.validate()?;
+
+        Ok(webhook)
+    }
+
+    pub fn subscribes_to(&self, event: &WebhookEvent) -> bool {
+        self.events.contains(event)
+    }
+}
+
+impl Storable for Webhook {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType)]
+pub struct WebhookInput {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub recipe_id: Option<RecipeId>,
+}
@@ -0,0 +1,83 @@
+use super::{
+    list_for_run, tasks::deliver_webhook::schedule_deliver_webhook, WebhookEvent, WebhookId,
+};
+use crate::{
+    eth_address::EthAddress,
+    json::{bytes_to_hex_string, ToJsonValue},
+    run::{Run, RunStatus},
+    time::time,
+};
+use blake2::digest::{Update, VariableOutput};
+use blake2::Blake2bVar;
+use ethers_core::utils::hex;
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+
+pub fn generate_webhook_id(owner: &EthAddress, random_bytes: &[u8]) -> WebhookId {
+    let mut hasher = Blake2bVar::new(12).unwrap();
+    hasher.update(&owner.as_byte_array());
+    hasher.update(random_bytes);
+    let mut buf = [0u8; 12];
+    hasher.finalize_variable(&mut buf).unwrap();
+    buf
+}
+
+fn generate_delivery_id(webhook_id: &WebhookId, run: &Run, event: &WebhookEvent) -> String {
+    let mut hasher = Blake2bVar::new(12).unwrap();
+    hasher.update(webhook_id);
+    hasher.update(&run.id);
+    hasher.update(event.to_string().as_bytes());
+    hasher.update(&ic_cdk::api::time().to_be_bytes());
+    let mut buf = [0u8; 12];
+    hasher.finalize_variable(&mut buf).unwrap();
+    bytes_to_hex_string(&buf)
+}
+
+/// Signs a payload with HMAC-SHA256. The signed message is the timestamp and the payload
+/// joined by a dot, receivers should reject payloads with old timestamps to prevent replays.
+pub fn sign_payload(secret: &str, timestamp: u32, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Returns the events that a run update triggers.
+fn get_run_events(old_run: &Run, run: &Run) -> Vec<WebhookEvent> {
+    let mut events = Vec::new();
+
+    if old_run.status() < RunStatus::PaymentVerified && run.status() >= RunStatus::PaymentVerified {
+        events.push(WebhookEvent::PaymentVerified);
+    }
+
+    if old_run.attestation_uid.is_none() && run.attestation_uid.is_some() {
+        events.push(WebhookEvent::Attested);
+    }
+
+    if old_run.error.is_none() && run.error.is_some() {
+        events.push(WebhookEvent::Failed);
+    }
+
+    events
+}
+
+/// Schedules deliveries to all webhooks subscribed to the events triggered by a run update.
+pub fn notify_run_update(old_run: &Run, run: &Run) {
+    let events = get_run_events(old_run, run);
+    if events.is_empty() {
+        return;
+    }
+
+    for webhook in list_for_run(run) {
+        for event in events.iter().filter(|event| webhook.subscribes_to(event)) {
+            let payload = json!({
+                "id": generate_delivery_id(&webhook.id, run, event),
+                "event": event.to_string(),
+                "created": time(),
+                "run": run.to_json_value(),
+            });
+            schedule_deliver_webhook(&webhook.id, payload.to_string(), 0);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use candid::Principal;
+use ic_agent::Agent;
+use ic_certification::{Certificate, HashTree, LookupResult};
+use ring::digest::{digest, SHA256};
+
+use crate::types::CertifiedResponse;
+
+/// Verifies that each item of `response.data` is certified by the canister, at
+/// `[label, keys[i]]` in the certified data tree. The certificate signature is not checked,
+/// use `verify_certificate_signature` for that.
+pub fn verify_certified_response(
+    response: &CertifiedResponse,
+    canister: Principal,
+    label: &str,
+    keys: &[Vec<u8>],
+) -> Result<(), String> {
+    let certificate: Certificate = serde_cbor::from_slice(&response.certificate)
+        .map_err(|err| format!("Invalid certificate: {err}"))?;
+    let witness: HashTree = serde_cbor::from_slice(&response.witness)
+        .map_err(|err| format!("Invalid witness: {err}"))?;
+
+    let certified_data = match certificate.tree.lookup_path([
+        "canister".as_bytes(),
+        canister.as_slice(),
+        "certified_data".as_bytes(),
+    ]) {
+        LookupResult::Found(certified_data) => certified_data,
+        _ => return Err("Certified data not found in certificate".to_string()),
+    };
+    if certified_data != witness.digest() {
+        return Err("Witness does not match the certified data".to_string());
+    }
+
+    if response.data.len() != keys.len() {
+        return Err("Unexpected number of data items".to_string());
+    }
+
+    for (data, key) in response.data.iter().zip(keys) {
+        let leaf = match witness.lookup_path([label.as_bytes(), key.as_slice()]) {
+            LookupResult::Found(leaf) => leaf,
+            _ => return Err(format!("Key {} not found in witness", hex::encode(key))),
+        };
+        if leaf != digest(&SHA256, data).as_ref() {
+            return Err(format!(
+                "Data for key {} is not certified",
+                hex::encode(key)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies the signature of a certificate against the root key, the NNS public key on
+/// mainnet. Certificates of canisters outside the NNS subnet carry a delegation from the
+/// NNS, it is checked to cover `canister` as well.
+pub fn verify_certificate_signature(
+    certificate: &[u8],
+    canister: Principal,
+    root_key: &[u8],
+) -> Result<(), String> {
+    let certificate: Certificate =
+        serde_cbor::from_slice(certificate).map_err(|err| format!("Invalid certificate: {err}"))?;
+
+    // The agent is only used to verify, it never connects to the url
+    let agent = Agent::builder()
+        .with_url("http://127.0.0.1")
+        .build()
+        .map_err(|err| err.to_string())?;
+    agent.set_root_key(root_key.to_vec());
+    agent
+        .verify(&certificate, canister)
+        .map_err(|err| format!("Invalid certificate signature: {err}"))
+}
@@ -1,9 +1,12 @@
 use candid::{decode_one, encode_one, CandidType, Principal};
-use pocket_ic::{PocketIc, WasmResult};
+use pocket_ic::{PocketIc, PocketIcBuilder, WasmResult};
 use serde::{Deserialize, Serialize};
 use std::{fs, time::Duration};
 
-use crate::types::{RpcError, RpcResult};
+use crate::types::{
+    ChainConfig, EvmClientType, RpcApi, RpcError, RpcResult, RpcService, RpcServices,
+    TransactionType,
+};
 
 pub const CATTS_ENGINE_WASM: &str = "../../target/wasm32-wasi/release/catts_engine.wasm.gz";
 pub const IC_SIWE_WASM: &str = "../ic_siwe_provider/ic_siwe_provider.wasm.gz";
@@ -37,8 +40,28 @@ struct CattsEngineSettings {
 }
 
 pub fn setup() -> (PocketIc, Principal, Principal) {
-    let ic = PocketIc::new();
+    install(PocketIc::new(), "test_key")
+}
+
+/// Installs the canisters on an NNS subnet. The NNS public key is the root key of the
+/// instance, so certificates can be verified, see `PocketIc::root_key`.
+pub fn setup_nns() -> (PocketIc, Principal, Principal) {
+    install(PocketIcBuilder::new().with_nns_subnet().build(), "test_key")
+}
+
+/// Adds an II subnet, it holds the threshold ECDSA test keys. Use it for tests that derive
+/// Ethereum addresses.
+pub fn setup_ecdsa() -> (PocketIc, Principal, Principal) {
+    install(
+        PocketIcBuilder::new()
+            .with_application_subnet()
+            .with_ii_subnet()
+            .build(),
+        "dfx_test_key",
+    )
+}
 
+fn install(ic: PocketIc, ecdsa_key_id: &str) -> (PocketIc, Principal, Principal) {
     // Install ic-siwe
     let ic_siwe_canister = ic.create_canister();
     ic.add_cycles(ic_siwe_canister, 2_000_000_000_000); // 2T Cycles
@@ -63,7 +86,7 @@ pub fn setup() -> (PocketIc, Principal, Principal) {
     ic.add_cycles(catts_engine_canister, 2_000_000_000_000); // 2T Cycles
     let catts_engine_wasm = fs::read(CATTS_ENGINE_WASM).expect("CATTS_ENGINE_WASM not found");
     let catts_engine_settings = CattsEngineSettings {
-        ecdsa_key_id: "test_key".to_string(),
+        ecdsa_key_id: ecdsa_key_id.to_string(),
         siwe_provider_canister: ic_siwe_canister.to_string(),
         evm_rpc_canister: "not used yet".to_string(),
     };
@@ -78,6 +101,61 @@ pub fn setup() -> (PocketIc, Principal, Principal) {
     (ic, ic_siwe_canister, catts_engine_canister)
 }
 
+/// Upgrades catts_engine to the same wasm, with the given signing key in the settings.
+pub fn upgrade(
+    ic: &PocketIc,
+    catts: Principal,
+    siwe: Principal,
+    ecdsa_key_id: &str,
+) -> Result<(), String> {
+    let catts_engine_wasm = fs::read(CATTS_ENGINE_WASM).expect("CATTS_ENGINE_WASM not found");
+    let catts_engine_settings = CattsEngineSettings {
+        ecdsa_key_id: ecdsa_key_id.to_string(),
+        siwe_provider_canister: siwe.to_string(),
+        evm_rpc_canister: "not used yet".to_string(),
+    };
+    ic.upgrade_canister(
+        catts,
+        catts_engine_wasm,
+        encode_one(catts_engine_settings).unwrap(),
+        None,
+    )
+    .map_err(|err| format!("{:?}", err))
+}
+
+/// Config for a local chain, such as anvil, accessed directly using JSON-RPC.
+pub fn local_chain_config(
+    chain_id: u32,
+    rpc_url: &str,
+    eas_contract: &str,
+    payment_contract: &str,
+) -> ChainConfig {
+    let rpc_api = RpcApi {
+        url: rpc_url.to_string(),
+        headers: None,
+    };
+    ChainConfig {
+        chain_id,
+        eth_usd_price: "0x0".to_string(),
+        rpc_api_endpoint: rpc_url.to_string(),
+        eas_contract: eas_contract.to_string(),
+        payment_contract: payment_contract.to_string(),
+        rpc_services: RpcServices::Custom {
+            chainId: chain_id as u64,
+            services: vec![rpc_api.clone()],
+        },
+        default_rpc_service: RpcService::Custom(rpc_api),
+        min_user_fee: Some(1_u8.into()),
+        min_offchain_user_fee: Some(1_u8.into()),
+        fee_margin_percent: None,
+        disabled: None,
+        transaction_type: Some(TransactionType::Eip1559),
+        block_time_ms: Some(1_000),
+        confirmations: None,
+        evm_client: Some(EvmClientType::JsonRpc),
+    }
+}
+
 pub fn update<T: CandidType + for<'de> Deserialize<'de>>(
     ic: &PocketIc,
     canister: Principal,
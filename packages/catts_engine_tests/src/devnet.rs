@@ -0,0 +1,105 @@
+//! Helpers for tests that run the whole pipeline against a local anvil devnet, along with
+//! the canisters deployed to a local replica, see `replica`. Start anvil and deploy the EAS
+//! and payment contracts first.
+//!
+//! Configured using environment variables:
+//! - `DEVNET_EAS_CONTRACT` and `DEVNET_PAYMENT_CONTRACT`, required
+//! - `DEVNET_RPC_URL`, defaults to `http://127.0.0.1:8545`
+//! - `DEVNET_PRIVATE_KEY`, a funded account, defaults to the first anvil dev account
+
+use ethers::{
+    abi::Abi,
+    contract::Contract,
+    core::abi::parse_abi,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, TransactionRequest, U256},
+};
+use std::{env, sync::Arc};
+
+pub const DEVNET_CHAIN_ID: u32 = 31337;
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8545";
+const ANVIL_PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+pub type DevnetClient = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+pub struct Devnet {
+    pub rpc_url: String,
+    pub eas_contract: String,
+    pub payment_contract: String,
+    pub client: Arc<DevnetClient>,
+}
+
+impl Devnet {
+    pub fn from_env() -> Self {
+        let required =
+            |name: &str| env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+        let rpc_url = env::var("DEVNET_RPC_URL").unwrap_or(DEFAULT_RPC_URL.to_string());
+        let wallet = env::var("DEVNET_PRIVATE_KEY")
+            .unwrap_or(ANVIL_PRIVATE_KEY.to_string())
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(DEVNET_CHAIN_ID);
+        let provider = Provider::<Http>::try_from(rpc_url.as_str()).unwrap();
+
+        Self {
+            rpc_url,
+            eas_contract: required("DEVNET_EAS_CONTRACT"),
+            payment_contract: required("DEVNET_PAYMENT_CONTRACT"),
+            client: Arc::new(SignerMiddleware::new(provider, wallet)),
+        }
+    }
+
+    pub fn contract(&self, address: &str, abi: &[&str]) -> Contract<DevnetClient> {
+        let abi: Abi = parse_abi(abi).unwrap();
+        Contract::new(
+            address.parse::<Address>().unwrap(),
+            abi,
+            self.client.clone(),
+        )
+    }
+
+    /// Sends `amount` wei from the devnet account and waits for the transfer to be mined.
+    pub async fn fund(&self, address: &str, amount: U256) {
+        let transaction = TransactionRequest::new()
+            .to(address.parse::<Address>().unwrap())
+            .value(amount);
+        self.client
+            .send_transaction(transaction, None)
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Registers an EAS schema without resolver, returns once the registration is mined.
+    pub async fn register_schema(&self, schema: &str, revocable: bool) {
+        let eas = self.contract(
+            &self.eas_contract,
+            &["function getSchemaRegistry() view returns (address)"],
+        );
+        let registry: Address = eas
+            .method::<_, Address>("getSchemaRegistry", ())
+            .unwrap()
+            .call()
+            .await
+            .unwrap();
+
+        let registry = self.contract(
+            &format!("{:?}", registry),
+            &["function register(string schema, address resolver, bool revocable) returns (bytes32)"],
+        );
+        registry
+            .method::<_, [u8; 32]>("register", (schema.to_string(), Address::zero(), revocable))
+            .unwrap()
+            .send()
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -1,4 +1,7 @@
+pub mod certification;
 pub mod common;
+pub mod devnet;
 pub mod recipes;
+pub mod replica;
 pub mod siwe;
 pub mod types;
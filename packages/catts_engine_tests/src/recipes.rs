@@ -1,4 +1,10 @@
-use crate::types::{RecipeDetailsInput, RecipeQuery};
+use candid::{encode_args, encode_one, Principal};
+use pocket_ic::PocketIc;
+
+use crate::{
+    common::catts_update,
+    types::{Recipe, RecipeDetailsInput, RecipeQuery, RpcResult},
+};
 
 pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
     let details = RecipeDetailsInput {
@@ -59,6 +65,7 @@ pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
         resolver: "0x0000000000000000000000000000000000000000".to_string(),
         schema: "uint256 score,uint32 scorer_id,uint8 score_decimals".to_string(),
         revokable: false,
+        dedicated_attester: None,
     };
 
     let readme = r#"This recipe fetches the Gitcoin Passport score for a given Ethereum address."#
@@ -66,3 +73,23 @@ pub fn recipe_eu_gtc_passport_clone() -> (RecipeDetailsInput, String) {
 
     (details, readme)
 }
+
+/// Creates and publishes a clone of the Gitcoin Passport recipe.
+pub fn create_published_recipe(ic: &PocketIc, catts: Principal, sender: Principal) -> Recipe {
+    let create_response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+    let publish_response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    publish_response.unwrap_ok().clone()
+}
@@ -0,0 +1,327 @@
+//! Helpers for tests that depend on HTTPS outcalls, which PocketIC doesn't support. These
+//! tests run against the canisters deployed to a local replica (`dfx start` and
+//! `make deploy-all`) and are ignored by default, run them with `make test-replica`.
+//!
+//! Configured using environment variables:
+//! - `CATTS_ENGINE_CANISTER_ID` and `IC_SIWE_PROVIDER_CANISTER_ID`, required
+//! - `IC_URL`, defaults to `http://127.0.0.1:4943`
+//! - `CATTS_CONTROLLER_PEM`, identity of a catts_engine controller, defaults to the dfx
+//!   default identity
+
+use candid::{decode_one, encode_args, encode_one, CandidType, Principal};
+use ethers::{signers::LocalWallet, utils::hash_message};
+use ic_agent::{
+    identity::{BasicIdentity, Secp256k1Identity},
+    Agent, Identity,
+};
+use ic_siwe::{delegation::SignedDelegation, login::LoginDetails};
+use serde::Deserialize;
+use std::{
+    env,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    siwe::{create_delegated_identity, create_session_identity, create_wallet},
+    types::{RpcError, RpcResult, User},
+};
+
+const DEFAULT_IC_URL: &str = "http://127.0.0.1:4943";
+
+pub struct Replica {
+    pub url: String,
+    pub catts: Principal,
+    pub siwe: Principal,
+}
+
+impl Replica {
+    pub fn from_env() -> Self {
+        let canister_id = |name: &str| {
+            let id = env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+            Principal::from_text(id).unwrap()
+        };
+        Self {
+            url: env::var("IC_URL").unwrap_or(DEFAULT_IC_URL.to_string()),
+            catts: canister_id("CATTS_ENGINE_CANISTER_ID"),
+            siwe: canister_id("IC_SIWE_PROVIDER_CANISTER_ID"),
+        }
+    }
+
+    pub async fn agent(&self, identity: Box<dyn Identity>) -> Agent {
+        let agent = Agent::builder()
+            .with_url(&self.url)
+            .with_boxed_identity(identity)
+            .build()
+            .unwrap();
+        // Local replicas use their own root key
+        agent.fetch_root_key().await.unwrap();
+        agent
+    }
+
+    /// Returns an agent using the identity of a catts_engine controller.
+    pub async fn controller(&self) -> Agent {
+        let pem = env::var("CATTS_CONTROLLER_PEM").unwrap_or_else(|_| {
+            format!(
+                "{}/.config/dfx/identity/default/identity.pem",
+                env::var("HOME").unwrap()
+            )
+        });
+        let identity: Box<dyn Identity> = match Secp256k1Identity::from_pem_file(&pem) {
+            Ok(identity) => Box::new(identity),
+            Err(_) => Box::new(BasicIdentity::from_pem_file(&pem).unwrap()),
+        };
+        self.agent(identity).await
+    }
+
+    /// Signs in with a new wallet and creates a catts user for it.
+    pub async fn login(&self) -> (String, LocalWallet, Agent) {
+        let (wallet, address) = create_wallet();
+        let anonymous = self
+            .agent(Box::new(ic_agent::identity::AnonymousIdentity))
+            .await;
+
+        let siwe_message: String = replica_call(
+            &anonymous,
+            self.siwe,
+            "siwe_prepare_login",
+            encode_one(&address).unwrap(),
+            false,
+        )
+        .await;
+        let signature = wallet
+            .sign_hash(hash_message(siwe_message.as_bytes()))
+            .unwrap();
+
+        let session_identity = create_session_identity();
+        let session_pubkey = session_identity.public_key().unwrap();
+        let login_response: LoginDetails = replica_call(
+            &anonymous,
+            self.siwe,
+            "siwe_login",
+            encode_args((
+                format!("0x{}", signature),
+                address.clone(),
+                session_pubkey.clone(),
+            ))
+            .unwrap(),
+            false,
+        )
+        .await;
+
+        let delegation: SignedDelegation = replica_call(
+            &anonymous,
+            self.siwe,
+            "siwe_get_delegation",
+            encode_args((address.clone(), session_pubkey, login_response.expiration)).unwrap(),
+            true,
+        )
+        .await;
+
+        // The local siwe provider is deployed with both canisters as delegation targets
+        let identity = create_delegated_identity(
+            session_identity,
+            &login_response,
+            delegation.signature.as_ref().to_vec(),
+            Some(vec![self.siwe, self.catts]),
+        );
+        let agent = self.agent(Box::new(identity)).await;
+
+        let response: RpcResult<User> =
+            replica_update(&agent, self.catts, "user_create", encode_one(()).unwrap()).await;
+        assert!(response.is_ok());
+
+        (address, wallet, agent)
+    }
+}
+
+/// Calls a method that returns `Result<T, String>`, panics on errors.
+async fn replica_call<T: CandidType + for<'de> Deserialize<'de>>(
+    agent: &Agent,
+    canister: Principal,
+    method: &str,
+    args: Vec<u8>,
+    query: bool,
+) -> T {
+    let response = match query {
+        true => agent.query(&canister, method).with_arg(args).call().await,
+        false => {
+            agent
+                .update(&canister, method)
+                .with_arg(args)
+                .call_and_wait()
+                .await
+        }
+    }
+    .unwrap();
+    decode_one::<Result<T, String>>(&response).unwrap().unwrap()
+}
+
+fn to_rpc_result<T: CandidType + for<'de> Deserialize<'de>>(
+    response: Result<Vec<u8>, ic_agent::AgentError>,
+) -> RpcResult<T> {
+    match response {
+        Ok(data) => decode_one(&data).unwrap(),
+        Err(err) => RpcResult::Err(RpcError {
+            code: 500,
+            message: err.to_string(),
+            details: None,
+        }),
+    }
+}
+
+pub async fn replica_update<T: CandidType + for<'de> Deserialize<'de>>(
+    agent: &Agent,
+    canister: Principal,
+    method: &str,
+    args: Vec<u8>,
+) -> RpcResult<T> {
+    to_rpc_result(
+        agent
+            .update(&canister, method)
+            .with_arg(args)
+            .call_and_wait()
+            .await,
+    )
+}
+
+pub async fn replica_query<T: CandidType + for<'de> Deserialize<'de>>(
+    agent: &Agent,
+    canister: Principal,
+    method: &str,
+    args: Vec<u8>,
+) -> RpcResult<T> {
+    to_rpc_result(agent.query(&canister, method).with_arg(args).call().await)
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A local HTTP server that stands in for webhook receivers and RPC nodes.
+pub struct HttpStandIn {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl HttpStandIn {
+    /// Answers requests with the scripted status codes in order and an empty body, the
+    /// last status is repeated once the script runs out.
+    pub fn start(statuses: Vec<u16>) -> Self {
+        let count = AtomicUsize::new(0);
+        Self::start_with(move |_| {
+            let i = count.fetch_add(1, Ordering::SeqCst);
+            let status = statuses.get(i).or(statuses.last()).copied().unwrap_or(200);
+            (status, String::new())
+        })
+    }
+
+    /// Answers each request with the status code and body returned by `respond`.
+    pub fn start_with(
+        respond: impl Fn(&RecordedRequest) -> (u16, String) + Send + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let Some(request) = read_request(&mut stream) else {
+                    continue;
+                };
+                let (status, body) = respond(&request);
+                recorded.lock().unwrap().push(request);
+
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Waits until at least `count` requests have been received.
+    pub async fn wait_for_requests(&self, count: usize, timeout: Duration) -> Vec<RecordedRequest> {
+        let started = Instant::now();
+        loop {
+            let requests = self.requests();
+            if requests.len() >= count {
+                return requests;
+            }
+            if started.elapsed() > timeout {
+                panic!(
+                    "Received {} of {} requests within {:?}",
+                    requests.len(),
+                    count,
+                    timeout
+                );
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+}
+
+fn read_request(stream: &mut std::net::TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let path = request_line.split_whitespace().nth(1)?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
@@ -77,13 +77,14 @@ pub fn create_delegated_identity(
     )
 }
 
-pub fn full_login(
+/// Signs in to the SIWE provider with `wallet`, without creating a catts user.
+pub fn siwe_login(
     ic: &PocketIc,
     ic_siwe_provider_canister: Principal,
-    catts_canister: Principal,
+    wallet: Wallet<SigningKey>,
     targets: Option<Vec<Principal>>,
 ) -> (String, DelegatedIdentity) {
-    let (wallet, address) = create_wallet();
+    let address = to_checksum(&wallet.address(), None);
     let (signature, _) =
         prepare_login_and_sign_message(ic, ic_siwe_provider_canister, wallet, &address);
 
@@ -128,6 +129,18 @@ pub fn full_login(
         targets,
     );
 
+    (address, delegated_identity)
+}
+
+pub fn full_login(
+    ic: &PocketIc,
+    ic_siwe_provider_canister: Principal,
+    catts_canister: Principal,
+    targets: Option<Vec<Principal>>,
+) -> (String, DelegatedIdentity) {
+    let (wallet, _) = create_wallet();
+    let (address, delegated_identity) = siwe_login(ic, ic_siwe_provider_canister, wallet, targets);
+
     // Create a user in the catts canister
     let _: User = update(
         ic,
@@ -1,4 +1,4 @@
-use candid::{CandidType, Nat};
+use candid::{CandidType, Nat, Principal};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, CandidType, Clone)]
@@ -41,6 +41,22 @@ impl<T> RpcResult<T> {
 #[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
 pub struct User {
     pub eth_address: String,
+    pub created: Option<u32>,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub links: Option<Vec<String>>,
+    pub linked_addresses: Option<Vec<String>>,
+    pub primary_address: Option<String>,
+    pub link_nonce: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct UserProfileInput {
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub links: Option<Vec<String>>,
 }
 
 pub type EthAddressBytes = [u8; 20];
@@ -77,6 +93,7 @@ pub struct Recipe {
     pub revokable: bool,
     pub gas: Option<Nat>,
     pub publish_state: RecipePublishState,
+    pub attester: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, CandidType)]
@@ -90,4 +107,298 @@ pub struct RecipeDetailsInput {
     pub schema: String,
     pub resolver: String,
     pub revokable: bool,
+    pub dedicated_attester: Option<bool>,
+}
+
+pub type RunId = [u8; 12];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum RunMode {
+    Onchain,
+    Offchain,
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType)]
+pub struct RunRecipientInput {
+    pub address: String,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct Run {
+    pub id: RunId,
+    pub recipe_id: RecipeId,
+    pub creator: String,
+    pub recipient: Option<String>,
+    pub created: u32,
+    pub chain_id: u32,
+    pub gas: Option<Nat>,
+    pub base_fee_per_gas: Option<Nat>,
+    pub max_priority_fee_per_gas: Option<Nat>,
+    pub user_fee: Option<Nat>,
+    pub payment_transaction_hash: Option<String>,
+    pub payment_block_number: Option<Nat>,
+    pub payment_log_index: Option<Nat>,
+    pub attestation_transaction_hash: Option<String>,
+    pub attestation_transaction_hashes: Option<Vec<String>>,
+    pub attestation_batch_index: Option<u32>,
+    pub attestation_nonce: Option<u64>,
+    pub attester_addresses: Option<Vec<String>>,
+    pub attestation_uid: Option<String>,
+    pub mode: Option<RunMode>,
+    pub offchain_attestation: Option<String>,
+    pub is_cancelled: bool,
+    pub error: Option<String>,
+}
+
+pub type WebhookId = [u8; 12];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum WebhookEvent {
+    PaymentVerified,
+    Attested,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub owner: String,
+    pub recipe_id: Option<RecipeId>,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub secret: String,
+    pub created: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, CandidType)]
+pub struct WebhookInput {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub recipe_id: Option<RecipeId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct CertifiedResponse {
+    pub data: Vec<Vec<u8>>,
+    pub certificate: Vec<u8>,
+    pub witness: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct HttpGatewayRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct HttpGatewayResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpGatewayResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, CandidType)]
+pub enum ChangeLogTypeName {
+    Recipe,
+    Run,
+    User,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, CandidType)]
+pub enum ChangeLogAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogItem {
+    pub type_name: ChangeLogTypeName,
+    pub id: String,
+    pub action: ChangeLogAction,
+    pub patch: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct IndexedChangeLogItem {
+    pub index: u32,
+    pub data: ChangeLogItem,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogResponse {
+    pub total_count: u32,
+    pub data: Vec<IndexedChangeLogItem>,
+    pub next_index: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, CandidType)]
+pub struct ChangeLogFilter {
+    pub type_name: Option<ChangeLogTypeName>,
+    pub id: Option<String>,
+    pub action: Option<ChangeLogAction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogSnapshot {
+    pub index: u32,
+    pub created: u32,
+    pub entity_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogSnapshotEntity {
+    pub type_name: ChangeLogTypeName,
+    pub id: String,
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogSnapshotEntitiesResponse {
+    pub snapshot: ChangeLogSnapshot,
+    pub entities: Vec<ChangeLogSnapshotEntity>,
+    pub next_index: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct EntityVersion {
+    pub index: u32,
+    pub action: Option<ChangeLogAction>,
+    pub data: String,
+    pub snapshot: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum Role {
+    Admin,
+    Operator,
+    Moderator,
+    Auditor,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RoleAssignment {
+    pub principal: Principal,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct SigningKey {
+    pub key_id: String,
+    pub address: String,
+    pub registered: u32,
+    pub activated: Option<u32>,
+    pub retired: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct SigningKeys {
+    pub active: Option<SigningKey>,
+    pub pending: Option<SigningKey>,
+    pub previous: Vec<SigningKey>,
+    pub settings_key_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum EthSepoliaService {
+    Alchemy,
+    BlockPi,
+    PublicNode,
+    Ankr,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum L2MainnetService {
+    Alchemy,
+    BlockPi,
+    PublicNode,
+    Ankr,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum EthMainnetService {
+    Alchemy,
+    BlockPi,
+    Cloudflare,
+    PublicNode,
+    Ankr,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RpcApiHeader {
+    pub value: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct RpcApi {
+    pub url: String,
+    pub headers: Option<Vec<RpcApiHeader>>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum RpcServices {
+    EthSepolia(Option<Vec<EthSepoliaService>>),
+    BaseMainnet(Option<Vec<L2MainnetService>>),
+    Custom { chainId: u64, services: Vec<RpcApi> },
+    OptimismMainnet(Option<Vec<L2MainnetService>>),
+    ArbitrumOne(Option<Vec<L2MainnetService>>),
+    EthMainnet(Option<Vec<EthMainnetService>>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum RpcService {
+    EthSepolia(EthSepoliaService),
+    BaseMainnet(L2MainnetService),
+    Custom(RpcApi),
+    OptimismMainnet(L2MainnetService),
+    ArbitrumOne(L2MainnetService),
+    EthMainnet(EthMainnetService),
+    Chain(u64),
+    Provider(u64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum TransactionType {
+    Legacy,
+    Eip2930,
+    Eip1559,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, CandidType)]
+pub enum EvmClientType {
+    EvmRpc,
+    JsonRpc,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChainConfig {
+    pub chain_id: u32,
+    pub eth_usd_price: String,
+    pub rpc_api_endpoint: String,
+    pub eas_contract: String,
+    pub payment_contract: String,
+    pub rpc_services: RpcServices,
+    pub default_rpc_service: RpcService,
+    pub min_user_fee: Option<Nat>,
+    pub min_offchain_user_fee: Option<Nat>,
+    pub fee_margin_percent: Option<u32>,
+    pub disabled: Option<bool>,
+    pub transaction_type: Option<TransactionType>,
+    pub block_time_ms: Option<u64>,
+    pub confirmations: Option<u32>,
+    pub evm_client: Option<EvmClientType>,
 }
@@ -0,0 +1,242 @@
+use candid::{encode_args, encode_one, Nat, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::create_published_recipe,
+    siwe::full_login,
+    types::{
+        ChainConfig, EvmClientType, Role, RoleAssignment, RpcApi, RpcResult, RpcService,
+        RpcServices, Run, RunMode, RunRecipientInput, TransactionType,
+    },
+};
+use ic_agent::Identity;
+
+fn devnet_config() -> ChainConfig {
+    let rpc_api = RpcApi {
+        url: "http://127.0.0.1:8545".to_string(),
+        headers: None,
+    };
+    ChainConfig {
+        chain_id: 31337,
+        eth_usd_price: "0x0".to_string(),
+        rpc_api_endpoint: "http://127.0.0.1:8545".to_string(),
+        eas_contract: "0xC2679fBD37d54388Ce493F1DB75320D236e1815e".to_string(),
+        payment_contract: "0xe498539Cad0E4325b88d6F6a1B89af7e4C8dF404".to_string(),
+        rpc_services: RpcServices::Custom {
+            chainId: 31337,
+            services: vec![rpc_api.clone()],
+        },
+        default_rpc_service: RpcService::Custom(rpc_api),
+        min_user_fee: Some(Nat::from(1_u8)),
+        min_offchain_user_fee: Some(Nat::from(1_u8)),
+        fee_margin_percent: Some(10),
+        disabled: None,
+        transaction_type: Some(TransactionType::Legacy),
+        block_time_ms: Some(1_000),
+        confirmations: Some(1),
+        evm_client: Some(EvmClientType::JsonRpc),
+    }
+}
+
+#[test]
+fn chain_config_management() {
+    let (ic, _, catts) = setup();
+
+    // The anonymous principal controls the test canister
+    let response: RpcResult<Vec<ChainConfig>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_list",
+        encode_one(()).unwrap(),
+    );
+    let default_count = response.unwrap_ok().len();
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(devnet_config()).unwrap(),
+    );
+    let config = response.unwrap_ok();
+    assert_eq!(config.chain_id, 31337);
+    assert_eq!(config.transaction_type, Some(TransactionType::Legacy));
+    assert_eq!(config.evm_client, Some(EvmClientType::JsonRpc));
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_disable",
+        encode_args((31337_u32, true)).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().disabled, Some(true));
+
+    let response: RpcResult<Vec<ChainConfig>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_list",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().len(), default_count + 1);
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_remove",
+        encode_one(31337_u32).unwrap(),
+    );
+    response.unwrap_ok();
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_remove",
+        encode_one(31337_u32).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+/// Runs that are not attested or cancelled keep the chain config, paid or not.
+#[test]
+fn chain_config_remove_open_runs() {
+    let (ic, siwe, catts) = setup();
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(devnet_config()).unwrap(),
+    );
+    assert!(response.is_ok());
+
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_create",
+        encode_args((
+            recipe.id,
+            31337_u32,
+            Some(RunMode::Offchain),
+            None::<RunRecipientInput>,
+        ))
+        .unwrap(),
+    );
+    let run = response.unwrap_ok().clone();
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_remove",
+        encode_one(31337_u32).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 409);
+
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_cancel",
+        encode_one(run.id).unwrap(),
+    );
+    assert!(response.is_ok());
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_remove",
+        encode_one(31337_u32).unwrap(),
+    );
+    assert!(response.is_ok());
+}
+
+#[test]
+fn chain_config_upsert_invalid() {
+    let (ic, _, catts) = setup();
+    let config = ChainConfig {
+        rpc_services: RpcServices::Custom {
+            chainId: 1,
+            services: vec![],
+        },
+        ..devnet_config()
+    };
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(config).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn chain_config_upsert_remote_http() {
+    let (ic, _, catts) = setup();
+    let rpc_api = RpcApi {
+        url: "http://rpc.example.com".to_string(),
+        headers: None,
+    };
+    let config = ChainConfig {
+        rpc_services: RpcServices::Custom {
+            chainId: 31337,
+            services: vec![rpc_api],
+        },
+        ..devnet_config()
+    };
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(config).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn chain_config_upsert_forbidden() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "chain_config_upsert",
+        encode_one(devnet_config()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+/// Chain configs hold the RPC endpoints and contracts, only controllers can change them.
+#[test]
+fn chain_config_upsert_admin() {
+    let (ic, _, catts) = setup();
+    let admin = Principal::from_slice(&[7; 29]);
+
+    let response: RpcResult<RoleAssignment> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "role_grant",
+        encode_args((admin, Role::Admin)).unwrap(),
+    );
+    response.unwrap_ok();
+
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        admin,
+        "chain_config_upsert",
+        encode_one(devnet_config()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
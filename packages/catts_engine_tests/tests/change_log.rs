@@ -0,0 +1,93 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{
+        ChangeLogAction, ChangeLogFilter, ChangeLogResponse, ChangeLogTypeName, Recipe, RpcResult,
+    },
+};
+use ic_agent::Identity;
+
+fn change_log(
+    ic: &pocket_ic::PocketIc,
+    catts: Principal,
+    start_index: u32,
+    limit: Option<u32>,
+    filter: Option<ChangeLogFilter>,
+) -> ChangeLogResponse {
+    let response: RpcResult<ChangeLogResponse> = catts_query(
+        ic,
+        catts,
+        Principal::anonymous(),
+        "change_log",
+        encode_args((start_index, limit, filter)).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+#[test]
+fn change_log_filter() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+    let recipe_id = format!("0x{}", hex::encode(recipe.id));
+
+    let filter = ChangeLogFilter {
+        type_name: Some(ChangeLogTypeName::Recipe),
+        id: Some(recipe_id.clone()),
+        action: None,
+    };
+    let response = change_log(&ic, catts, 0, None, Some(filter));
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(response.data[0].data.id, recipe_id);
+    assert_eq!(response.data[0].data.action, ChangeLogAction::Create);
+    assert_eq!(response.next_index, None);
+
+    let filter = ChangeLogFilter {
+        action: Some(ChangeLogAction::Delete),
+        ..Default::default()
+    };
+    let response = change_log(&ic, catts, 0, None, Some(filter));
+    assert!(response.data.is_empty());
+}
+
+#[test]
+fn change_log_next_index() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+    let _: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+
+    let all = change_log(&ic, catts, 0, None, None);
+    // User create, recipe create and recipe publish
+    assert_eq!(all.total_count, 3);
+
+    let first = change_log(&ic, catts, 0, Some(1), None);
+    assert_eq!(first.data.len(), 1);
+    assert_eq!(first.next_index, Some(1));
+
+    let rest = change_log(&ic, catts, first.next_index.unwrap(), None, None);
+    assert_eq!(rest.data.len() as u32, all.total_count - 1);
+    assert_eq!(rest.next_index, None);
+}
@@ -0,0 +1,76 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{
+        ChangeLogSnapshot, ChangeLogSnapshotEntitiesResponse, ChangeLogTypeName, Recipe, RpcResult,
+    },
+};
+use ic_agent::Identity;
+
+#[test]
+fn change_log_snapshot_create() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    // The anonymous principal is the controller of the test canister
+    let snapshot_response: RpcResult<ChangeLogSnapshot> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_create",
+        encode_one(()).unwrap(),
+    );
+    let snapshot = snapshot_response.unwrap_ok();
+    // User create and recipe create
+    assert_eq!(snapshot.index, 2);
+    assert_eq!(snapshot.entity_count, 2);
+
+    let latest_response: RpcResult<ChangeLogSnapshot> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_latest",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(latest_response.unwrap_ok().index, 2);
+
+    let entities_response: RpcResult<ChangeLogSnapshotEntitiesResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_entities",
+        encode_args((snapshot.index, 0_u32, None::<u32>)).unwrap(),
+    );
+    let entities = entities_response.unwrap_ok();
+    assert_eq!(entities.entities.len(), 2);
+    assert_eq!(entities.entities[0].type_name, ChangeLogTypeName::Recipe);
+    assert_eq!(
+        entities.entities[0].id,
+        format!("0x{}", hex::encode(recipe.id))
+    );
+    assert_eq!(entities.next_index, None);
+}
+
+#[test]
+fn change_log_snapshot_create_forbidden() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<ChangeLogSnapshot> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "change_log_snapshot_create",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
@@ -0,0 +1,154 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{
+        ChangeLogAction, ChangeLogSnapshot, ChangeLogTypeName, EntityVersion, Recipe, RpcResult,
+    },
+};
+use ic_agent::Identity;
+
+fn create_and_publish_recipe(
+    ic: &pocket_ic::PocketIc,
+    siwe: Principal,
+    catts: Principal,
+) -> Recipe {
+    let (_, identity) = full_login(ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok().clone();
+    let publish_response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    publish_response.unwrap_ok().clone()
+}
+
+#[test]
+fn entity_history() {
+    let (ic, siwe, catts) = setup();
+    let recipe = create_and_publish_recipe(&ic, siwe, catts);
+    let id = format!("0x{}", hex::encode(recipe.id));
+
+    let response: RpcResult<Vec<EntityVersion>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "entity_history",
+        encode_args((ChangeLogTypeName::Recipe, id)).unwrap(),
+    );
+    let versions = response.unwrap_ok();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0].action, Some(ChangeLogAction::Create));
+    assert_eq!(versions[1].action, Some(ChangeLogAction::Update));
+
+    let data: serde_json::Value = serde_json::from_str(&versions[1].data).unwrap();
+    assert_eq!(data["publish_state"], "Published");
+}
+
+#[test]
+fn entity_at() {
+    let (ic, siwe, catts) = setup();
+    let recipe = create_and_publish_recipe(&ic, siwe, catts);
+    let id = format!("0x{}", hex::encode(recipe.id));
+
+    let response: RpcResult<EntityVersion> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "entity_at",
+        encode_args((ChangeLogTypeName::Recipe, id, 1_u32)).unwrap(),
+    );
+    let version = response.unwrap_ok();
+    let data: serde_json::Value = serde_json::from_str(&version.data).unwrap();
+    assert_eq!(data["publish_state"], "Draft");
+}
+
+fn create_snapshot(ic: &pocket_ic::PocketIc, catts: Principal) -> ChangeLogSnapshot {
+    // The anonymous principal is the controller of the test canister
+    let response: RpcResult<ChangeLogSnapshot> = catts_update(
+        ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_create",
+        encode_one(()).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+#[test]
+fn entity_history_from_snapshot() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok().clone();
+    create_snapshot(&ic, catts);
+    full_login(&ic, siwe, catts, None);
+    let snapshot = create_snapshot(&ic, catts);
+    let publish_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+    assert!(publish_response.is_ok());
+    create_snapshot(&ic, catts);
+    full_login(&ic, siwe, catts, None);
+
+    // The fourth snapshot compacts the change log, history starts at the second one
+    create_snapshot(&ic, catts);
+
+    let response: RpcResult<Vec<EntityVersion>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "entity_history",
+        encode_args((
+            ChangeLogTypeName::Recipe,
+            format!("0x{}", hex::encode(recipe.id)),
+        ))
+        .unwrap(),
+    );
+    let versions = response.unwrap_ok();
+    assert_eq!(versions.len(), 2);
+
+    // The snapshot and the publish entry share the index
+    assert_eq!(versions[0].index, snapshot.index);
+    assert!(versions[0].snapshot);
+    assert_eq!(versions[0].action, None);
+    assert_eq!(versions[1].index, snapshot.index);
+    assert!(!versions[1].snapshot);
+    assert_eq!(versions[1].action, Some(ChangeLogAction::Update));
+
+    let data: serde_json::Value = serde_json::from_str(&versions[0].data).unwrap();
+    assert_eq!(data["publish_state"], "Draft");
+}
+
+#[test]
+fn entity_history_invalid_id() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<Vec<EntityVersion>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "entity_history",
+        encode_args((ChangeLogTypeName::Recipe, "not-an-id".to_string())).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
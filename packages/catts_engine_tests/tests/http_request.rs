@@ -0,0 +1,90 @@
+use candid::{decode_one, encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{HttpGatewayRequest, HttpGatewayResponse, Recipe, RpcResult},
+};
+use ic_agent::Identity;
+use pocket_ic::{PocketIc, WasmResult};
+
+fn http_get(ic: &PocketIc, catts: Principal, url: &str) -> HttpGatewayResponse {
+    let request = HttpGatewayRequest {
+        method: "GET".to_string(),
+        url: url.to_string(),
+        headers: vec![],
+        body: vec![],
+    };
+    match ic.query_call(
+        catts,
+        Principal::anonymous(),
+        "http_request",
+        encode_one(request).unwrap(),
+    ) {
+        Ok(WasmResult::Reply(data)) => decode_one(&data).unwrap(),
+        _ => panic!("http_request failed"),
+    }
+}
+
+fn create_recipe(ic: &PocketIc, siwe: Principal, catts: Principal) -> Recipe {
+    let (_, identity) = full_login(ic, siwe, catts, None);
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+#[test]
+fn http_request_recipe() {
+    let (ic, siwe, catts) = setup();
+    let recipe = create_recipe(&ic, siwe, catts);
+
+    let response = http_get(&ic, catts, &format!("/recipes/{}", recipe.name));
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.header("Content-Type"), Some("application/json"));
+    assert!(response.header("IC-Certificate").is_some());
+
+    let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(body["name"], recipe.name);
+}
+
+#[test]
+fn http_request_recipe_badge() {
+    let (ic, siwe, catts) = setup();
+    let recipe = create_recipe(&ic, siwe, catts);
+
+    let response = http_get(&ic, catts, &format!("/recipes/{}/badge.svg", recipe.name));
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.header("Content-Type"), Some("image/svg+xml"));
+    assert!(response.header("IC-Certificate").is_some());
+    assert!(String::from_utf8(response.body).unwrap().contains("draft"));
+}
+
+#[test]
+fn http_request_recipe_list() {
+    let (ic, siwe, catts) = setup();
+    create_recipe(&ic, siwe, catts);
+
+    let response = http_get(&ic, catts, "/recipes?page=1");
+    assert_eq!(response.status_code, 200);
+    let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn http_request_not_found() {
+    let (ic, _, catts) = setup();
+    assert_eq!(http_get(&ic, catts, "/recipes/unknown").status_code, 404);
+    assert_eq!(http_get(&ic, catts, "/unknown").status_code, 404);
+}
+
+#[test]
+fn http_request_invalid_address() {
+    let (ic, _, catts) = setup();
+    let response = http_get(&ic, catts, "/users/not-an-address/runs");
+    assert_eq!(response.status_code, 400);
+}
@@ -0,0 +1,120 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup, setup_ecdsa, update},
+    recipes::{create_published_recipe, recipe_eu_gtc_passport_clone},
+    siwe::full_login,
+    types::{Recipe, Role, RoleAssignment, RpcResult},
+};
+use ic_agent::Identity;
+
+fn create_recipe(
+    ic: &pocket_ic::PocketIc,
+    catts: Principal,
+    sender: Principal,
+    name: &str,
+    dedicated_attester: bool,
+) -> Recipe {
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = name.to_string();
+    details.dedicated_attester = Some(dedicated_attester);
+    let response: RpcResult<Recipe> = catts_update(
+        ic,
+        catts,
+        sender,
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    response.unwrap_ok().clone()
+}
+
+/// Every recipe with a dedicated attester gets its own address, derived from the recipe id.
+#[test]
+fn recipe_attester_derived_addresses() {
+    let (ic, siwe, catts) = setup_ecdsa();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let sender = identity.sender().unwrap();
+
+    let recipe_a = create_recipe(&ic, catts, sender, "attester-a", true);
+    let recipe_b = create_recipe(&ic, catts, sender, "attester-b", true);
+    let shared = create_recipe(&ic, catts, sender, "attester-shared", false);
+
+    let canister_address: String = update(
+        &ic,
+        catts,
+        sender,
+        "canister_eth_address",
+        encode_one(()).unwrap(),
+    )
+    .unwrap();
+
+    let attester_a = recipe_a.attester.unwrap();
+    let attester_b = recipe_b.attester.unwrap();
+    assert_eq!(attester_a.len(), 42);
+    assert_eq!(attester_b.len(), 42);
+    assert_ne!(attester_a, attester_b);
+    assert_ne!(attester_a.to_lowercase(), canister_address.to_lowercase());
+    assert_ne!(attester_b.to_lowercase(), canister_address.to_lowercase());
+    assert_eq!(shared.attester, None);
+}
+
+/// Auditors can check attester balances without signing in with an Ethereum address.
+#[test]
+fn recipe_attester_balance_access() {
+    let (ic, siwe, catts) = setup();
+    let (_, creator) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, creator.sender().unwrap());
+
+    let response: RpcResult<candid::Nat> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "recipe_attester_balance",
+        encode_args((recipe.id, 31337_u32)).unwrap(),
+    );
+    // The anonymous principal controls the test canister and passes every role check
+    assert_eq!(response.unwrap_err().code, 400);
+
+    let (_, other) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<candid::Nat> = catts_update(
+        &ic,
+        catts,
+        other.sender().unwrap(),
+        "recipe_attester_balance",
+        encode_args((recipe.id, 31337_u32)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+
+    let auditor = Principal::from_slice(&[7; 29]);
+    let response: RpcResult<candid::Nat> = catts_update(
+        &ic,
+        catts,
+        auditor,
+        "recipe_attester_balance",
+        encode_args((recipe.id, 31337_u32)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+
+    let response: RpcResult<RoleAssignment> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "role_grant",
+        encode_args((auditor, Role::Auditor)).unwrap(),
+    );
+    assert!(response.is_ok());
+
+    // The recipe uses the canister attester, there is no dedicated balance to check
+    let response: RpcResult<candid::Nat> = catts_update(
+        &ic,
+        catts,
+        auditor,
+        "recipe_attester_balance",
+        encode_args((recipe.id, 31337_u32)).unwrap(),
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 400);
+    assert_eq!(
+        error.details.as_deref(),
+        Some("Recipe has no dedicated attester.")
+    );
+}
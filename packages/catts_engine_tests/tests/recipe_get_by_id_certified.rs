@@ -0,0 +1,96 @@
+use candid::{encode_args, encode_one};
+use catts_engine_tests::{
+    certification::{verify_certificate_signature, verify_certified_response},
+    common::{catts_query, catts_update, setup, setup_nns},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{CertifiedResponse, Recipe, RpcResult},
+};
+use ic_agent::Identity;
+
+#[test]
+fn recipe_get_by_id_certified() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let response: RpcResult<CertifiedResponse> = catts_query(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_get_by_id_certified",
+        encode_one(recipe.id).unwrap(),
+    );
+    let certified = response.unwrap_ok();
+
+    assert_eq!(certified.data.len(), 1);
+    verify_certified_response(certified, catts, "recipes", &[recipe.id.to_vec()]).unwrap();
+}
+
+#[test]
+fn recipe_get_by_id_certified_tampered() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let response: RpcResult<CertifiedResponse> = catts_query(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_get_by_id_certified",
+        encode_one(recipe.id).unwrap(),
+    );
+    let mut certified = response.unwrap_ok().clone();
+    certified.data[0].push(0);
+
+    assert!(
+        verify_certified_response(&certified, catts, "recipes", &[recipe.id.to_vec()]).is_err()
+    );
+}
+
+#[test]
+fn recipe_get_by_id_certified_signature() {
+    let (ic, siwe, catts) = setup_nns();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok();
+
+    let response: RpcResult<CertifiedResponse> = catts_query(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_get_by_id_certified",
+        encode_one(recipe.id).unwrap(),
+    );
+    let certified = response.unwrap_ok();
+    let root_key = ic.root_key().unwrap();
+
+    verify_certificate_signature(&certified.certificate, catts, &root_key).unwrap();
+    verify_certified_response(certified, catts, "recipes", &[recipe.id.to_vec()]).unwrap();
+
+    // A certificate verified against another root key is rejected
+    let mut other_root_key = root_key.clone();
+    let last = other_root_key.len() - 1;
+    other_root_key[last] ^= 1;
+    assert!(verify_certificate_signature(&certified.certificate, catts, &other_root_key).is_err());
+}
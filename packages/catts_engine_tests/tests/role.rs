@@ -0,0 +1,128 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    recipes::{create_published_recipe, recipe_eu_gtc_passport_clone},
+    siwe::full_login,
+    types::{Recipe, RecipePublishState, Role, RoleAssignment, RpcResult},
+};
+use ic_agent::Identity;
+
+#[test]
+fn role_grant_and_revoke() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let principal = identity.sender().unwrap();
+
+    // Roles are managed by controllers, the anonymous principal controls the test canister
+    let response: RpcResult<RoleAssignment> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "role_grant",
+        encode_args((principal, Role::Auditor)).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().roles, vec![Role::Auditor]);
+
+    // Auditors can list roles
+    let response: RpcResult<Vec<RoleAssignment>> =
+        catts_query(&ic, catts, principal, "role_list", encode_one(()).unwrap());
+    let assignments = response.unwrap_ok();
+    assert_eq!(assignments.len(), 1);
+    assert_eq!(assignments[0].principal, principal);
+
+    let response: RpcResult<RoleAssignment> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "role_revoke",
+        encode_args((principal, Role::Auditor)).unwrap(),
+    );
+    assert!(response.unwrap_ok().roles.is_empty());
+
+    let response: RpcResult<Vec<RoleAssignment>> =
+        catts_query(&ic, catts, principal, "role_list", encode_one(()).unwrap());
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+#[test]
+fn role_grant_not_controller() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let principal = identity.sender().unwrap();
+
+    let response: RpcResult<RoleAssignment> = catts_update(
+        &ic,
+        catts,
+        principal,
+        "role_grant",
+        encode_args((principal, Role::Admin)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+#[test]
+fn recipe_moderation() {
+    let (ic, siwe, catts) = setup();
+    let (_, creator) = full_login(&ic, siwe, catts, None);
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let principal = identity.sender().unwrap();
+    let published = create_published_recipe(&ic, catts, creator.sender().unwrap());
+
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = "draft".to_string();
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        creator.sender().unwrap(),
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    );
+    let draft = response.unwrap_ok().clone();
+
+    // Other users can't moderate recipes
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        principal,
+        "recipe_unpublish",
+        encode_one(published.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        principal,
+        "recipe_delete",
+        encode_one(draft.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+
+    let response: RpcResult<RoleAssignment> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "role_grant",
+        encode_args((principal, Role::Moderator)).unwrap(),
+    );
+    assert!(response.is_ok());
+
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        principal,
+        "recipe_unpublish",
+        encode_one(published.id).unwrap(),
+    );
+    assert_eq!(
+        response.unwrap_ok().publish_state,
+        RecipePublishState::Unpublished
+    );
+    let response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        principal,
+        "recipe_delete",
+        encode_one(draft.id).unwrap(),
+    );
+    assert!(response.is_ok());
+}
@@ -0,0 +1,204 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_update, local_chain_config, setup},
+    recipes::{create_published_recipe, recipe_eu_gtc_passport_clone},
+    replica::{replica_update, HttpStandIn, Replica},
+    siwe::{create_wallet, full_login},
+    types::{ChainConfig, Recipe, RpcResult, Run, RunMode, RunRecipientInput},
+};
+use ethers::utils::hash_message;
+use ic_agent::Identity;
+
+const CHAIN_ID: u32 = 31337;
+const REPLICA_CHAIN_ID: u32 = 31339;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+fn recipient_proof_message(creator: &str, recipient: &str, chain_id: u32) -> String {
+    format!(
+        "Receive catts attestations for {} at {} on chain {}",
+        creator.to_lowercase(),
+        recipient.to_lowercase(),
+        chain_id
+    )
+}
+
+/// EOA proofs are recovered in the canister, no RPC calls needed.
+#[test]
+fn run_create_recipient_eoa_proof() {
+    let (ic, siwe, catts) = setup();
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(local_chain_config(
+            CHAIN_ID,
+            "http://127.0.0.1:8545",
+            ZERO_ADDRESS,
+            ZERO_ADDRESS,
+        ))
+        .unwrap(),
+    );
+    assert!(response.is_ok());
+
+    let (creator, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+
+    let (recipient_wallet, recipient) = create_wallet();
+    let signature = recipient_wallet
+        .sign_hash(hash_message(recipient_proof_message(
+            &creator, &recipient, CHAIN_ID,
+        )))
+        .unwrap();
+
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_create",
+        encode_args((
+            recipe.id,
+            CHAIN_ID,
+            Some(RunMode::Offchain),
+            Some(RunRecipientInput {
+                address: recipient.clone(),
+                signature: format!("0x{}", signature),
+            }),
+        ))
+        .unwrap(),
+    );
+    let run = response.unwrap_ok();
+    assert_eq!(run.recipient, Some(recipient.to_lowercase()));
+}
+
+#[test]
+fn run_create_recipient_malformed_proof() {
+    let (ic, siwe, catts) = setup();
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(local_chain_config(
+            CHAIN_ID,
+            "http://127.0.0.1:8545",
+            ZERO_ADDRESS,
+            ZERO_ADDRESS,
+        ))
+        .unwrap(),
+    );
+    assert!(response.is_ok());
+
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+    let (_, recipient) = create_wallet();
+
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_create",
+        encode_args((
+            recipe.id,
+            CHAIN_ID,
+            Some(RunMode::Offchain),
+            Some(RunRecipientInput {
+                address: recipient,
+                signature: "not a signature".to_string(),
+            }),
+        ))
+        .unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+/// A proof signed by another wallet is rejected once the node reports that the recipient
+/// has no code, `isValidSignature` is never called.
+#[tokio::test]
+#[ignore = "requires a local replica, run with make test-replica"]
+async fn run_create_recipient_wrong_signer() {
+    let replica = Replica::from_env();
+
+    let node = HttpStandIn::start_with(|request| {
+        let payload: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        let result = match payload["method"].as_str() {
+            Some("eth_getCode") => "0x",
+            _ => "0x0000000000000000000000000000000000000000000000000000000000000000",
+        };
+        (
+            200,
+            serde_json::json!({ "id": 1, "jsonrpc": "2.0", "result": result }).to_string(),
+        )
+    });
+    let controller = replica.controller().await;
+    let config = local_chain_config(REPLICA_CHAIN_ID, &node.url, ZERO_ADDRESS, ZERO_ADDRESS);
+    let response: RpcResult<ChainConfig> = replica_update(
+        &controller,
+        replica.catts,
+        "chain_config_upsert",
+        encode_one(config).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    let (creator, _, agent) = replica.login().await;
+
+    // Recipe names are unique and the replica keeps its state between test runs
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = format!("recipient-proof-{}", rand::random::<u32>());
+    let response: RpcResult<Recipe> = replica_update(
+        &agent,
+        replica.catts,
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    )
+    .await;
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = replica_update(
+        &agent,
+        replica.catts,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    let (_, recipient) = create_wallet();
+    let (other_wallet, _) = create_wallet();
+    let signature = other_wallet
+        .sign_hash(hash_message(recipient_proof_message(
+            &creator,
+            &recipient,
+            REPLICA_CHAIN_ID,
+        )))
+        .unwrap();
+
+    let response: RpcResult<Run> = replica_update(
+        &agent,
+        replica.catts,
+        "run_create",
+        encode_args((
+            recipe.id,
+            REPLICA_CHAIN_ID,
+            Some(RunMode::Offchain),
+            Some(RunRecipientInput {
+                address: recipient,
+                signature: format!("0x{}", signature),
+            }),
+        ))
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.unwrap_err().code, 401);
+
+    let methods: Vec<String> = node
+        .requests()
+        .iter()
+        .map(|request| {
+            let payload: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            payload["method"].as_str().unwrap_or_default().to_string()
+        })
+        .collect();
+    assert!(methods.iter().any(|method| method == "eth_getCode"));
+    assert!(!methods.iter().any(|method| method == "eth_call"));
+}
@@ -0,0 +1,170 @@
+use candid::{decode_one, encode_args, encode_one};
+use catts_engine_tests::{
+    common::local_chain_config,
+    devnet::{Devnet, DEVNET_CHAIN_ID},
+    recipes::recipe_eu_gtc_passport_clone,
+    replica::{replica_query, replica_update, Replica},
+    types::{ChainConfig, Recipe, RpcResult, Run, RunMode, RunRecipientInput},
+};
+use ethers::{types::U256, utils::parse_ether};
+use std::time::{Duration, Instant};
+
+/// Creates an on-chain run on a custom chain, pays for it using the payment contract and
+/// waits for the canister to attest it. The recipe query still goes through the query
+/// proxy, the replica needs internet access.
+#[tokio::test]
+#[ignore = "requires a local replica and an anvil devnet, run with make test-replica"]
+async fn run_devnet_paid_and_attested() {
+    let replica = Replica::from_env();
+    let devnet = Devnet::from_env();
+
+    let controller = replica.controller().await;
+    let config = local_chain_config(
+        DEVNET_CHAIN_ID,
+        &devnet.rpc_url,
+        &devnet.eas_contract,
+        &devnet.payment_contract,
+    );
+    let response: RpcResult<ChainConfig> = replica_update(
+        &controller,
+        replica.catts,
+        "chain_config_upsert",
+        encode_one(config).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    // The canister pays for the attestation transaction
+    let response = controller
+        .update(&replica.catts, "canister_eth_address")
+        .with_arg(encode_one(()).unwrap())
+        .call_and_wait()
+        .await
+        .unwrap();
+    let canister_address = decode_one::<Result<String, String>>(&response)
+        .unwrap()
+        .unwrap();
+    devnet
+        .fund(&canister_address, parse_ether(1).unwrap())
+        .await;
+
+    // Schemas can only be registered once and the devnet keeps its state between test runs
+    let suffix = rand::random::<u32>();
+    let schema = format!("uint256 score_{}", suffix);
+    devnet.register_schema(&schema, false).await;
+
+    let (creator, _, agent) = replica.login().await;
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = format!("devnet-{}", suffix);
+    details.schema = schema;
+    details.processor = format!(
+        r#"return JSON.stringify([{{ name: "score_{}", type: "uint256", value: 1 }}]);"#,
+        suffix
+    );
+    let response: RpcResult<Recipe> = replica_update(
+        &agent,
+        replica.catts,
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    )
+    .await;
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = replica_update(
+        &agent,
+        replica.catts,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    let response: RpcResult<Run> = replica_update(
+        &agent,
+        replica.catts,
+        "run_create",
+        encode_args((
+            recipe.id,
+            DEVNET_CHAIN_ID,
+            Some(RunMode::Onchain),
+            None::<RunRecipientInput>,
+        ))
+        .unwrap(),
+    )
+    .await;
+    let run = response.unwrap_ok().clone();
+    let user_fee = U256::from_dec_str(&run.user_fee.clone().unwrap().0.to_string()).unwrap();
+
+    let payments = devnet.contract(
+        &devnet.payment_contract,
+        &["function payRun(bytes12 id) payable"],
+    );
+    let receipt = payments
+        .method::<_, ()>("payRun", run.id)
+        .unwrap()
+        .value(user_fee)
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response: RpcResult<Run> = replica_update(
+        &agent,
+        replica.catts,
+        "run_register_payment",
+        encode_args((
+            run.id,
+            format!("{:?}", receipt.transaction_hash),
+            receipt.block_number.unwrap().as_u64() as u128,
+        ))
+        .unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    // Payment confirmation, the attestation transaction and the uid lookup each take a
+    // few poll intervals
+    let started = Instant::now();
+    let run = loop {
+        let response: RpcResult<Run> = replica_query(
+            &agent,
+            replica.catts,
+            "run_get",
+            encode_one(run.id).unwrap(),
+        )
+        .await;
+        let run = response.unwrap_ok().clone();
+        assert_eq!(run.error, None);
+        if run.attestation_uid.is_some() {
+            break run;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(300),
+            "Run was not attested within 5 minutes"
+        );
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    };
+
+    assert_eq!(
+        run.payment_transaction_hash,
+        Some(format!("{:?}", receipt.transaction_hash))
+    );
+    assert_eq!(run.recipient, Some(creator.to_lowercase()));
+
+    let eas = devnet.contract(
+        &devnet.eas_contract,
+        &["function isAttestationValid(bytes32 uid) view returns (bool)"],
+    );
+    let uid: [u8; 32] = hex::decode(run.attestation_uid.unwrap().trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let is_valid: bool = eas
+        .method::<_, bool>("isAttestationValid", uid)
+        .unwrap()
+        .call()
+        .await
+        .unwrap();
+    assert!(is_valid);
+}
@@ -0,0 +1,79 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, local_chain_config, setup},
+    recipes::create_published_recipe,
+    siwe::full_login,
+    types::{ChainConfig, RpcResult, Run, RunMode, RunRecipientInput},
+};
+use ic_agent::Identity;
+
+const CHAIN_ID: u32 = 31337;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// The creator gets through the access check, the run has no private data until it has
+/// been attested.
+#[test]
+fn run_get_private_data_creator_only() {
+    let (ic, siwe, catts) = setup();
+    let response: RpcResult<ChainConfig> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "chain_config_upsert",
+        encode_one(local_chain_config(
+            CHAIN_ID,
+            "http://127.0.0.1:8545",
+            ZERO_ADDRESS,
+            ZERO_ADDRESS,
+        ))
+        .unwrap(),
+    );
+    assert!(response.is_ok());
+
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let recipe = create_published_recipe(&ic, catts, identity.sender().unwrap());
+    let response: RpcResult<Run> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_create",
+        encode_args((
+            recipe.id,
+            CHAIN_ID,
+            Some(RunMode::Offchain),
+            None::<RunRecipientInput>,
+        ))
+        .unwrap(),
+    );
+    let run = response.unwrap_ok();
+
+    let response: RpcResult<()> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "run_get_private_data",
+        encode_one(run.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+
+    let (_, identity2) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<()> = catts_query(
+        &ic,
+        catts,
+        identity2.sender().unwrap(),
+        "run_get_private_data",
+        encode_one(run.id).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+
+    let response: RpcResult<()> = catts_query(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "run_get_private_data",
+        encode_one(run.id).unwrap(),
+    );
+    let error = response.unwrap_err();
+    assert_eq!(error.code, 404);
+    assert_eq!(error.details.as_deref(), Some("Run has no private data"));
+}
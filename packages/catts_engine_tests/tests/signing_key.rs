@@ -0,0 +1,85 @@
+use candid::{encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup, upgrade},
+    siwe::full_login,
+    types::{RpcResult, SigningKeys},
+};
+use ic_agent::Identity;
+
+#[test]
+fn signing_key_rotate_not_controller() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+
+    let response: RpcResult<SigningKeys> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "signing_key_rotate",
+        encode_one("key_1".to_string()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+#[test]
+fn signing_key_rotate_active_key() {
+    let (ic, _, catts) = setup();
+
+    // The anonymous principal controls the test canister, test_key is the installed key
+    let response: RpcResult<SigningKeys> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "signing_key_rotate",
+        encode_one("test_key".to_string()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 409);
+}
+
+#[test]
+fn signing_key_activate_without_rotation() {
+    let (ic, _, catts) = setup();
+
+    let response: RpcResult<SigningKeys> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "signing_key_activate",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+
+    let response: RpcResult<SigningKeys> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "signing_key_cancel",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+}
+
+#[test]
+fn signing_key_status_requires_auditor() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+
+    let response: RpcResult<SigningKeys> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "signing_key_status",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 403);
+}
+
+/// The test key isn't available in PocketIC, so the active key is never recorded. The key
+/// from the settings can't change until it is.
+#[test]
+fn signing_key_settings_change_without_history() {
+    let (ic, siwe, catts) = setup();
+
+    assert!(upgrade(&ic, catts, siwe, "key_1").is_err());
+    assert!(upgrade(&ic, catts, siwe, "test_key").is_ok());
+}
@@ -0,0 +1,132 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    siwe::full_login,
+    types::{
+        ChangeLogAction, ChangeLogFilter, ChangeLogResponse, ChangeLogSnapshot,
+        ChangeLogSnapshotEntitiesResponse, ChangeLogTypeName, EntityVersion, RpcResult, User,
+        UserProfileInput,
+    },
+};
+use ic_agent::Identity;
+
+#[test]
+fn user_export() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let response: RpcResult<String> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_export",
+        encode_one(()).unwrap(),
+    );
+    let export: serde_json::Value = serde_json::from_str(response.unwrap_ok()).unwrap();
+    assert_eq!(export["user"]["eth_address"], address.to_lowercase());
+    assert_eq!(export["runs"], serde_json::json!([]));
+    assert_eq!(export["recipes"], serde_json::json!([]));
+}
+
+#[test]
+fn user_delete() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let profile = UserProfileInput {
+        display_name: Some("Alice".to_string()),
+        ..Default::default()
+    };
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_update_profile",
+        encode_one(profile).unwrap(),
+    );
+    response.unwrap_ok();
+
+    // The anonymous principal is the controller of the test canister
+    let response: RpcResult<ChangeLogSnapshot> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_create",
+        encode_one(()).unwrap(),
+    );
+    let snapshot = response.unwrap_ok().clone();
+
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_delete",
+        encode_one(()).unwrap(),
+    );
+    response.unwrap_ok();
+
+    let response: RpcResult<User> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "user_get_by_eth_address",
+        encode_one(address.clone()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 404);
+
+    // The profile is scrubbed from the history before the delete is recorded
+    let filter = ChangeLogFilter {
+        type_name: Some(ChangeLogTypeName::User),
+        id: Some(address.to_lowercase()),
+        action: None,
+    };
+    let change_log: RpcResult<ChangeLogResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log",
+        encode_args((0_u32, None::<u32>, Some(filter))).unwrap(),
+    );
+    let items = &change_log.unwrap_ok().data;
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].data.action, ChangeLogAction::Update);
+    assert_eq!(items[2].data.action, ChangeLogAction::Delete);
+    assert!(items.iter().all(|item| !item.data.patch.contains("Alice")));
+
+    let response: RpcResult<Vec<EntityVersion>> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "entity_history",
+        encode_args((ChangeLogTypeName::User, address.to_lowercase())).unwrap(),
+    );
+    let versions = response.unwrap_ok();
+    assert_eq!(versions.len(), 3);
+    assert!(versions
+        .iter()
+        .all(|version| !version.data.contains("Alice")));
+    assert_eq!(versions[2].data, "null");
+
+    let response: RpcResult<ChangeLogSnapshotEntitiesResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_entities",
+        encode_args((snapshot.index, 0_u32, None::<u32>)).unwrap(),
+    );
+    let entities = &response.unwrap_ok().entities;
+    assert_eq!(entities.len(), 1);
+    assert_eq!(entities[0].type_name, ChangeLogTypeName::User);
+    assert!(!entities[0].data.contains("Alice"));
+}
+
+#[test]
+fn user_delete_unauthorized() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "user_delete",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
@@ -0,0 +1,194 @@
+use candid::{encode_args, encode_one};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    siwe::{create_wallet, full_login, siwe_login},
+    types::{RpcResult, User},
+};
+use ethers::{signers::LocalWallet, utils::hash_message};
+use ic_agent::Identity;
+
+fn sign_link_message(
+    wallet: &LocalWallet,
+    address: &str,
+    user_address: &str,
+    link_nonce: u64,
+) -> String {
+    let message = format!(
+        "Link address {} to the catts account of {} with nonce {}",
+        address.to_lowercase(),
+        user_address.to_lowercase(),
+        link_nonce
+    );
+    let signature = wallet
+        .sign_hash(hash_message(message.as_bytes()))
+        .unwrap()
+        .to_string();
+    format!("0x{}", signature)
+}
+
+#[test]
+fn user_link_address() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (wallet, linked_address) = create_wallet();
+
+    let signature = sign_link_message(&wallet, &linked_address, &address, 0);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address.clone(), signature)).unwrap(),
+    );
+    let user = response.unwrap_ok();
+    assert_eq!(
+        user.linked_addresses,
+        Some(vec![linked_address.to_lowercase()])
+    );
+    assert_eq!(user.link_nonce, Some(1));
+
+    // The linked address resolves to the same user
+    let response: RpcResult<User> = catts_query(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_get_by_eth_address",
+        encode_one(linked_address.clone()).unwrap(),
+    );
+    assert_eq!(response.unwrap_ok().eth_address, address.to_lowercase());
+
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_set_primary_address",
+        encode_one(linked_address.clone()).unwrap(),
+    );
+    assert_eq!(
+        response.unwrap_ok().primary_address,
+        Some(linked_address.to_lowercase())
+    );
+
+    // Unlinking the primary address resets it to the sign in address
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_unlink_address",
+        encode_one(linked_address.clone()).unwrap(),
+    );
+    let user = response.unwrap_ok();
+    assert_eq!(user.linked_addresses, None);
+    assert_eq!(user.primary_address, None);
+}
+
+#[test]
+fn user_link_address_invalid_signature() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (_, linked_address) = create_wallet();
+    let (other_wallet, _) = create_wallet();
+
+    let signature = sign_link_message(&other_wallet, &linked_address, &address, 0);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address, signature)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn user_link_address_signature_reuse() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (wallet, linked_address) = create_wallet();
+
+    let signature = sign_link_message(&wallet, &linked_address, &address, 0);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address.clone(), signature.clone())).unwrap(),
+    );
+    response.unwrap_ok();
+
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_unlink_address",
+        encode_one(linked_address.clone()).unwrap(),
+    );
+    response.unwrap_ok();
+
+    // The nonce was burned, the signature can't link the address again
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address, signature)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
+
+#[test]
+fn user_link_address_already_in_use() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (other_address, other_identity) = full_login(&ic, siwe, catts, None);
+    let (wallet, linked_address) = create_wallet();
+
+    let signature = sign_link_message(&wallet, &linked_address, &address, 0);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address.clone(), signature)).unwrap(),
+    );
+    response.unwrap_ok();
+
+    // An address can only be linked to one user
+    let signature = sign_link_message(&wallet, &linked_address, &other_address, 0);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        other_identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address, signature)).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 409);
+}
+
+#[test]
+fn user_create_with_linked_address() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let (wallet, linked_address) = create_wallet();
+
+    let signature = sign_link_message(&wallet, &linked_address, &address, 0);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_link_address",
+        encode_args((linked_address.clone(), signature)).unwrap(),
+    );
+    response.unwrap_ok();
+
+    // The linked address can't sign up as a separate user
+    let (_, linked_identity) = siwe_login(&ic, siwe, wallet, None);
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        linked_identity.sender().unwrap(),
+        "user_create",
+        encode_one(()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 409);
+}
@@ -0,0 +1,81 @@
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_query, catts_update, setup},
+    siwe::full_login,
+    types::{
+        ChangeLogAction, ChangeLogFilter, ChangeLogResponse, ChangeLogTypeName, RpcResult, User,
+        UserProfileInput,
+    },
+};
+use ic_agent::Identity;
+
+#[test]
+fn user_update_profile() {
+    let (ic, siwe, catts) = setup();
+    let (address, identity) = full_login(&ic, siwe, catts, None);
+    let profile = UserProfileInput {
+        display_name: Some("Alice".to_string()),
+        avatar_url: Some("https://example.com/avatar.png".to_string()),
+        bio: Some("Building on catts".to_string()),
+        links: Some(vec!["https://example.com".to_string()]),
+    };
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_update_profile",
+        encode_one(profile).unwrap(),
+    );
+    let user = response.unwrap_ok();
+    assert_eq!(user.display_name, Some("Alice".to_string()));
+    assert!(user.created.is_some());
+
+    // User create and update are both in the change log
+    let filter = ChangeLogFilter {
+        type_name: Some(ChangeLogTypeName::User),
+        id: Some(address.to_lowercase()),
+        action: None,
+    };
+    let change_log: RpcResult<ChangeLogResponse> = catts_query(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log",
+        encode_args((0_u32, None::<u32>, Some(filter))).unwrap(),
+    );
+    let items = &change_log.unwrap_ok().data;
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].data.action, ChangeLogAction::Create);
+    assert_eq!(items[1].data.action, ChangeLogAction::Update);
+}
+
+#[test]
+fn user_update_profile_invalid_url() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let profile = UserProfileInput {
+        avatar_url: Some("http://example.com/avatar.png".to_string()),
+        ..Default::default()
+    };
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "user_update_profile",
+        encode_one(profile).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 400);
+}
+
+#[test]
+fn user_update_profile_unauthorized() {
+    let (ic, _, catts) = setup();
+    let response: RpcResult<User> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "user_update_profile",
+        encode_one(UserProfileInput::default()).unwrap(),
+    );
+    assert_eq!(response.unwrap_err().code, 401);
+}
@@ -0,0 +1,141 @@
+use candid::{encode_args, encode_one};
+use catts_engine_tests::{
+    common::local_chain_config,
+    recipes::recipe_eu_gtc_passport_clone,
+    replica::{replica_update, HttpStandIn, Replica},
+    types::{
+        ChainConfig, Recipe, RpcResult, Run, RunMode, RunRecipientInput, Webhook, WebhookEvent,
+        WebhookInput,
+    },
+};
+use ring::hmac;
+use std::{net::TcpListener, time::Duration};
+
+const CHAIN_ID: u32 = 31338;
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Returns a local url that refuses connections.
+fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port())
+}
+
+#[tokio::test]
+#[ignore = "requires a local replica, run with make test-replica"]
+async fn webhook_delivery_signed_and_retried() {
+    let replica = Replica::from_env();
+
+    // Payments on this chain can't be verified, registering one fails the run
+    let controller = replica.controller().await;
+    let config = local_chain_config(CHAIN_ID, &unreachable_url(), ZERO_ADDRESS, ZERO_ADDRESS);
+    let response: RpcResult<ChainConfig> = replica_update(
+        &controller,
+        replica.catts,
+        "chain_config_upsert",
+        encode_one(config).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    // The first delivery fails, the retry succeeds
+    let stand_in = HttpStandIn::start(vec![500, 200]);
+
+    let (_, _, agent) = replica.login().await;
+    let response: RpcResult<Webhook> = replica_update(
+        &agent,
+        replica.catts,
+        "webhook_create",
+        encode_one(WebhookInput {
+            url: format!("{}/webhook", stand_in.url),
+            events: vec![WebhookEvent::Failed],
+            recipe_id: None,
+        })
+        .unwrap(),
+    )
+    .await;
+    let webhook = response.unwrap_ok().clone();
+
+    // Recipe names are unique and the replica keeps its state between test runs
+    let (mut details, readme) = recipe_eu_gtc_passport_clone();
+    details.name = format!("webhook-delivery-{}", rand::random::<u32>());
+    let response: RpcResult<Recipe> = replica_update(
+        &agent,
+        replica.catts,
+        "recipe_create",
+        encode_args((details, readme)).unwrap(),
+    )
+    .await;
+    let recipe = response.unwrap_ok().clone();
+    let response: RpcResult<Recipe> = replica_update(
+        &agent,
+        replica.catts,
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    let response: RpcResult<Run> = replica_update(
+        &agent,
+        replica.catts,
+        "run_create",
+        encode_args((
+            recipe.id,
+            CHAIN_ID,
+            Some(RunMode::Offchain),
+            None::<RunRecipientInput>,
+        ))
+        .unwrap(),
+    )
+    .await;
+    let run = response.unwrap_ok().clone();
+    let response: RpcResult<Run> = replica_update(
+        &agent,
+        replica.catts,
+        "run_register_payment",
+        encode_args((run.id, format!("0x{}", "ab".repeat(32)), 1_u128)).unwrap(),
+    )
+    .await;
+    assert!(response.is_ok());
+
+    // Retries are 30 seconds apart, the failed run itself takes a few poll intervals
+    let requests = stand_in
+        .wait_for_requests(2, Duration::from_secs(120))
+        .await;
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, webhook.secret.as_bytes());
+    for request in requests.iter() {
+        assert_eq!(request.path, "/webhook");
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+
+        let timestamp = request.header("X-Catts-Timestamp").unwrap();
+        let signature = request.header("X-Catts-Signature").unwrap();
+        let expected = hmac::sign(&key, format!("{}.{}", timestamp, request.body).as_bytes());
+        assert_eq!(
+            signature,
+            format!("sha256={}", hex::encode(expected.as_ref()))
+        );
+
+        let payload: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(payload["event"], "run.failed");
+        assert_eq!(payload["run"]["id"], format!("0x{}", hex::encode(run.id)));
+    }
+
+    // The retry delivers the same payload, signed again with a new timestamp
+    assert_eq!(requests[0].body, requests[1].body);
+    let first: u32 = requests[0]
+        .header("X-Catts-Timestamp")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let second: u32 = requests[1]
+        .header("X-Catts-Timestamp")
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(second >= first + 30);
+
+    // Delivered, no further retries
+    tokio::time::sleep(Duration::from_secs(70)).await;
+    assert_eq!(stand_in.requests().len(), 2);
+}
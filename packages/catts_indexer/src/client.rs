@@ -0,0 +1,66 @@
+use crate::types::{
+    ChangeLogResponse, ChangeLogSnapshot, ChangeLogSnapshotEntitiesResponse, EngineResult,
+    HttpError,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use candid::{decode_one, encode_args, encode_one, CandidType, Principal};
+use ic_agent::Agent;
+use serde::Deserialize;
+
+/// The change log queries of the engine used by the indexer.
+#[async_trait(?Send)]
+pub trait EngineClient {
+    async fn query(&self, method: &str, args: Vec<u8>) -> Result<Vec<u8>>;
+
+    async fn change_log(
+        &self,
+        start_index: u32,
+        limit: Option<u32>,
+    ) -> Result<Result<ChangeLogResponse, HttpError>> {
+        // The trailing filter argument is optional and left out
+        let args = encode_args((start_index, limit))?;
+        decode_result(&self.query("change_log", args).await?)
+    }
+
+    async fn change_log_snapshot_latest(&self) -> Result<Option<ChangeLogSnapshot>> {
+        let response = self
+            .query("change_log_snapshot_latest", encode_one(())?)
+            .await?;
+        Ok(decode_result(&response)?.ok())
+    }
+
+    async fn change_log_snapshot_entities(
+        &self,
+        snapshot_index: u32,
+        start_index: u32,
+    ) -> Result<Result<ChangeLogSnapshotEntitiesResponse, HttpError>> {
+        let args = encode_args((snapshot_index, start_index, None::<u32>))?;
+        decode_result(&self.query("change_log_snapshot_entities", args).await?)
+    }
+}
+
+fn decode_result<T: CandidType + for<'de> Deserialize<'de>>(
+    bytes: &[u8],
+) -> Result<Result<T, HttpError>> {
+    let result: EngineResult<T> = decode_one(bytes)?;
+    Ok(result.into())
+}
+
+/// Queries the engine using `ic-agent`.
+pub struct AgentEngineClient {
+    pub agent: Agent,
+    pub canister_id: Principal,
+}
+
+#[async_trait(?Send)]
+impl EngineClient for AgentEngineClient {
+    async fn query(&self, method: &str, args: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(self
+            .agent
+            .query(&self.canister_id, method)
+            .with_arg(args)
+            .call()
+            .await?)
+    }
+}
@@ -0,0 +1,181 @@
+use crate::types::{
+    ChangeLogAction, ChangeLogSnapshotEntity, ChangeLogTypeName, IndexedChangeLogItem,
+};
+use anyhow::{anyhow, Result};
+use json_patch::Patch;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde_json::Value;
+use std::path::Path;
+
+const ENTITY_TABLES: [&str; 3] = ["recipes", "runs", "users"];
+
+/// SQLite mirror of the engine state. Each entity table stores the full JSON state of the
+/// entities, use `json_extract` to query individual fields.
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = Self {
+            conn: Connection::open(path)?,
+        };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        let db = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        for table in ENTITY_TABLES {
+            self.conn.execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS {table} (
+                    id TEXT PRIMARY KEY,
+                    data TEXT NOT NULL,
+                    change_log_index INTEGER NOT NULL
+                );"
+            ))?;
+        }
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cursor (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                next_index INTEGER NOT NULL
+            );",
+        )?;
+        Ok(())
+    }
+
+    /// The index of the next change log entry to apply, `None` for a new database.
+    pub fn cursor(&self) -> Result<Option<u32>> {
+        Ok(self
+            .conn
+            .query_row("SELECT next_index FROM cursor WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    pub fn get(&self, type_name: &ChangeLogTypeName, id: &str) -> Result<Option<Value>> {
+        get_entity(&self.conn, type_name, id)
+    }
+
+    pub fn count(&self, type_name: &ChangeLogTypeName) -> Result<u32> {
+        Ok(self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", type_name.table()),
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Applies change log items and moves the cursor to `next_index`, in one transaction so
+    /// that the indexer can resume after a restart.
+    pub fn apply_items(&mut self, items: &[IndexedChangeLogItem], next_index: u32) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for item in items {
+            apply_item(&tx, item)?;
+        }
+        set_cursor(&tx, next_index)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replaces all entities with the state of a snapshot.
+    pub fn start_snapshot(&mut self) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for table in ENTITY_TABLES {
+            tx.execute(&format!("DELETE FROM {table}"), [])?;
+        }
+        tx.execute("DELETE FROM cursor", [])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn insert_snapshot_entities(
+        &mut self,
+        entities: &[ChangeLogSnapshotEntity],
+        snapshot_index: u32,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for entity in entities {
+            let data: Value = serde_json::from_str(&entity.data)?;
+            put_entity(&tx, &entity.type_name, &entity.id, &data, snapshot_index)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Completes a snapshot import, change log items are applied from the snapshot index.
+    pub fn finish_snapshot(&mut self, snapshot_index: u32) -> Result<()> {
+        set_cursor(&self.conn, snapshot_index)
+    }
+}
+
+fn get_entity(conn: &Connection, type_name: &ChangeLogTypeName, id: &str) -> Result<Option<Value>> {
+    let data: Option<String> = conn
+        .query_row(
+            &format!("SELECT data FROM {} WHERE id = ?1", type_name.table()),
+            params![id.to_lowercase()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+}
+
+fn put_entity(
+    conn: &Connection,
+    type_name: &ChangeLogTypeName,
+    id: &str,
+    data: &Value,
+    index: u32,
+) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (id, data, change_log_index) VALUES (?1, ?2, ?3)
+             ON CONFLICT(id) DO UPDATE SET data = excluded.data,
+             change_log_index = excluded.change_log_index",
+            type_name.table()
+        ),
+        params![id.to_lowercase(), data.to_string(), index],
+    )?;
+    Ok(())
+}
+
+fn set_cursor(conn: &Connection, next_index: u32) -> Result<()> {
+    conn.execute(
+        "INSERT INTO cursor (id, next_index) VALUES (0, ?1)
+         ON CONFLICT(id) DO UPDATE SET next_index = excluded.next_index",
+        params![next_index],
+    )?;
+    Ok(())
+}
+
+fn apply_item(tx: &Transaction, item: &IndexedChangeLogItem) -> Result<()> {
+    let IndexedChangeLogItem { index, data: item } = item;
+    match item.action {
+        ChangeLogAction::Create => {
+            let data: Value = serde_json::from_str(&item.patch)?;
+            put_entity(tx, &item.type_name, &item.id, &data, *index)?;
+        }
+        ChangeLogAction::Update => {
+            let mut data = get_entity(tx, &item.type_name, &item.id)?.ok_or_else(|| {
+                anyhow!("Update at index {} for unknown entity {}", index, item.id)
+            })?;
+            let patch: Patch = serde_json::from_str(&item.patch)?;
+            json_patch::patch(&mut data, &patch)?;
+            put_entity(tx, &item.type_name, &item.id, &data, *index)?;
+        }
+        ChangeLogAction::Delete => {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE id = ?1", item.type_name.table()),
+                params![item.id.to_lowercase()],
+            )?;
+        }
+    }
+    Ok(())
+}
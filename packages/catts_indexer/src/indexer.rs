@@ -0,0 +1,78 @@
+use crate::{client::EngineClient, db::Database, types::ChangeLogSnapshot};
+use anyhow::{bail, Result};
+
+const PAGE_SIZE: u32 = 500;
+
+/// Imports all entities of a snapshot, used for new databases and when the entries after
+/// the cursor have been compacted.
+async fn import_snapshot<C: EngineClient>(
+    client: &C,
+    db: &mut Database,
+    snapshot: &ChangeLogSnapshot,
+) -> Result<()> {
+    db.start_snapshot()?;
+
+    let mut start_index = 0;
+    loop {
+        let response = match client
+            .change_log_snapshot_entities(snapshot.index, start_index)
+            .await?
+        {
+            Ok(response) => response,
+            Err(err) => bail!("Failed to fetch snapshot entities: {:?}", err),
+        };
+        db.insert_snapshot_entities(&response.entities, snapshot.index)?;
+        match response.next_index {
+            Some(next_index) => start_index = next_index,
+            None => break,
+        }
+    }
+
+    db.finish_snapshot(snapshot.index)
+}
+
+/// Applies all new change log entries, returns the number of entries applied.
+pub async fn sync<C: EngineClient>(client: &C, db: &mut Database) -> Result<usize> {
+    let mut cursor = match db.cursor()? {
+        Some(cursor) => cursor,
+        None => match client.change_log_snapshot_latest().await? {
+            Some(snapshot) => {
+                import_snapshot(client, db, &snapshot).await?;
+                snapshot.index
+            }
+            None => 0,
+        },
+    };
+
+    let mut applied = 0;
+    loop {
+        let response = match client.change_log(cursor, Some(PAGE_SIZE)).await? {
+            Ok(response) => response,
+            Err(err) => {
+                // Entries after the cursor might have been compacted, continue from the
+                // latest snapshot if it is ahead of the cursor
+                match client.change_log_snapshot_latest().await? {
+                    Some(snapshot) if snapshot.index > cursor => {
+                        import_snapshot(client, db, &snapshot).await?;
+                        cursor = snapshot.index;
+                        continue;
+                    }
+                    _ => bail!("Failed to fetch change log: {:?}", err),
+                }
+            }
+        };
+
+        let next_index = response
+            .next_index
+            .unwrap_or(response.total_count.max(cursor));
+        db.apply_items(&response.data, next_index)?;
+        applied += response.data.len();
+        cursor = next_index;
+
+        if response.next_index.is_none() {
+            break;
+        }
+    }
+
+    Ok(applied)
+}
@@ -0,0 +1,4 @@
+pub mod client;
+pub mod db;
+pub mod indexer;
+pub mod types;
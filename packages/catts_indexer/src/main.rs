@@ -0,0 +1,42 @@
+use anyhow::Result;
+use candid::Principal;
+use catts_indexer::{client::AgentEngineClient, db::Database, indexer};
+use ic_agent::Agent;
+use std::{env, time::Duration};
+
+const DEFAULT_IC_URL: &str = "https://icp-api.io";
+const DEFAULT_DATABASE_PATH: &str = "catts.sqlite";
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 15;
+
+/// Configured using environment variables:
+/// - `CATTS_ENGINE_CANISTER_ID`, required
+/// - `IC_URL`, defaults to the IC mainnet
+/// - `DATABASE_PATH`, defaults to `catts.sqlite`
+/// - `POLL_INTERVAL_SECS`, defaults to 15 seconds
+#[tokio::main]
+async fn main() -> Result<()> {
+    let canister_id = Principal::from_text(env::var("CATTS_ENGINE_CANISTER_ID")?)?;
+    let ic_url = env::var("IC_URL").unwrap_or(DEFAULT_IC_URL.to_string());
+    let database_path = env::var("DATABASE_PATH").unwrap_or(DEFAULT_DATABASE_PATH.to_string());
+    let poll_interval = env::var("POLL_INTERVAL_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+
+    let agent = Agent::builder().with_url(&ic_url).build()?;
+    if ic_url != DEFAULT_IC_URL {
+        // Local replicas use their own root key
+        agent.fetch_root_key().await?;
+    }
+    let client = AgentEngineClient { agent, canister_id };
+    let mut db = Database::open(&database_path)?;
+
+    loop {
+        match indexer::sync(&client, &mut db).await {
+            Ok(0) => {}
+            Ok(applied) => println!("Applied {} change log entries", applied),
+            Err(err) => eprintln!("Sync failed: {:?}", err),
+        }
+        tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+    }
+}
@@ -0,0 +1,90 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct HttpError {
+    pub code: u16,
+    pub message: String,
+    pub details: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub enum EngineResult<T> {
+    Ok(T),
+    Err(HttpError),
+}
+
+impl<T> From<EngineResult<T>> for Result<T, HttpError> {
+    fn from(result: EngineResult<T>) -> Self {
+        match result {
+            EngineResult::Ok(value) => Ok(value),
+            EngineResult::Err(err) => Err(err),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, CandidType)]
+pub enum ChangeLogTypeName {
+    Recipe,
+    Run,
+    User,
+}
+
+impl ChangeLogTypeName {
+    pub fn table(&self) -> &'static str {
+        match self {
+            ChangeLogTypeName::Recipe => "recipes",
+            ChangeLogTypeName::Run => "runs",
+            ChangeLogTypeName::User => "users",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, CandidType)]
+pub enum ChangeLogAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogItem {
+    pub type_name: ChangeLogTypeName,
+    pub id: String,
+    pub action: ChangeLogAction,
+    pub patch: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct IndexedChangeLogItem {
+    pub index: u32,
+    pub data: ChangeLogItem,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogResponse {
+    pub total_count: u32,
+    pub data: Vec<IndexedChangeLogItem>,
+    pub next_index: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogSnapshot {
+    pub index: u32,
+    pub created: u32,
+    pub entity_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogSnapshotEntity {
+    pub type_name: ChangeLogTypeName,
+    pub id: String,
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, CandidType)]
+pub struct ChangeLogSnapshotEntitiesResponse {
+    pub snapshot: ChangeLogSnapshot,
+    pub entities: Vec<ChangeLogSnapshotEntity>,
+    pub next_index: Option<u32>,
+}
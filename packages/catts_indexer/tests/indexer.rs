@@ -0,0 +1,116 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use candid::{encode_args, encode_one, Principal};
+use catts_engine_tests::{
+    common::{catts_update, setup},
+    recipes::recipe_eu_gtc_passport_clone,
+    siwe::full_login,
+    types::{Recipe, RpcResult},
+};
+use catts_indexer::{client::EngineClient, db::Database, indexer, types::ChangeLogTypeName};
+use futures::executor::block_on;
+use ic_agent::Identity;
+use pocket_ic::{PocketIc, WasmResult};
+
+struct PocketIcEngineClient<'a> {
+    ic: &'a PocketIc,
+    canister_id: Principal,
+}
+
+#[async_trait(?Send)]
+impl EngineClient for PocketIcEngineClient<'_> {
+    async fn query(&self, method: &str, args: Vec<u8>) -> Result<Vec<u8>> {
+        match self
+            .ic
+            .query_call(self.canister_id, Principal::anonymous(), method, args)
+        {
+            Ok(WasmResult::Reply(data)) => Ok(data),
+            Ok(WasmResult::Reject(message)) => Err(anyhow!(message)),
+            Err(err) => Err(anyhow!(err.to_string())),
+        }
+    }
+}
+
+#[test]
+fn indexer_sync() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let create_response: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let recipe = create_response.unwrap_ok().clone();
+    let recipe_id = format!("0x{}", hex::encode(recipe.id));
+
+    let client = PocketIcEngineClient {
+        ic: &ic,
+        canister_id: catts,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let database_path = dir.path().join("catts.sqlite");
+    let mut db = Database::open(&database_path).unwrap();
+
+    // User create and recipe create
+    assert_eq!(block_on(indexer::sync(&client, &mut db)).unwrap(), 2);
+    assert_eq!(db.count(&ChangeLogTypeName::User).unwrap(), 1);
+    let data = db
+        .get(&ChangeLogTypeName::Recipe, &recipe_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(data["publish_state"], "Draft");
+
+    let _: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_publish",
+        encode_one(recipe.id).unwrap(),
+    );
+
+    // Resumes from the persisted cursor
+    drop(db);
+    let mut db = Database::open(&database_path).unwrap();
+    assert_eq!(db.cursor().unwrap(), Some(2));
+    assert_eq!(block_on(indexer::sync(&client, &mut db)).unwrap(), 1);
+    let data = db
+        .get(&ChangeLogTypeName::Recipe, &recipe_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(data["publish_state"], "Published");
+    assert_eq!(db.count(&ChangeLogTypeName::Recipe).unwrap(), 1);
+}
+
+#[test]
+fn indexer_sync_from_snapshot() {
+    let (ic, siwe, catts) = setup();
+    let (_, identity) = full_login(&ic, siwe, catts, None);
+    let _: RpcResult<Recipe> = catts_update(
+        &ic,
+        catts,
+        identity.sender().unwrap(),
+        "recipe_create",
+        encode_args(recipe_eu_gtc_passport_clone()).unwrap(),
+    );
+    let _: RpcResult<catts_engine_tests::types::ChangeLogSnapshot> = catts_update(
+        &ic,
+        catts,
+        Principal::anonymous(),
+        "change_log_snapshot_create",
+        encode_one(()).unwrap(),
+    );
+
+    let client = PocketIcEngineClient {
+        ic: &ic,
+        canister_id: catts,
+    };
+    let mut db = Database::open_in_memory().unwrap();
+
+    // All state comes from the snapshot, no entries to replay
+    assert_eq!(block_on(indexer::sync(&client, &mut db)).unwrap(), 0);
+    assert_eq!(db.count(&ChangeLogTypeName::Recipe).unwrap(), 1);
+    assert_eq!(db.count(&ChangeLogTypeName::User).unwrap(), 1);
+    assert_eq!(db.cursor().unwrap(), Some(2));
+}